@@ -1,12 +1,15 @@
 use gtk4::prelude::*;
 use gtk4::{Application, gio};
+use crate::data::source_app::SourceApp;
 
 pub struct App {
     pub app: Application,
 }
 
 impl App {
-    pub fn new(url: Option<String>) -> Self {
+    /// `from_later` is the read-later item `url` came from; it leaves the queue
+    /// once the link is opened.
+    pub fn new(url: Option<String>, source: Option<SourceApp>, from_later: Option<i64>) -> Self {
         let app = Application::builder()
             .application_id("com.opennav.app")
             .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
@@ -14,7 +17,17 @@ impl App {
             
         let url_clone = url.clone();
         app.connect_activate(move |app| {
-            crate::ui::window::build_ui(app, url_clone.as_deref());
+            // Links matching a routing rule, and searches whose engine launches its
+            // preferred browser directly, skip the picker entirely
+            if let Some(url) = url_clone.as_deref() {
+                if crate::data::routing::try_route(url, source.as_ref()) || crate::data::routing::try_engine_route(url) {
+                    if let Some(id) = from_later {
+                        crate::data::read_later::mark_opened(id);
+                    }
+                    return;
+                }
+            }
+            crate::ui::window::build_ui(app, url_clone.as_deref(), source.as_ref(), from_later);
         });
         
         // When HANDLES_COMMAND_LINE is set, we must handle the command-line signal
@@ -0,0 +1,133 @@
+// DuckDuckGo-style "!bang" shortcuts, resolved locally.
+//
+// A small database ships with OpenNav (resources/bangs.json). "Update Bangs" in
+// Settings downloads DuckDuckGo's full list into the data dir, which then takes
+// precedence over the bundled file. Both use DuckDuckGo's bang.js format.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub const BANG_SOURCE_URL: &str = "https://duckduckgo.com/bang.js";
+const BUNDLED_BANGS: &str = include_str!("../../resources/bangs.json");
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct Bang {
+    pub trigger: String,
+    pub name: String,
+    pub url: String,            // "{}" is replaced by the query
+    pub domain: Option<String>, // Opened when the bang has no query
+}
+
+// One entry of bang.js
+#[derive(Deserialize)]
+struct RawBang {
+    t: String,
+    s: String,
+    u: String,
+    #[serde(default)]
+    d: Option<String>,
+}
+
+static BANGS: Lazy<RwLock<HashMap<String, Bang>>> = Lazy::new(|| RwLock::new(load()));
+
+fn user_bangs_path() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("opennav");
+    path.push("bangs.json");
+    path
+}
+
+fn parse(json: &str) -> Result<HashMap<String, Bang>> {
+    let raw: Vec<RawBang> = serde_json::from_str(json)?;
+    Ok(raw
+        .into_iter()
+        // Some bangs point at DuckDuckGo-internal relative paths
+        .filter(|b| b.u.starts_with("http") && !b.t.is_empty())
+        .map(|b| {
+            let trigger = b.t.to_lowercase();
+            let bang = Bang {
+                trigger: trigger.clone(),
+                name: b.s,
+                url: b.u.replace("{{{s}}}", "{}"),
+                domain: b.d.filter(|d| !d.is_empty()),
+            };
+            (trigger, bang)
+        })
+        .collect())
+}
+
+// Downloaded list if present and valid, otherwise the bundled one
+fn load() -> HashMap<String, Bang> {
+    let path = user_bangs_path();
+    if let Ok(json) = std::fs::read_to_string(&path) {
+        match parse(&json) {
+            Ok(bangs) if !bangs.is_empty() => return bangs,
+            Ok(_) => log::warn!("{:?} has no bangs, using bundled list", path),
+            Err(e) => log::warn!("Failed to read {:?}: {}, using bundled list", path, e),
+        }
+    }
+    parse(BUNDLED_BANGS).unwrap_or_default()
+}
+
+pub fn lookup(trigger: &str) -> Option<Bang> {
+    BANGS.read().ok()?.get(&trigger.to_lowercase()).cloned()
+}
+
+pub fn count() -> usize {
+    BANGS.read().map(|b| b.len()).unwrap_or(0)
+}
+
+/// Finds a bang token anywhere in the text: "!w rust" and "rust !w" both give ("w", "rust").
+/// The first bang wins; the query is the text with that token removed.
+pub fn find_bang(text: &str) -> Option<(String, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let index = words.iter().position(|w| w.len() > 1 && w.starts_with('!'))?;
+    let trigger = words[index][1..].to_lowercase();
+    let query = words
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, w)| *w)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((trigger, query))
+}
+
+/// Downloads DuckDuckGo's bang list, stores it in the data dir and reloads.
+/// Blocking; returns the number of bangs now available.
+pub fn update() -> Result<usize> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(UPDATE_TIMEOUT)
+        .user_agent(concat!("OpenNav/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let body = client
+        .get(BANG_SOURCE_URL)
+        .send()
+        .with_context(|| format!("Downloading {} failed", BANG_SOURCE_URL))?
+        .error_for_status()?
+        .text()?;
+
+    // Validate before replacing a working file
+    let bangs = parse(&body).context("Downloaded bang list is invalid")?;
+    if bangs.is_empty() {
+        bail!("Downloaded bang list is empty");
+    }
+
+    let path = user_bangs_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, body)?;
+
+    let count = bangs.len();
+    if let Ok(mut current) = BANGS.write() {
+        *current = bangs;
+    }
+    Ok(count)
+}
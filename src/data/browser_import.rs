@@ -0,0 +1,471 @@
+// Reads keyword searches already configured in installed browsers:
+// - Chromium-based: the `keywords` table of each profile's "Web Data" SQLite file
+// - Firefox: `search.json.mozlz4` (custom engines) and keyword bookmarks in `places.sqlite`
+//
+// Browser databases are locked while the browser runs, so they're copied (with
+// their -wal/-shm files, which hold recent changes) to temp files and read there.
+
+use anyhow::{anyhow, bail, Result};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use crate::data::opensearch;
+use crate::data::store::{EngineParam, SearchEngine};
+
+#[derive(Debug, Clone)]
+pub struct ImportCandidate {
+    pub engine: SearchEngine,
+    pub source: String, // e.g. "Chromium (Default)"
+}
+
+// (label, config dir relative to home) for Chromium-based browsers
+const CHROMIUM_DIRS: &[(&str, &str)] = &[
+    ("Chrome", ".config/google-chrome"),
+    ("Chromium", ".config/chromium"),
+    ("Brave", ".config/BraveSoftware/Brave-Browser"),
+    ("Edge", ".config/microsoft-edge"),
+    ("Vivaldi", ".config/vivaldi"),
+    ("Chrome (Flatpak)", ".var/app/com.google.Chrome/config/google-chrome"),
+    ("Chromium (Flatpak)", ".var/app/org.chromium.Chromium/config/chromium"),
+    ("Brave (Flatpak)", ".var/app/com.brave.Browser/config/BraveSoftware/Brave-Browser"),
+    ("Chromium (Snap)", "snap/chromium/common/chromium"),
+];
+
+// search.json is well under a megabyte even with many engines and icons
+const MOZLZ4_MAX_SIZE: usize = 16 * 1024 * 1024;
+
+const FIREFOX_DIRS: &[(&str, &str)] = &[
+    ("Firefox", ".mozilla/firefox"),
+    ("Firefox (Flatpak)", ".var/app/org.mozilla.firefox/.mozilla/firefox"),
+    ("Firefox (Snap)", "snap/firefox/common/.mozilla/firefox"),
+    ("LibreWolf", ".librewolf"),
+];
+
+/// Every importable keyword search found on this machine.
+pub fn find_candidates() -> Vec<ImportCandidate> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+
+    for (label, dir) in CHROMIUM_DIRS {
+        for profile in profile_dirs(&home.join(dir), "Web Data") {
+            let source = format!("{} ({})", label, dir_name(&profile));
+            match read_chromium_keywords(&profile.join("Web Data")) {
+                Ok(engines) => out.extend(engines.into_iter().map(|engine| ImportCandidate { engine, source: source.clone() })),
+                Err(e) => log::warn!("Skipping {}: {}", source, e),
+            }
+        }
+    }
+
+    for (label, dir) in FIREFOX_DIRS {
+        for profile in profile_dirs(&home.join(dir), "places.sqlite") {
+            let source = format!("{} ({})", label, dir_name(&profile));
+            let search_json = profile.join("search.json.mozlz4");
+            if search_json.exists() {
+                match read_firefox_search_json(&search_json) {
+                    Ok(engines) => out.extend(engines.into_iter().map(|engine| ImportCandidate { engine, source: source.clone() })),
+                    Err(e) => log::warn!("Skipping engines of {}: {}", source, e),
+                }
+            }
+            match read_firefox_keyword_bookmarks(&profile.join("places.sqlite")) {
+                Ok(engines) => out.extend(engines.into_iter().map(|engine| ImportCandidate { engine, source: source.clone() })),
+                Err(e) => log::warn!("Skipping keyword bookmarks of {}: {}", source, e),
+            }
+        }
+    }
+
+    out
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+// Subdirectories of `root` that contain `marker` (i.e. browser profiles)
+fn profile_dirs(root: &Path, marker: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(marker).is_file())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+// SQLite's companion files of a database in WAL mode
+const SQLITE_SUFFIXES: &[&str] = &["", "-wal", "-shm"];
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// Copies a (possibly locked) SQLite database and opens the copy. Not read-only:
+// SQLite may need to replay the copied WAL. Remove it with `remove_copy`.
+fn open_copy(path: &Path) -> Result<(Connection, PathBuf)> {
+    let tmp = std::env::temp_dir().join(format!("opennav-import-{}-{}.sqlite", std::process::id(), dir_name(path).replace(' ', "_")));
+    for suffix in SQLITE_SUFFIXES {
+        let (from, to) = (with_suffix(path, suffix), with_suffix(&tmp, suffix));
+        // Only the main file is required; the others exist while the browser runs
+        let copied = if suffix.is_empty() || from.exists() { std::fs::copy(&from, &to).map(|_| ()) } else { Ok(()) };
+        if let Err(e) = copied {
+            remove_copy(&tmp);
+            return Err(e.into());
+        }
+    }
+    match Connection::open(&tmp) {
+        Ok(conn) => Ok((conn, tmp)),
+        Err(e) => {
+            remove_copy(&tmp);
+            Err(e.into())
+        }
+    }
+}
+
+fn remove_copy(tmp: &Path) {
+    for suffix in SQLITE_SUFFIXES {
+        let _ = std::fs::remove_file(with_suffix(tmp, suffix));
+    }
+}
+
+// Chromium's placeholders: {searchTerms} plus a few {google:*} ones
+fn convert_chromium_template(template: &str) -> String {
+    let template = template
+        .replace("{google:baseURL}", "https://www.google.com/")
+        .replace("{google:baseSuggestURL}", "https://www.google.com/complete/");
+    opensearch::convert_template(&template)
+}
+
+// A row of Chromium's `keywords` table
+struct ChromiumKeyword {
+    name: String,
+    keyword: String,
+    url: String,
+    suggest_url: Option<String>,
+}
+
+pub fn read_chromium_keywords(web_data: &Path) -> Result<Vec<SearchEngine>> {
+    let (conn, tmp) = open_copy(web_data)?;
+
+    // User-defined (or user-edited) entries only; auto-generated site searches have safe_for_autoreplace = 1
+    let rows = || -> rusqlite::Result<Vec<ChromiumKeyword>> {
+        let mut stmt = conn.prepare(
+            "SELECT short_name, keyword, url, suggest_url FROM keywords WHERE safe_for_autoreplace = 0",
+        )?;
+        let mapped = stmt.query_map([], |row| {
+            Ok(ChromiumKeyword { name: row.get(0)?, keyword: row.get(1)?, url: row.get(2)?, suggest_url: row.get(3)? })
+        })?;
+        mapped.collect()
+    }();
+    drop(conn);
+    remove_copy(&tmp);
+
+    Ok(rows?
+        .into_iter()
+        .filter(|row| !row.keyword.is_empty() && row.url.contains("{searchTerms}"))
+        .map(|row| SearchEngine {
+            name: row.name,
+            keyword: row.keyword,
+            url: convert_chromium_template(&row.url),
+            icon_path: None,
+            suggest_url: row.suggest_url.filter(|s| !s.is_empty()).map(|s| convert_chromium_template(&s)),
+            ..Default::default()
+        })
+        .collect())
+}
+
+// Firefox's keyword placeholders: %s (encoded query) and %S (as typed)
+fn convert_firefox_placeholders(text: &str) -> String {
+    text.replace("%s", "{}").replace("%S", "{}")
+}
+
+// POST keywords keep their form data ("q=%s&lang=en") in post_data
+fn post_data_params(post_data: &str) -> Vec<EngineParam> {
+    url::form_urlencoded::parse(post_data.as_bytes())
+        .map(|(name, value)| EngineParam { name: name.into_owned(), value: convert_firefox_placeholders(&value) })
+        .collect()
+}
+
+// A keyword from moz_keywords with its bookmark
+struct KeywordBookmark {
+    keyword: String,
+    url: String,
+    title: Option<String>,
+    post_data: Option<String>,
+}
+
+pub fn read_firefox_keyword_bookmarks(places: &Path) -> Result<Vec<SearchEngine>> {
+    let (conn, tmp) = open_copy(places)?;
+
+    let rows = || -> rusqlite::Result<Vec<KeywordBookmark>> {
+        let mut stmt = conn.prepare(
+            "SELECT k.keyword, p.url,
+                    (SELECT b.title FROM moz_bookmarks b WHERE b.fk = p.id AND b.title IS NOT NULL LIMIT 1),
+                    k.post_data
+             FROM moz_keywords k JOIN moz_places p ON p.id = k.place_id",
+        )?;
+        let mapped = stmt.query_map([], |row| {
+            Ok(KeywordBookmark { keyword: row.get(0)?, url: row.get(1)?, title: row.get(2)?, post_data: row.get(3)? })
+        })?;
+        mapped.collect()
+    }();
+    drop(conn);
+    remove_copy(&tmp);
+
+    Ok(rows?
+        .into_iter()
+        .map(|row| {
+            let params = row.post_data.as_deref().map(post_data_params).unwrap_or_default();
+            SearchEngine {
+                name: row.title.filter(|t| !t.is_empty()).unwrap_or_else(|| row.keyword.clone()),
+                keyword: row.keyword,
+                url: convert_firefox_placeholders(&row.url),
+                icon_path: None,
+                suggest_url: None,
+                method: if params.is_empty() { "GET" } else { "POST" }.to_string(),
+                params,
+                ..Default::default()
+            }
+        })
+        .filter(|e| e.url.contains("{}") || e.params.iter().any(|p| p.value.contains("{}")))
+        .collect())
+}
+
+pub fn read_firefox_search_json(path: &Path) -> Result<Vec<SearchEngine>> {
+    let json = decompress_mozlz4(&std::fs::read(path)?)?;
+    let value: serde_json::Value = serde_json::from_slice(&json)?;
+    let engines = value
+        .get("engines")
+        .and_then(|e| e.as_array())
+        .ok_or_else(|| anyhow!("search.json has no engines"))?;
+
+    let mut out = Vec::new();
+    for engine in engines {
+        let name = engine.get("_name").and_then(|n| n.as_str()).unwrap_or_default();
+        let Some(urls) = engine.get("_urls").and_then(|u| u.as_array()) else {
+            continue; // Built-in engines are defined by Firefox itself, not in this file
+        };
+
+        // (method, template, params); POST params are form fields, GET ones go into the URL
+        let url_for = |kind: &str| {
+            urls.iter()
+                .find(|u| u.get("type").and_then(|t| t.as_str()).unwrap_or("text/html") == kind)
+                .map(|u| {
+                    let template = u.get("template").and_then(|t| t.as_str()).unwrap_or_default();
+                    let params: Vec<(String, String)> = u
+                        .get("params")
+                        .and_then(|p| p.as_array())
+                        .map(|ps| {
+                            ps.iter()
+                                .filter_map(|p| Some((p.get("name")?.as_str()?.to_string(), p.get("value")?.as_str()?.to_string())))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let is_post = u.get("method").and_then(|m| m.as_str()).is_some_and(|m| m.eq_ignore_ascii_case("POST"));
+                    if is_post {
+                        let fields: Vec<EngineParam> = params
+                            .into_iter()
+                            .map(|(name, value)| EngineParam { name, value: opensearch::convert_template(&value) })
+                            .collect();
+                        return ("POST", opensearch::convert_template(template), fields);
+                    }
+                    let mut full = template.to_string();
+                    for (n, v) in params {
+                        full.push(if full.contains('?') { '&' } else { '?' });
+                        full.push_str(&format!("{}={}", n, v));
+                    }
+                    ("GET", opensearch::convert_template(&full), Vec::new())
+                })
+        };
+
+        let Some((method, url, params)) =
+            url_for("text/html").filter(|(_, url, params)| url.contains("{}") || params.iter().any(|p| p.value.contains("{}")))
+        else {
+            continue;
+        };
+
+        let alias = engine
+            .get("_metaData")
+            .and_then(|m| m.get("alias"))
+            .and_then(|a| a.as_str())
+            .or_else(|| engine.get("_definedAliases").and_then(|a| a.as_array()).and_then(|a| a.first()).and_then(|a| a.as_str()))
+            .map(|a| a.trim_start_matches('@').to_string())
+            .filter(|a| !a.is_empty());
+        // No alias: derive one the user can change in the preview
+        let keyword = alias.unwrap_or_else(|| opensearch::keyword_from_name(name));
+        if keyword.is_empty() {
+            continue;
+        }
+
+        out.push(SearchEngine {
+            name: name.to_string(),
+            keyword,
+            url,
+            icon_path: None,
+            suggest_url: url_for("application/x-suggestions+json").map(|(_, url, _)| url),
+            method: method.to_string(),
+            params,
+            ..Default::default()
+        });
+    }
+    Ok(out)
+}
+
+/// Firefox's "mozLz40\0" container: magic, u32 LE decompressed size, one LZ4 block.
+pub fn decompress_mozlz4(data: &[u8]) -> Result<Vec<u8>> {
+    const MAGIC: &[u8] = b"mozLz40\0";
+    if data.len() < 12 || &data[..8] != MAGIC {
+        bail!("Not a mozlz4 file");
+    }
+    let size = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+    // The header is trusted for the allocation, so keep it to what search.json can be
+    if size > MOZLZ4_MAX_SIZE {
+        bail!("mozlz4 file too large ({} bytes)", size);
+    }
+    lz4_flex::block::decompress(&data[12..], size).map_err(|e| anyhow!("Corrupt LZ4 block: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A scratch file in the temp dir, removed with its SQLite companions on drop
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(std::env::temp_dir().join(format!("opennav-test-{}-{}", std::process::id(), name)))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            remove_copy(&self.0);
+        }
+    }
+
+    fn mozlz4(json: &[u8]) -> Vec<u8> {
+        let mut data = b"mozLz40\0".to_vec();
+        data.extend((json.len() as u32).to_le_bytes());
+        data.extend(lz4_flex::block::compress(json));
+        data
+    }
+
+    #[test]
+    fn decodes_mozlz4() {
+        // One literal-only LZ4 sequence: token 0xe0 (14 literals), then the literals
+        let mut fixture = b"mozLz40\0\x0e\0\0\0\xe0".to_vec();
+        fixture.extend(br#"{"engines":[]}"#);
+        assert_eq!(decompress_mozlz4(&fixture).unwrap(), br#"{"engines":[]}"#);
+
+        let json = br#"{"engines":[{"_name":"A"},{"_name":"B"}]}"#;
+        assert_eq!(decompress_mozlz4(&mozlz4(json)).unwrap(), json);
+    }
+
+    #[test]
+    fn rejects_bad_mozlz4_headers() {
+        assert!(decompress_mozlz4(b"").is_err());
+        assert!(decompress_mozlz4(b"mozLz40\0\x01").is_err());
+        let mut wrong_magic = mozlz4(b"{}");
+        wrong_magic[6] = b'5';
+        assert!(decompress_mozlz4(&wrong_magic).is_err());
+    }
+
+    #[test]
+    fn bounds_the_mozlz4_size() {
+        let mut huge = b"mozLz40\0".to_vec();
+        huge.extend((MOZLZ4_MAX_SIZE as u32 + 1).to_le_bytes());
+        huge.extend([0x10, b'x']);
+        assert!(decompress_mozlz4(&huge).unwrap_err().to_string().contains("too large"));
+
+        // A header claiming less than the block holds is corrupt, not truncated silently
+        let mut short = mozlz4(b"0123456789");
+        short[8] = 4;
+        assert!(decompress_mozlz4(&short).is_err());
+    }
+
+    #[test]
+    fn reads_firefox_search_json() {
+        let json = br#"{"engines":[
+            {"_name":"Google","_isAppProvided":true},
+            {"_name":"Rust Docs","_metaData":{"alias":"@rs"},"_urls":[
+                {"template":"https://doc.rust-lang.org/std/?search={searchTerms}"},
+                {"type":"application/x-suggestions+json","template":"https://s.example/?q={searchTerms}"}]},
+            {"_name":"Intranet Search","_urls":[
+                {"template":"https://intranet.example/find","method":"POST","params":[{"name":"q","value":"{searchTerms}"}]}]},
+            {"_name":"No Query","_urls":[{"template":"https://example.com/"}]}
+        ]}"#;
+        let file = TempFile::new("search.json.mozlz4");
+        std::fs::write(&file.0, mozlz4(json)).unwrap();
+        let engines = read_firefox_search_json(&file.0).unwrap();
+        assert_eq!(engines.len(), 2);
+
+        assert_eq!((engines[0].keyword.as_str(), engines[0].method.as_str()), ("rs", "GET"));
+        assert_eq!(engines[0].url, "https://doc.rust-lang.org/std/?search={}");
+        assert_eq!(engines[0].suggest_url.as_deref(), Some("https://s.example/?q={}"));
+
+        assert_eq!((engines[1].keyword.as_str(), engines[1].method.as_str()), ("intranet", "POST"));
+        assert_eq!(engines[1].params.len(), 1);
+        assert_eq!((engines[1].params[0].name.as_str(), engines[1].params[0].value.as_str()), ("q", "{}"));
+    }
+
+    #[test]
+    fn maps_chromium_keywords() {
+        let file = TempFile::new("Web Data");
+        let conn = Connection::open(&file.0).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE keywords (short_name TEXT, keyword TEXT, url TEXT, suggest_url TEXT, safe_for_autoreplace INTEGER);
+             INSERT INTO keywords VALUES ('Crates', 'cr', 'https://crates.io/search?q={searchTerms}', '', 0);
+             INSERT INTO keywords VALUES ('Google', 'g', '{google:baseURL}search?q={searchTerms}&{google:RLZ}', '{google:baseSuggestURL}search?q={searchTerms}', 0);
+             INSERT INTO keywords VALUES ('Auto Site', 'site.example', 'https://site.example/?q={searchTerms}', NULL, 1);
+             INSERT INTO keywords VALUES ('No Keyword', '', 'https://a.example/?q={searchTerms}', NULL, 0);
+             INSERT INTO keywords VALUES ('Homepage', 'home', 'https://home.example/', NULL, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let engines = read_chromium_keywords(&file.0).unwrap();
+        let summary: Vec<(&str, &str, &str, Option<&str>)> = engines
+            .iter()
+            .map(|e| (e.name.as_str(), e.keyword.as_str(), e.url.as_str(), e.suggest_url.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Crates", "cr", "https://crates.io/search?q={}", None),
+                ("Google", "g", "https://www.google.com/search?q={}&", Some("https://www.google.com/complete/search?q={}")),
+            ]
+        );
+        // The browser's file is read through a copy that is cleaned up
+        assert!(file.0.exists());
+        assert!(!std::env::temp_dir()
+            .join(format!("opennav-import-{}-{}.sqlite", std::process::id(), dir_name(&file.0).replace(' ', "_")))
+            .exists());
+    }
+
+    #[test]
+    fn maps_firefox_keyword_bookmarks() {
+        let file = TempFile::new("places.sqlite");
+        let conn = Connection::open(&file.0).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT);
+             CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, fk INTEGER, title TEXT);
+             CREATE TABLE moz_keywords (id INTEGER PRIMARY KEY, keyword TEXT, place_id INTEGER, post_data TEXT);
+             INSERT INTO moz_places VALUES (1, 'https://wiki.example/?q=%s'), (2, 'https://post.example/search'), (3, 'https://plain.example/');
+             INSERT INTO moz_bookmarks VALUES (1, 1, 'Wiki');
+             INSERT INTO moz_keywords VALUES (1, 'wk', 1, NULL), (2, 'ps', 2, 'q=%s&lang=en'), (3, 'pl', 3, NULL);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let engines = read_firefox_keyword_bookmarks(&file.0).unwrap();
+        assert_eq!(engines.len(), 2);
+        assert_eq!((engines[0].name.as_str(), engines[0].url.as_str(), engines[0].method.as_str()), ("Wiki", "https://wiki.example/?q={}", "GET"));
+        assert_eq!((engines[1].name.as_str(), engines[1].method.as_str()), ("ps", "POST"));
+        let params: Vec<(&str, &str)> = engines[1].params.iter().map(|p| (p.name.as_str(), p.value.as_str())).collect();
+        assert_eq!(params, vec![("q", "{}"), ("lang", "en")]);
+    }
+}
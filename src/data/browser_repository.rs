@@ -10,6 +10,28 @@ pub struct Browser {
     pub id: String, // desktop file id
     #[serde(default)]
     pub is_pinned: bool,
+    #[serde(default)]
+    pub usage_count: i64,
+    #[serde(default)]
+    pub keywords: Vec<String>, // Keywords= from the desktop file
+    #[serde(default)]
+    pub profile: Option<String>, // Profile passed on the Exec line, if any
+}
+
+// Extracts the profile name from a browser command line, e.g.
+// `chrome --profile-directory="Profile 1"` or `firefox -P Work`.
+fn profile_from_command(command: &str) -> Option<String> {
+    let args = shlex::split(command)?;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = arg.strip_prefix("--profile-directory=") {
+            return Some(value.to_string());
+        }
+        if arg == "-P" || arg == "--profile" || arg == "--profile-directory" {
+            return iter.next().filter(|v| !v.starts_with('%')).cloned();
+        }
+    }
+    None
 }
 
 pub fn get_installed_browsers() -> Vec<Browser> {
@@ -49,12 +71,18 @@ pub fn get_installed_browsers() -> Vec<Browser> {
                      "web-browser".to_string()
                  };
 
+                 let keywords = app_info.keywords().iter().map(|k| k.to_string()).collect();
+                 let profile = profile_from_command(&command);
+
                  let b = Browser {
                      name,
                      command,
                      icon: icon_str.clone(),
                      id: id_str,
                      is_pinned: false,
+                     usage_count: 0,
+                     keywords,
+                     profile,
                  };
                  browsers.push(b);
              }
@@ -70,12 +98,60 @@ pub fn get_installed_browsers() -> Vec<Browser> {
 }
 
 pub fn launch_browser(browser_id: &str, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    launch_browser_urls(browser_id, &[url])
+}
+
+// Command-line flag for a private window, by executable (or Flatpak app id)
+const PRIVATE_FLAGS: &[(&str, &str)] = &[
+    ("firefox", "--private-window"),
+    ("librewolf", "--private-window"),
+    ("waterfox", "--private-window"),
+    ("floorp", "--private-window"),
+    ("chrome", "--incognito"),
+    ("chromium", "--incognito"),
+    ("brave", "--incognito"),
+    ("vivaldi", "--incognito"),
+    ("msedge", "--inprivate"),
+    ("edge", "--inprivate"),
+    ("opera", "--private"),
+    ("epiphany", "--incognito-mode"),
+];
+
+/// The flag that opens `browser` in a private window, if it is a known browser.
+pub fn private_flag(browser: &Browser) -> Option<&'static str> {
+    let args = shlex::split(&browser.command)?;
+    args.iter()
+        .filter(|arg| !arg.starts_with('-') && !arg.starts_with('%'))
+        .find_map(|arg| {
+            // "/usr/bin/google-chrome-stable", "com.brave.Browser"
+            let arg = arg.to_lowercase();
+            PRIVATE_FLAGS
+                .iter()
+                .find(|(exe, _)| arg.split(['/', '.', '-']).any(|part| part == *exe))
+                .map(|(_, flag)| *flag)
+        })
+}
+
+// Opens several URLs (e.g. an engine group's searches) in one browser invocation
+pub fn launch_browser_urls(browser_id: &str, urls: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    launch_with_args(browser_id, urls, &[])
+}
+
+/// Opens URLs in a private window of the browser (see `private_flag`).
+pub fn launch_private(browser: &Browser, urls: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let flag = private_flag(browser).ok_or_else(|| format!("No private mode known for {}", browser.name))?;
+    launch_with_args(&browser.id, urls, &[flag])
+}
+
+// `extra_args` go before the URLs; they need the raw command line, so a browser
+// without one can't take them
+fn launch_with_args(browser_id: &str, urls: &[&str], extra_args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
     // Trim input
-    let url = url.trim();
+    let urls: Vec<&str> = urls.iter().map(|u| u.trim()).filter(|u| !u.is_empty()).collect();
 
     if let Some(app) = gtk4::gio::DesktopAppInfo::new(browser_id) {
         // CASE 1: Empty URL -> just launch the app
-        if url.is_empty() {
+        if urls.is_empty() && extra_args.is_empty() {
             let launch_context = gtk4::gio::AppLaunchContext::new();
             app.launch(&[], Some(&launch_context))?;
             return Ok(());
@@ -95,26 +171,29 @@ pub fn launch_browser(browser_id: &str, url: &str) -> Result<(), Box<dyn std::er
                     for arg in args.iter().skip(1) {
                         command.arg(arg);
                     }
+                    command.args(extra_args);
                     
                     // Smart Argument Handling
                     // 1. If it has a protocol (://), it's a URL.
                     // 2. If it has dots NO spaces (example.com), treat as domain -> prepend https://
                     // 3. Otherwise (spaces, no dots), treat as SEARCH -> https://google.com/search?q=...
                     
-                    let final_arg = if url.contains("://") {
-                        url.to_string()
-                    } else if url.contains(' ') || !url.contains('.') {
-                        // Treat as Search
-                        // TODO: Ideally configurable, defaulting to Google
-                        let query = url.replace(" ", "+");
-                        format!("https://www.google.com/search?q={}", query)
-                    } else {
-                        // Treat as Domain (e.g. "example.com", "localhost:3000")
-                        format!("https://{}", url)
-                    };
-
-                    // Append user input
-                    command.arg(final_arg);
+                    for url in &urls {
+                        let final_arg = if url.contains("://") {
+                            url.to_string()
+                        } else if url.contains(' ') || !url.contains('.') {
+                            // Treat as Search
+                            // TODO: Ideally configurable, defaulting to Google
+                            let query = url.replace(" ", "+");
+                            format!("https://www.google.com/search?q={}", query)
+                        } else {
+                            // Treat as Domain (e.g. "example.com", "localhost:3000")
+                            format!("https://{}", url)
+                        };
+
+                        // Append user input (browsers open each URL in its own tab)
+                        command.arg(final_arg);
+                    }
                     
                     // Detach process
                     let _ = command.spawn().map_err(|e| format!("Failed to spawn command: {}", e))?;
@@ -125,9 +204,11 @@ pub fn launch_browser(browser_id: &str, url: &str) -> Result<(), Box<dyn std::er
         
         // Fallback: Use launch_uris if raw command extraction fails (should rarely happen)
         // Note: launch_uris requires valid generic URIs, so "search query" might fail here.
+        if !extra_args.is_empty() {
+            return Err(format!("Cannot pass {} to {}", extra_args.join(" "), browser_id).into());
+        }
         let launch_context = gtk4::gio::AppLaunchContext::new();
-        let uris = vec![url];
-        app.launch_uris(&uris, Some(&launch_context))?;
+        app.launch_uris(&urls, Some(&launch_context))?;
         Ok(())
     } else {
         Err(format!("Browser {} not found", browser_id).into())
@@ -0,0 +1,433 @@
+// Portable configuration file: settings, engines, groups, pins and routing data as
+// versioned JSON, so a setup can be moved to another machine without copying data.db.
+//
+// Used by Settings (Export/Import buttons) and the CLI:
+//   opennav --export-config FILE
+//   opennav --import-config FILE [--replace] [--yes]
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+use crate::data::store::{DomainStat, EngineGroup, RoutingRule, SearchEngine, Store};
+
+pub const FORMAT: &str = "opennav-config";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortableConfig {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: i64,
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+    #[serde(default)]
+    pub engines: Vec<SearchEngine>,
+    #[serde(default)]
+    pub engine_groups: Vec<EngineGroup>,
+    #[serde(default)]
+    pub pinned_browsers: Vec<String>, // Desktop file ids
+    #[serde(default)]
+    pub routing_rules: Vec<RoutingRule>,
+    #[serde(default)]
+    pub domain_stats: Vec<DomainStat>,
+    #[serde(default)]
+    pub browser_schedules: HashMap<String, String>, // Desktop file id -> schedule
+}
+
+// Merge keeps everything already configured and adds/updates from the file;
+// Replace makes the configuration exactly what the file contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+/// Counts shown before an import is applied.
+#[derive(Debug, Default)]
+pub struct ImportPreview {
+    pub engines_new: usize,
+    pub engines_changed: usize,
+    pub engines_removed: usize, // Replace only
+    pub groups_new: usize,
+    pub groups_changed: usize,
+    pub rules_new: usize,
+    pub settings_changed: usize,
+    pub pins: usize,
+    pub schedules: usize,
+    pub domain_stats: usize,
+}
+
+impl ImportPreview {
+    pub fn lines(&self, mode: ImportMode) -> Vec<String> {
+        let mut lines = vec![
+            format!("Search engines: {} new, {} updated", self.engines_new, self.engines_changed),
+            format!("Engine groups: {} new, {} updated", self.groups_new, self.groups_changed),
+            format!("Routing rules: {} new", self.rules_new),
+            format!("Settings: {} changed", self.settings_changed),
+            format!("Pinned browsers: {}", self.pins),
+            format!("Browser schedules: {}", self.schedules),
+            format!("Per-site browser statistics: {} entries", self.domain_stats),
+        ];
+        if mode == ImportMode::Replace {
+            lines.push(format!(
+                "Replace removes everything not in the file, including {} search engines.",
+                self.engines_removed
+            ));
+        }
+        lines
+    }
+}
+
+pub fn export(store: &Store) -> Result<PortableConfig> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    let engines = store
+        .list_engines()?
+        .into_iter()
+        // Icons are cached files of this machine; they're fetched again on import
+        .map(|e| SearchEngine { icon_path: e.icon_path.filter(|p| !p.contains('/')), ..e })
+        .collect();
+
+    Ok(PortableConfig {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        exported_at: now,
+        settings: store.list_settings()?.into_iter().collect(),
+        engines,
+        engine_groups: store.list_engine_groups()?,
+        pinned_browsers: store.list_pinned()?,
+        routing_rules: store.list_routing_rules()?,
+        domain_stats: store.list_domain_stats()?,
+        browser_schedules: store.list_browser_schedules()?.into_iter().collect(),
+    })
+}
+
+pub fn write_file(config: &PortableConfig, path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(config)?;
+    std::fs::write(path, json).with_context(|| format!("Cannot write {}", path.display()))
+}
+
+pub fn read_file(path: &Path) -> Result<PortableConfig> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let config: PortableConfig = serde_json::from_str(&json).context("Not an OpenNav configuration file")?;
+    if config.format != FORMAT {
+        bail!("Not an OpenNav configuration file");
+    }
+    if config.version > VERSION {
+        bail!("The file was written by a newer OpenNav (format version {})", config.version);
+    }
+    Ok(config)
+}
+
+pub fn preview(store: &Store, config: &PortableConfig) -> Result<ImportPreview> {
+    let engines = store.list_engines()?;
+    let groups = store.list_engine_groups()?;
+    let rules = store.list_routing_rules()?;
+    let settings: HashMap<String, String> = store.list_settings()?.into_iter().collect();
+
+    let mut preview = ImportPreview::default();
+    for engine in &config.engines {
+        match engines.iter().find(|e| e.keyword == engine.keyword) {
+            None => preview.engines_new += 1,
+            Some(existing) if !same_engine(existing, engine) => preview.engines_changed += 1,
+            Some(_) => {}
+        }
+    }
+    let imported_keywords: HashSet<&str> = config.engines.iter().map(|e| e.keyword.as_str()).collect();
+    preview.engines_removed = engines.iter().filter(|e| !imported_keywords.contains(e.keyword.as_str())).count();
+
+    for group in &config.engine_groups {
+        match groups.iter().find(|g| g.keyword == group.keyword) {
+            None => preview.groups_new += 1,
+            Some(existing) if existing.name != group.name || existing.members != group.members => {
+                preview.groups_changed += 1
+            }
+            Some(_) => {}
+        }
+    }
+    preview.rules_new = config
+        .routing_rules
+        .iter()
+        .filter(|r| {
+            !rules
+                .iter()
+                .any(|e| {
+                    e.domain == r.domain
+                        && e.browser_id == r.browser_id
+                        && e.source_app == r.source_app
+                        && e.schedule == r.schedule
+                        && e.requires == r.requires
+                })
+        })
+        .count();
+    preview.settings_changed = config
+        .settings
+        .iter()
+        .filter(|(k, v)| settings.get(*k) != Some(*v))
+        .count();
+    preview.pins = config.pinned_browsers.len();
+    preview.schedules = config.browser_schedules.len();
+    preview.domain_stats = config.domain_stats.len();
+    Ok(preview)
+}
+
+fn same_engine(a: &SearchEngine, b: &SearchEngine) -> bool {
+    a.name == b.name
+        && a.url == b.url
+        && a.suggest_url == b.suggest_url
+        && a.is_post() == b.is_post()
+        && a.browser_id == b.browser_id
+        && a.launch_directly == b.launch_directly
+        && serde_json::to_string(&a.params).ok() == serde_json::to_string(&b.params).ok()
+}
+
+/// Applies the file. Engines keep their local icon unless the file names a theme icon.
+pub fn apply(store: &Store, config: &PortableConfig, mode: ImportMode) -> Result<()> {
+    let local_icons: HashMap<String, Option<String>> = store
+        .list_engines()?
+        .into_iter()
+        .map(|e| (e.keyword, e.icon_path))
+        .collect();
+    let engines: Vec<SearchEngine> = config
+        .engines
+        .iter()
+        .map(|e| SearchEngine {
+            icon_path: e.icon_path.clone().or_else(|| local_icons.get(&e.keyword).cloned().flatten()),
+            ..e.clone()
+        })
+        .collect();
+    let imported = PortableConfig { engines, ..config.clone() };
+    store.import_config(&imported, mode == ImportMode::Replace)?;
+    Ok(())
+}
+
+// --- CLI ---
+
+/// Handles --export-config / --import-config. Returns None when the arguments are
+/// not a config command, otherwise the process exit code.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let command = args.get(1)?.as_str();
+    if command != "--export-config" && command != "--import-config" {
+        return None;
+    }
+    let Some(path) = args.get(2) else {
+        eprintln!("Usage: opennav {} FILE", command);
+        return Some(2);
+    };
+    let result = if command == "--export-config" {
+        cli_export(Path::new(path))
+    } else {
+        let flags = &args[3..];
+        let mode = if flags.iter().any(|f| f == "--replace") { ImportMode::Replace } else { ImportMode::Merge };
+        let confirmed = flags.iter().any(|f| f == "--yes" || f == "-y");
+        cli_import(Path::new(path), mode, confirmed)
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("opennav: {:#}", e);
+            Some(1)
+        }
+    }
+}
+
+fn cli_export(path: &Path) -> Result<()> {
+    let store = Store::new()?;
+    write_file(&export(&store)?, path)?;
+    println!("Exported configuration to {}", path.display());
+    Ok(())
+}
+
+fn cli_import(path: &Path, mode: ImportMode, confirmed: bool) -> Result<()> {
+    let store = Store::new()?;
+    let config = read_file(path)?;
+    let preview = preview(&store, &config)?;
+
+    println!("Importing {} ({}):", path.display(), if mode == ImportMode::Replace { "replace" } else { "merge" });
+    for line in preview.lines(mode) {
+        println!("  {}", line);
+    }
+    if !confirmed {
+        print!("Apply? [y/N] ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+    apply(&store, &config, mode)?;
+    println!("Configuration imported.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords(store: &Store) -> Vec<String> {
+        let mut keywords: Vec<String> = store.list_engines().unwrap().into_iter().map(|e| e.keyword).collect();
+        keywords.sort();
+        keywords
+    }
+
+    // A store set up with one of everything an export carries
+    fn configured_store() -> Store {
+        let store = Store::in_memory().unwrap();
+        store.set_setting("search_engine", "rs").unwrap();
+        store
+            .add_engine(&SearchEngine {
+                name: "Rust Docs".to_string(),
+                keyword: "rs".to_string(),
+                url: "https://docs.rs/releases/search?query={}".to_string(),
+                icon_path: Some("/home/me/.local/share/opennav/icons/rs.png".to_string()),
+                browser_id: Some("firefox.desktop".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        store
+            .add_engine_group(&EngineGroup {
+                name: "Dev".to_string(),
+                keyword: "dev".to_string(),
+                members: vec!["rs".to_string(), "gh".to_string()],
+            })
+            .unwrap();
+        store
+            .add_routing_rule(&RoutingRule {
+                id: 0,
+                domain: "*.corp".to_string(),
+                browser_id: "chrome-work.desktop".to_string(),
+                source_app: None,
+                schedule: Some("Mon-Fri 09:00-18:00".to_string()),
+                requires: Some("iface:tun0".to_string()),
+            })
+            .unwrap();
+        store.toggle_pin("firefox.desktop").unwrap();
+        store.set_browser_schedule("chrome-work.desktop", "weekdays").unwrap();
+        store.record_launch("firefox.desktop", "https://github.com/rust-lang", "normal").unwrap();
+        store
+    }
+
+    // Through JSON, as the file would carry it
+    fn round_trip(config: &PortableConfig) -> PortableConfig {
+        let path = std::env::temp_dir().join(format!("opennav-config-test-{}-{}.json", std::process::id(), config.engines.len()));
+        write_file(config, &path).unwrap();
+        let read = read_file(&path);
+        let _ = std::fs::remove_file(&path);
+        read.unwrap()
+    }
+
+    fn assert_same_config(a: &Store, b: &Store) {
+        assert_eq!(keywords(a), keywords(b));
+        let sorted = |store: &Store| {
+            let mut settings = store.list_settings().unwrap();
+            settings.sort();
+            settings
+        };
+        assert_eq!(sorted(a), sorted(b));
+        assert_eq!(serde_json::to_value(a.list_engine_groups().unwrap()).unwrap(), serde_json::to_value(b.list_engine_groups().unwrap()).unwrap());
+        let rules = |store: &Store| -> Vec<_> {
+            store
+                .list_routing_rules()
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.domain, r.browser_id, r.source_app, r.schedule, r.requires))
+                .collect()
+        };
+        assert_eq!(rules(a), rules(b));
+        assert_eq!(a.list_pinned().unwrap(), b.list_pinned().unwrap());
+        assert_eq!(a.list_browser_schedules().unwrap(), b.list_browser_schedules().unwrap());
+        assert_eq!(serde_json::to_value(a.list_domain_stats().unwrap()).unwrap(), serde_json::to_value(b.list_domain_stats().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn export_round_trips_into_an_empty_store_by_merge() {
+        let source = configured_store();
+        let config = round_trip(&export(&source).unwrap());
+        assert_eq!((config.format.as_str(), config.version), (FORMAT, VERSION));
+        // Local icon files stay behind
+        assert_eq!(config.engines.iter().find(|e| e.keyword == "rs").unwrap().icon_path, None);
+
+        let target = Store::in_memory().unwrap();
+        let first = preview(&target, &config).unwrap();
+        assert_eq!((first.engines_new, first.groups_new, first.rules_new, first.pins), (1, 1, 1, 1));
+
+        apply(&target, &config, ImportMode::Merge).unwrap();
+        assert_same_config(&source, &target);
+
+        // Applying it again changes nothing
+        let again = preview(&target, &config).unwrap();
+        assert_eq!((again.engines_new, again.engines_changed, again.rules_new, again.settings_changed), (0, 0, 0, 0));
+        apply(&target, &config, ImportMode::Merge).unwrap();
+        assert_same_config(&source, &target);
+    }
+
+    #[test]
+    fn merge_keeps_what_is_not_in_the_file() {
+        let config = round_trip(&export(&configured_store()).unwrap());
+        let target = Store::in_memory().unwrap();
+        target
+            .add_engine(&SearchEngine {
+                name: "Local".to_string(),
+                keyword: "local".to_string(),
+                url: "https://local.example/?q={}".to_string(),
+                icon_path: Some("/icons/local.png".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        target.set_setting("theme", "dark").unwrap();
+
+        apply(&target, &config, ImportMode::Merge).unwrap();
+        assert!(keywords(&target).contains(&"local".to_string()));
+        assert!(keywords(&target).contains(&"rs".to_string()));
+        assert_eq!(target.get_setting("theme").unwrap().as_deref(), Some("dark"));
+        assert_eq!(target.get_setting("search_engine").unwrap().as_deref(), Some("rs"));
+    }
+
+    #[test]
+    fn replace_makes_the_store_match_the_file() {
+        let source = configured_store();
+        let config = round_trip(&export(&source).unwrap());
+        let target = Store::in_memory().unwrap();
+        target.set_setting("theme", "dark").unwrap();
+        target.toggle_pin("epiphany.desktop").unwrap();
+        target.record_launch("epiphany.desktop", "https://example.com/", "normal").unwrap();
+        let mut rs = source.list_engines().unwrap().into_iter().find(|e| e.keyword == "rs").unwrap();
+        rs.icon_path = Some("/icons/rs-local.png".to_string());
+        target.add_engine(&rs).unwrap();
+
+        assert_eq!(preview(&target, &config).unwrap().engines_removed, 0);
+
+        apply(&target, &config, ImportMode::Replace).unwrap();
+        assert_same_config(&source, &target);
+        assert_eq!(target.get_setting("theme").unwrap(), None);
+        // The local favicon of an engine in the file is kept
+        let icon = target.list_engines().unwrap().into_iter().find(|e| e.keyword == "rs").unwrap().icon_path;
+        assert_eq!(icon.as_deref(), Some("/icons/rs-local.png"));
+    }
+
+    #[test]
+    fn rejects_foreign_and_newer_files() {
+        let path = std::env::temp_dir().join(format!("opennav-config-test-{}-invalid.json", std::process::id()));
+        let read = |json: &str| {
+            std::fs::write(&path, json).unwrap();
+            read_file(&path)
+        };
+        let foreign = read(r#"{"format": "other", "version": 1}"#);
+        let newer = read(&format!(r#"{{"format": "{}", "version": {}}}"#, FORMAT, VERSION + 1));
+        let garbage = read("not json");
+        let minimal = read(&format!(r#"{{"format": "{}", "version": {}}}"#, FORMAT, VERSION));
+        let _ = std::fs::remove_file(&path);
+        assert!(foreign.is_err());
+        assert!(newer.unwrap_err().to_string().contains("newer OpenNav"));
+        assert!(garbage.is_err());
+        assert!(minimal.unwrap().engines.is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+// ~/.config/opennav/config.toml: declarative settings layered over the database,
+// meant to live in a dotfiles repository. Nothing here is ever written back.
+//
+//   default_engine = "d"
+//   hidden_browsers = ["org.gnome.Epiphany"]
+//
+//   [[engines]]
+//   name = "Rust Docs"
+//   keyword = "rs"
+//   url = "https://docs.rs/releases/search?query={}"
+//
+//   [[rules]]
+//   domain = "*.corp"
+//   browser = "google-chrome-work.desktop"
+//
+//   [[rules]]
+//   source = "slack"
+//   browser = "google-chrome-work.desktop"
+//   schedule = "Mon-Fri 09:00-18:00"
+//   requires = "iface:tun0"
+//
+//   [keybindings]
+//   history = "<Control>h"   # see ui::shortcuts::ACTIONS
+//
+// Engines and rules from the file win over database entries with the same keyword
+// or domain. The window reloads the file when it changes.
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use crate::data::store::{EngineParam, RoutingRule, SearchEngine};
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub default_engine: Option<String>, // Engine or group keyword
+    pub hidden_browsers: Vec<String>,   // Desktop ids, ".desktop" optional
+    pub engines: Vec<FileEngine>,
+    pub rules: Vec<FileRule>,
+    pub keybindings: BTreeMap<String, String>, // Action name -> GTK accelerator
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileEngine {
+    pub name: String,
+    pub keyword: String,
+    pub url: String,
+    #[serde(default)]
+    pub suggest_url: Option<String>,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub params: Vec<FileParam>,
+    #[serde(default)]
+    pub browser: Option<String>,
+    #[serde(default)]
+    pub launch_directly: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileParam {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileRule {
+    #[serde(default)]
+    pub domain: String,
+    pub browser: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<String>,
+    #[serde(default)]
+    pub requires: Option<String>,
+}
+
+impl FileEngine {
+    fn to_engine(&self) -> SearchEngine {
+        SearchEngine {
+            name: self.name.clone(),
+            keyword: self.keyword.clone(),
+            url: self.url.clone(),
+            icon_path: None,
+            suggest_url: self.suggest_url.clone(),
+            method: self.method.clone().unwrap_or_default().to_uppercase(),
+            params: self
+                .params
+                .iter()
+                .map(|p| EngineParam { name: p.name.clone(), value: p.value.clone() })
+                .collect(),
+            browser_id: self.browser.clone(),
+            launch_directly: self.launch_directly,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    config: ConfigFile,
+    error: Option<String>, // Last parse error; the previous config stays active
+}
+
+static STATE: Lazy<RwLock<State>> = Lazy::new(|| {
+    let mut state = State::default();
+    apply_load(&mut state);
+    RwLock::new(state)
+});
+
+pub fn config_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("opennav");
+    path.push("config.toml");
+    path
+}
+
+fn load() -> Result<ConfigFile> {
+    let path = config_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text).with_context(|| format!("{} is invalid", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+        Err(e) => Err(e).with_context(|| format!("Cannot read {}", path.display())),
+    }
+}
+
+// Returns true when the active config changed
+fn apply_load(state: &mut State) -> bool {
+    match load() {
+        Ok(config) => {
+            state.error = None;
+            if config != state.config {
+                state.config = config;
+                return true;
+            }
+            false
+        }
+        Err(e) => {
+            log::warn!("{:#}", e);
+            state.error = Some(format!("{:#}", e));
+            false
+        }
+    }
+}
+
+/// Re-reads config.toml. Returns true when anything changed.
+pub fn reload() -> bool {
+    STATE.write().map(|mut s| apply_load(&mut s)).unwrap_or(false)
+}
+
+pub fn current() -> ConfigFile {
+    STATE.read().map(|s| s.config.clone()).unwrap_or_default()
+}
+
+pub fn last_error() -> Option<String> {
+    STATE.read().ok().and_then(|s| s.error.clone())
+}
+
+pub fn default_engine() -> Option<String> {
+    current().default_engine.filter(|k| !k.trim().is_empty())
+}
+
+pub fn is_hidden(browser_id: &str) -> bool {
+    let id = browser_id.strip_suffix(".desktop").unwrap_or(browser_id);
+    STATE
+        .read()
+        .map(|s| {
+            s.config
+                .hidden_browsers
+                .iter()
+                .any(|h| h.strip_suffix(".desktop").unwrap_or(h) == id)
+        })
+        .unwrap_or(false)
+}
+
+pub fn keybindings() -> BTreeMap<String, String> {
+    current().keybindings
+}
+
+/// Database engines with the file's engines layered over them (same keyword: the file wins).
+pub fn layer_engines(engines: Vec<SearchEngine>) -> Vec<SearchEngine> {
+    layer_engines_from(&current(), engines)
+}
+
+fn layer_engines_from(config: &ConfigFile, mut engines: Vec<SearchEngine>) -> Vec<SearchEngine> {
+    for file_engine in &config.engines {
+        let engine = file_engine.to_engine();
+        match engines.iter_mut().find(|e| e.keyword == engine.keyword) {
+            // Keep the favicon fetched for the database entry
+            Some(existing) => *existing = SearchEngine { icon_path: existing.icon_path.take(), ..engine },
+            None => engines.push(engine),
+        }
+    }
+    engines
+}
+
+/// Database rules plus the file's. A file rule replaces a database rule for the
+/// same domain and source app.
+pub fn layer_rules(rules: Vec<RoutingRule>) -> Vec<RoutingRule> {
+    layer_rules_from(&current(), rules)
+}
+
+fn layer_rules_from(config: &ConfigFile, rules: Vec<RoutingRule>) -> Vec<RoutingRule> {
+    let file_rules: Vec<RoutingRule> = config
+        .rules
+        .iter()
+        .map(|f| {
+            RoutingRule {
+                id: 0,
+                domain: f.domain.clone(),
+                browser_id: f.browser.clone(),
+                source_app: f.source.clone(),
+                schedule: f.schedule.clone(),
+                requires: f.requires.clone(),
+            }
+            .normalized()
+        })
+        .collect();
+    let same_source = |a: &Option<String>, b: &Option<String>| match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (None, None) => true,
+        _ => false,
+    };
+    let mut layered: Vec<RoutingRule> = rules
+        .into_iter()
+        .map(RoutingRule::normalized)
+        .filter(|r| {
+            !file_rules
+                .iter()
+                .any(|f| f.domain.eq_ignore_ascii_case(&r.domain) && same_source(&f.source_app, &r.source_app))
+        })
+        .collect();
+    // id 0: not stored, so not deletable from Settings
+    layered.extend(file_rules);
+    layered
+}
+
+/// One-line description for Settings.
+pub fn summary() -> String {
+    let path = config_path();
+    if let Some(error) = last_error() {
+        return error;
+    }
+    if !path.exists() {
+        return format!("No {} (optional)", path.display());
+    }
+    let config = current();
+    format!(
+        "{}: {} engines, {} rules, {} hidden browsers, {} keybindings",
+        path.display(),
+        config.engines.len(),
+        config.rules.len(),
+        config.hidden_browsers.len(),
+        config.keybindings.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::store::Store;
+
+    const CONFIG: &str = r#"
+default_engine = "rs"
+hidden_browsers = ["org.gnome.Epiphany"]
+
+[[engines]]
+name = "Rust Docs"
+keyword = "rs"
+url = "https://docs.rs/releases/search?query={}"
+
+[[engines]]
+name = "GitHub Code"
+keyword = "gh"
+url = "https://github.com/search?type=code&q={}"
+method = "post"
+params = [{ name = "q", value = "{}" }]
+browser = "firefox.desktop"
+launch_directly = true
+
+[[rules]]
+domain = "*.corp"
+browser = "chrome-work.desktop"
+requires = "iface:tun0"
+
+[[rules]]
+domain = "github.com"
+source = "Slack"
+browser = "chrome-work.desktop"
+schedule = "Mon-Fri 09:00-18:00"
+
+[keybindings]
+history = "<Control>h"
+"#;
+
+    fn rule(domain: &str, browser_id: &str, source_app: Option<&str>) -> RoutingRule {
+        RoutingRule {
+            id: 1,
+            domain: domain.to_string(),
+            browser_id: browser_id.to_string(),
+            source_app: source_app.map(str::to_string),
+            schedule: None,
+            requires: None,
+        }
+    }
+
+    #[test]
+    fn parses_the_file() {
+        let config: ConfigFile = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.default_engine.as_deref(), Some("rs"));
+        assert_eq!(config.hidden_browsers, vec!["org.gnome.Epiphany"]);
+        assert_eq!(config.engines.len(), 2);
+        assert_eq!(config.rules[0].domain, "*.corp");
+        assert_eq!(config.rules[1].source.as_deref(), Some("Slack"));
+        assert_eq!(config.keybindings.get("history").map(String::as_str), Some("<Control>h"));
+
+        let engine = config.engines[1].to_engine();
+        assert!(engine.is_post());
+        assert_eq!(engine.method, "POST");
+        assert_eq!((engine.params[0].name.as_str(), engine.params[0].value.as_str()), ("q", "{}"));
+        assert_eq!(engine.browser_id.as_deref(), Some("firefox.desktop"));
+        assert!(engine.launch_directly);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<ConfigFile>("default_engne = \"rs\"").is_err());
+        assert!(toml::from_str::<ConfigFile>("[[rules]]\ndomain = \"a.example\"\nbrowser = \"b\"\nbrowsr = \"c\"").is_err());
+        assert!(toml::from_str::<ConfigFile>("[[rules]]\ndomain = \"a.example\"").is_err());
+        assert_eq!(toml::from_str::<ConfigFile>("").unwrap(), ConfigFile::default());
+    }
+
+    #[test]
+    fn file_engines_override_stored_ones_by_keyword() {
+        let config: ConfigFile = toml::from_str(CONFIG).unwrap();
+        let store = Store::in_memory().unwrap();
+        let mut stored = store.list_engines().unwrap();
+        let stored_count = stored.len();
+        stored.iter_mut().find(|e| e.keyword == "gh").unwrap().icon_path = Some("/icons/gh.png".to_string());
+
+        let engines = layer_engines_from(&config, stored);
+        assert_eq!(engines.len(), stored_count + 1);
+        let gh = engines.iter().find(|e| e.keyword == "gh").unwrap();
+        assert_eq!((gh.name.as_str(), gh.url.as_str()), ("GitHub Code", "https://github.com/search?type=code&q={}"));
+        assert_eq!(gh.icon_path.as_deref(), Some("/icons/gh.png"));
+        assert!(engines.iter().any(|e| e.keyword == "rs" && e.name == "Rust Docs"));
+        assert!(engines.iter().any(|e| e.keyword == "g"));
+    }
+
+    #[test]
+    fn file_rules_replace_stored_rules_by_domain_and_source_app() {
+        let config: ConfigFile = toml::from_str(CONFIG).unwrap();
+        let store = Store::in_memory().unwrap();
+        for stored in [
+            rule("*.CORP", "firefox.desktop", None),                // replaced: same domain, no source
+            rule("*.corp", "firefox.desktop", Some("thunderbird")), // kept: other source app
+            rule("github.com", "firefox.desktop", None),            // kept: the file rule names a source
+            rule("github.com", "firefox.desktop", Some("slack")),   // replaced: same domain and source
+            rule("docs.rs", "firefox.desktop", Some("")),           // kept, blank source unset
+        ] {
+            store.add_routing_rule(&stored).unwrap();
+        }
+
+        let rules = layer_rules_from(&config, store.list_routing_rules().unwrap());
+        let summary: Vec<(&str, &str, Option<&str>, i64)> = rules
+            .iter()
+            .map(|r| (r.domain.as_str(), r.browser_id.as_str(), r.source_app.as_deref(), r.id))
+            .collect();
+        assert_eq!(summary.len(), 5);
+        for kept in [
+            ("*.corp", "firefox.desktop", Some("thunderbird")),
+            ("github.com", "firefox.desktop", None),
+            ("docs.rs", "firefox.desktop", None),
+        ] {
+            assert!(summary.iter().any(|r| (r.0, r.1, r.2) == kept && r.3 != 0), "{:?} in {:?}", kept, summary);
+        }
+        assert!(summary.contains(&("*.corp", "chrome-work.desktop", None, 0)));
+        assert!(summary.contains(&("github.com", "chrome-work.desktop", Some("Slack"), 0)));
+        assert!(!summary.iter().any(|r| r.0 == "*.CORP"));
+        assert!(!summary.iter().any(|r| r.2 == Some("slack")));
+    }
+
+    #[test]
+    fn empty_file_changes_nothing() {
+        let config = ConfigFile::default();
+        let store = Store::in_memory().unwrap();
+        store.add_routing_rule(&rule("github.com", "firefox.desktop", None)).unwrap();
+        assert_eq!(layer_engines_from(&config, store.list_engines().unwrap()).len(), store.list_engines().unwrap().len());
+        assert_eq!(layer_rules_from(&config, store.list_routing_rules().unwrap()).len(), 1);
+    }
+}
@@ -0,0 +1,395 @@
+// Links opened later in a chosen browser ("Open at 9:55", "Open in 1 hour").
+// They wait in the scheduled_opens table until something fires them:
+//
+//   opennav --daemon     stays running and opens links when they are due
+//   opennav --fire-due   opens what is due and exits; run by the systemd user
+//                        timer generated for each link when no daemon is running
+
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::data::browser_repository;
+use crate::data::store::{now, ScheduledOpen, Store};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+// Longest delay accepted for "+..."
+const MAX_DELAY: i64 = 365 * SECS_PER_DAY;
+// Upper bound between checks, so links scheduled after the daemon started are seen
+const DAEMON_POLL: Duration = Duration::from_secs(20);
+const UNIT_PREFIX: &str = "opennav-open-";
+// What a browser started from a user service needs to reach the graphical session
+const SESSION_ENV: &[&str] = &["DISPLAY", "WAYLAND_DISPLAY", "XAUTHORITY", "DBUS_SESSION_BUS_ADDRESS"];
+// Signals that stop the daemon (Ctrl+C, kill)
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
+// Seconds since local midnight
+fn seconds_today() -> Option<i64> {
+    let now = gtk4::glib::DateTime::now_local().ok()?;
+    Some((now.hour() * 3600 + now.minute() * 60 + now.second()) as i64)
+}
+
+/// Due time for "09:55" (today, or tomorrow once it has passed) or a delay such
+/// as "+90m", "+1h" or "+1h30m".
+pub fn parse_due(text: &str) -> Result<i64, String> {
+    let seconds_today = seconds_today().ok_or("Cannot read the local time")?;
+    parse_due_at(text, now(), seconds_today)
+}
+
+fn parse_due_at(text: &str, now: i64, seconds_today: i64) -> Result<i64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Give a time like 09:55 or a delay like +1h".to_string());
+    }
+    if let Some(delay) = text.strip_prefix('+') {
+        return parse_delay(delay).map(|secs| now + secs);
+    }
+
+    let invalid = || format!("Invalid time \"{}\", expected e.g. 09:55 or +1h", text);
+    let (h, m) = text.split_once(':').ok_or_else(invalid)?;
+    let h: i64 = h.trim().parse().map_err(|_| invalid())?;
+    let m: i64 = m.trim().parse().map_err(|_| invalid())?;
+    if h > 23 || m > 59 || h < 0 || m < 0 {
+        return Err(invalid());
+    }
+    let mut due = now - seconds_today + h * 3600 + m * 60;
+    if due <= now {
+        due += SECS_PER_DAY;
+    }
+    Ok(due)
+}
+
+fn parse_delay(text: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid delay \"+{}\", expected e.g. +30m, +1h or +1h30m", text);
+    // Adds number * unit seconds, failing on overflow or past MAX_DELAY
+    let add = |total: i64, number: &str, unit: i64| {
+        number
+            .parse::<i64>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit))
+            .and_then(|secs| secs.checked_add(total))
+            .filter(|&total| total <= MAX_DELAY)
+            .ok_or_else(invalid)
+    };
+    let mut total = 0;
+    let mut number = String::new();
+    for c in text.trim().chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' if !number.is_empty() => {
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = add(total, &number, unit)?;
+                number.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    // A bare number means minutes
+    if !number.is_empty() {
+        total = add(total, &number, 60)?;
+    }
+    if total <= 0 {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// "09:55", or "Tue 09:55" when it isn't today.
+pub fn format_due(due_at: i64) -> String {
+    let (Ok(due), Ok(now)) = (gtk4::glib::DateTime::from_unix_local(due_at), gtk4::glib::DateTime::now_local()) else {
+        return due_at.to_string();
+    };
+    let same_day = (due.year(), due.day_of_year()) == (now.year(), now.day_of_year());
+    due.format(if same_day { "%H:%M" } else { "%a %H:%M" }).map(|s| s.to_string()).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Daemon,
+    Timer,
+}
+
+/// Saves the link and makes sure something will open it: the running daemon,
+/// else a systemd user timer.
+pub fn schedule(url: &str, browser_id: &str, due_at: i64) -> Result<Trigger> {
+    let store = Store::new()?;
+    let id = store.add_scheduled_open(url, browser_id, due_at)?;
+    if daemon_pid().is_some() {
+        return Ok(Trigger::Daemon);
+    }
+    match install_timer(id, due_at) {
+        Ok(()) => Ok(Trigger::Timer),
+        Err(e) => {
+            let _ = store.claim_scheduled_open(id);
+            Err(e.context("No OpenNav daemon is running and no systemd timer could be set up; start `opennav --daemon`"))
+        }
+    }
+}
+
+/// Opens every link that is due. Returns how many were opened. A link whose
+/// browser fails to start moves to the read-later list instead of being lost.
+/// One link failing doesn't hold up the others.
+pub fn fire_due() -> Result<usize> {
+    let store = Store::new()?;
+    let now = now();
+    let mut opened = 0;
+    for item in store.list_scheduled_opens()?.iter().filter(|i| i.due_at <= now) {
+        match store.claim_scheduled_open(item.id) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                log::warn!("Cannot claim scheduled link {}: {:#}", item.id, e);
+                continue;
+            }
+        }
+        remove_timer(item.id);
+        match browser_repository::launch_browser(&item.browser_id, &item.url) {
+            Ok(()) => {
+                let _ = store.record_launch(&item.browser_id, &item.url, "scheduled");
+                opened += 1;
+            }
+            Err(e) => {
+                log::warn!("Scheduled open of {} in {} failed: {}", item.url, item.browser_id, e);
+                // Dropped rather than put back: a due link would be retried on every wake
+                if let Err(e) = store.add_reading_item(&item.url, None, None) {
+                    log::error!("Cannot save {} for later, dropping it: {:#}", item.url, e);
+                }
+            }
+        }
+    }
+    Ok(opened)
+}
+
+fn next_due(items: &[ScheduledOpen]) -> Option<i64> {
+    items.iter().map(|i| i.due_at).min()
+}
+
+// The pid file stays locked while the daemon runs, so a file left behind by a
+// killed daemon (or a pid reused since) is never taken for a running one
+fn run_daemon() -> Result<()> {
+    let pid_file = pid_file();
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&pid_file)
+        .with_context(|| format!("Cannot open {}", pid_file.display()))?;
+    if file.try_lock().is_err() {
+        match daemon_pid() {
+            Some(pid) => bail!("The daemon is already running (pid {})", pid),
+            None => bail!("The daemon is already running"),
+        }
+    }
+    file.set_len(0)?;
+    write!(file, "{}", std::process::id()).with_context(|| format!("Cannot write {}", pid_file.display()))?;
+
+    // Links are fired on their own thread; this one waits for Ctrl+C or kill
+    // and removes the pid file on the way out
+    let main_loop = gtk4::glib::MainLoop::new(None, false);
+    for signal in [SIGINT, SIGTERM] {
+        let main_loop = main_loop.clone();
+        gtk4::glib::unix_signal_add_local_once(signal, move || main_loop.quit());
+    }
+    std::thread::spawn(fire_loop);
+    println!("Opening scheduled links as they become due (Ctrl+C to stop)");
+    main_loop.run();
+    let _ = std::fs::remove_file(&pid_file);
+    Ok(())
+}
+
+fn fire_loop() {
+    loop {
+        if let Err(e) = fire_due() {
+            log::warn!("Cannot open scheduled links: {:#}", e);
+        }
+        let wait = Store::new()
+            .and_then(|s| s.list_scheduled_opens())
+            .ok()
+            .and_then(|items| next_due(&items))
+            .map(|due| Duration::from_secs((due - now()).clamp(1, DAEMON_POLL.as_secs() as i64) as u64))
+            .unwrap_or(DAEMON_POLL);
+        std::thread::sleep(wait);
+    }
+}
+
+fn pid_file() -> PathBuf {
+    dirs::runtime_dir().unwrap_or_else(std::env::temp_dir).join("opennav-daemon.pid")
+}
+
+// Another process's daemon pid, if one holds the pid file's lock
+fn daemon_pid() -> Option<u32> {
+    let file = std::fs::File::open(pid_file()).ok()?;
+    if file.try_lock_shared().is_ok() {
+        return None;
+    }
+    let pid: u32 = std::fs::read_to_string(pid_file()).ok()?.trim().parse().ok()?;
+    (pid != std::process::id()).then_some(pid)
+}
+
+fn units_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("systemd").join("user"))
+}
+
+/// The executable to start another opennav with. Inside an AppImage, the image
+/// itself: the mounted executable is gone once this instance exits.
+pub fn self_exe() -> Result<PathBuf> {
+    match std::env::var_os("APPIMAGE") {
+        Some(image) => Ok(PathBuf::from(image)),
+        None => std::env::current_exe().context("Cannot find the opennav executable"),
+    }
+}
+
+// The command the timer runs
+fn fire_command() -> Result<String> {
+    let exe = self_exe()?;
+    // % starts a systemd specifier
+    Ok(format!("\"{}\" --fire-due", exe.display().to_string().replace('%', "%%")))
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let status = std::process::Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .context("Cannot run systemctl")?;
+    if !status.success() {
+        bail!("systemctl --user {} failed ({})", args.join(" "), status);
+    }
+    Ok(())
+}
+
+// User services don't inherit the session's environment; hand them ours (best
+// effort, a session that already exports it keeps working)
+fn import_session_env() {
+    let vars: Vec<&str> = SESSION_ENV.iter().copied().filter(|v| std::env::var_os(v).is_some()).collect();
+    if vars.is_empty() {
+        return;
+    }
+    let mut args = vec!["import-environment"];
+    args.extend(vars);
+    if let Err(e) = systemctl(&args) {
+        log::warn!("Cannot pass the session environment to systemd: {:#}", e);
+    }
+}
+
+// Persistent: a link due while the machine was off opens at the next login
+fn install_timer(id: i64, due_at: i64) -> Result<()> {
+    let dir = units_dir().context("No config directory")?;
+    std::fs::create_dir_all(&dir)?;
+    let calendar = gtk4::glib::DateTime::from_unix_local(due_at)
+        .and_then(|d| d.format("%Y-%m-%d %H:%M:%S"))
+        .context("Cannot format the due time")?;
+    let name = format!("{}{}", UNIT_PREFIX, id);
+
+    let service = format!(
+        "[Unit]\nDescription=OpenNav scheduled link {id}\n\n[Service]\nType=oneshot\nExecStart={}\n",
+        fire_command()?
+    );
+    let timer = format!(
+        "[Unit]\nDescription=Open OpenNav scheduled link {id} at {calendar}\n\n[Timer]\nOnCalendar={calendar}\nAccuracySec=1s\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n"
+    );
+    std::fs::write(dir.join(format!("{}.service", name)), service)?;
+    std::fs::write(dir.join(format!("{}.timer", name)), timer)?;
+    import_session_env();
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", &format!("{}.timer", name)])
+}
+
+// Best effort: the timer has elapsed (or the daemon got there first)
+fn remove_timer(id: i64) {
+    let Some(dir) = units_dir() else {
+        return;
+    };
+    let name = format!("{}{}", UNIT_PREFIX, id);
+    let timer = dir.join(format!("{}.timer", name));
+    if !timer.exists() {
+        return;
+    }
+    let _ = systemctl(&["disable", "--now", &format!("{}.timer", name)]);
+    let _ = std::fs::remove_file(timer);
+    let _ = std::fs::remove_file(dir.join(format!("{}.service", name)));
+    let _ = systemctl(&["daemon-reload"]);
+}
+
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let result = match args.get(1)?.as_str() {
+        "--daemon" => run_daemon(),
+        "--fire-due" => fire_due().map(|_| ()),
+        _ => return None,
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("opennav: {:#}", e);
+            Some(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const TEN_AM: i64 = 10 * 3600;
+
+    fn due(text: &str) -> Result<i64, String> {
+        parse_due_at(text, NOW, TEN_AM)
+    }
+
+    #[test]
+    fn time_later_today() {
+        assert_eq!(due("10:30"), Ok(NOW + 30 * 60));
+        assert_eq!(due(" 23:59 "), Ok(NOW + 13 * 3600 + 59 * 60));
+    }
+
+    #[test]
+    fn time_already_passed_rolls_to_tomorrow() {
+        assert_eq!(due("09:55"), Ok(NOW - 5 * 60 + SECS_PER_DAY));
+        assert_eq!(due("10:00"), Ok(NOW + SECS_PER_DAY));
+        assert_eq!(due("00:00"), Ok(NOW - TEN_AM + SECS_PER_DAY));
+    }
+
+    #[test]
+    fn delays() {
+        assert_eq!(due("+1h30m"), Ok(NOW + 90 * 60));
+        assert_eq!(due("+90m"), Ok(NOW + 90 * 60));
+        assert_eq!(due("+45s"), Ok(NOW + 45));
+        assert_eq!(parse_delay("2h"), Ok(2 * 3600));
+    }
+
+    #[test]
+    fn bare_number_means_minutes() {
+        assert_eq!(parse_delay("45"), Ok(45 * 60));
+        assert_eq!(parse_delay("1h15"), Ok(75 * 60));
+        assert_eq!(due("+10"), Ok(NOW + 10 * 60));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for text in ["", "+0", "+0m", "+h", "+1x", "+", "25:00", "10:60", "-1:00", "9.55", "tomorrow"] {
+            assert!(due(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_delays() {
+        for text in ["99999999999999999h", "9223372036854775807m", "99999999999999999999", "1h9223372036854775807s"] {
+            assert!(parse_delay(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn caps_delay_at_a_year() {
+        assert_eq!(parse_delay("8760h"), Ok(MAX_DELAY));
+        assert!(parse_delay("8760h1s").is_err());
+        assert!(parse_delay("8761h").is_err());
+        assert!(due("+525601").is_err());
+    }
+}
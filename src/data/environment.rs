@@ -0,0 +1,245 @@
+// Machine-state conditions for routing rules, read locally from /sys and the
+// environment. A condition list is comma-separated and every entry must hold:
+//
+//   iface:tun0        network interface exists and is up ("wg*" matches any wg interface)
+//   host:work-laptop  hostname
+//   env:CORP_VPN      environment variable is set and not empty
+//   env:PROFILE=work  environment variable has this value
+//
+// A leading "!" negates an entry, e.g. "!iface:tun0" for a VPN-down fallback.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+const NET_DIR: &str = "/sys/class/net";
+const IFF_UP: u32 = 0x1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Check {
+    Interface(String),
+    Hostname(String),
+    EnvSet(String),
+    EnvEquals(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    negated: bool,
+    check: Check,
+}
+
+fn parse(text: &str) -> Result<Vec<Condition>, String> {
+    let conditions: Vec<Condition> = text
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(parse_one)
+        .collect::<Result<_, _>>()?;
+    if conditions.is_empty() {
+        return Err("No conditions given".to_string());
+    }
+    Ok(conditions)
+}
+
+fn parse_one(text: &str) -> Result<Condition, String> {
+    let (negated, text) = match text.strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, text),
+    };
+    let (kind, value) = text
+        .split_once(':')
+        .ok_or_else(|| format!("\"{}\": expected iface:, host: or env:", text))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("\"{}\" is missing a value", text));
+    }
+    let check = match kind.trim().to_lowercase().as_str() {
+        "iface" | "interface" => Check::Interface(value.to_string()),
+        "host" | "hostname" => Check::Hostname(value.to_lowercase()),
+        "env" => match value.split_once('=') {
+            Some((name, expected)) => Check::EnvEquals(name.trim().to_string(), expected.trim().to_string()),
+            None => Check::EnvSet(value.to_string()),
+        },
+        other => return Err(format!("Unknown condition \"{}\", expected iface, host or env", other)),
+    };
+    Ok(Condition { negated, check })
+}
+
+/// Checks a condition list without evaluating it, for the rule editor.
+pub fn validate(text: &str) -> Result<(), String> {
+    parse(text).map(|_| ())
+}
+
+/// The first entry that does not hold, described for the user, or None when all
+/// hold. Invalid lists never hold.
+pub fn unmet(text: &str) -> Option<String> {
+    unmet_on(text, &Machine::current())
+}
+
+fn unmet_on(text: &str, machine: &Machine) -> Option<String> {
+    let conditions = match parse(text) {
+        Ok(c) => c,
+        Err(e) => return Some(e),
+    };
+    conditions.iter().find(|c| machine.holds(&c.check) == c.negated).map(describe_unmet)
+}
+
+pub fn holds_all(text: &str) -> bool {
+    unmet(text).is_none()
+}
+
+// What conditions are checked against
+struct Machine {
+    up_interfaces: Vec<String>,
+    hostname: Option<String>,
+    env: HashMap<String, String>,
+}
+
+impl Machine {
+    fn current() -> Self {
+        Machine {
+            up_interfaces: up_interfaces(),
+            hostname: hostname(),
+            env: std::env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+                .collect(),
+        }
+    }
+
+    fn holds(&self, check: &Check) -> bool {
+        match check {
+            Check::Interface(pattern) => self.up_interfaces.iter().any(|name| interface_matches(pattern, name)),
+            Check::Hostname(name) => self.hostname.as_ref() == Some(name),
+            Check::EnvSet(name) => self.env.get(name).is_some_and(|v| !v.is_empty()),
+            Check::EnvEquals(name, expected) => self.env.get(name) == Some(expected),
+        }
+    }
+}
+
+fn describe_unmet(condition: &Condition) -> String {
+    match (&condition.check, condition.negated) {
+        (Check::Interface(name), false) => format!("network interface {} is not up", name),
+        (Check::Interface(name), true) => format!("network interface {} is up", name),
+        (Check::Hostname(name), false) => format!("this machine is not {}", name),
+        (Check::Hostname(name), true) => format!("this machine is {}", name),
+        (Check::EnvSet(name), false) => format!("{} is not set", name),
+        (Check::EnvSet(name), true) => format!("{} is set", name),
+        (Check::EnvEquals(name, value), false) => format!("{} is not \"{}\"", name, value),
+        (Check::EnvEquals(name, value), true) => format!("{} is \"{}\"", name, value),
+    }
+}
+
+// "tun0" or a prefix pattern like "wg*"
+fn interface_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+// Interfaces that exist with IFF_UP set
+fn up_interfaces() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(NET_DIR) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| is_up(&entry.path()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+fn is_up(interface_dir: &Path) -> bool {
+    std::fs::read_to_string(interface_dir.join("flags"))
+        .ok()
+        .and_then(|flags| u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok())
+        .is_some_and(|flags| flags & IFF_UP != 0)
+}
+
+fn hostname() -> Option<String> {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|h| h.trim().to_lowercase())
+        .filter(|h| !h.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine() -> Machine {
+        Machine {
+            up_interfaces: vec!["lo".to_string(), "wlp3s0".to_string(), "wg-corp".to_string()],
+            hostname: Some("work-laptop".to_string()),
+            env: HashMap::from([
+                ("PROFILE".to_string(), "work".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]),
+        }
+    }
+
+    fn check(negated: bool, check: Check) -> Condition {
+        Condition { negated, check }
+    }
+
+    #[test]
+    fn parses_each_kind() {
+        assert_eq!(
+            parse("iface:tun0, host:Work-Laptop, env:CORP_VPN, env:PROFILE = work").unwrap(),
+            vec![
+                check(false, Check::Interface("tun0".to_string())),
+                check(false, Check::Hostname("work-laptop".to_string())),
+                check(false, Check::EnvSet("CORP_VPN".to_string())),
+                check(false, Check::EnvEquals("PROFILE".to_string(), "work".to_string())),
+            ]
+        );
+        assert_eq!(parse("interface:wg*").unwrap(), vec![check(false, Check::Interface("wg*".to_string()))]);
+        assert_eq!(parse("hostname:box").unwrap(), vec![check(false, Check::Hostname("box".to_string()))]);
+    }
+
+    #[test]
+    fn parses_negation() {
+        assert_eq!(parse("!iface:tun0").unwrap(), vec![check(true, Check::Interface("tun0".to_string()))]);
+        assert_eq!(parse("! env:CI").unwrap(), vec![check(true, Check::EnvSet("CI".to_string()))]);
+    }
+
+    #[test]
+    fn rejects_malformed_lists() {
+        for text in ["", " , ", "tun0", "iface:", "host: ", "vpn:tun0", "!", "iface:tun0,bogus"] {
+            assert!(validate(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn interfaces_match_by_name_or_prefix() {
+        let machine = machine();
+        assert_eq!(unmet_on("iface:wlp3s0", &machine), None);
+        assert_eq!(unmet_on("iface:wg*", &machine), None);
+        assert_eq!(unmet_on("iface:tun0", &machine), Some("network interface tun0 is not up".to_string()));
+        assert!(unmet_on("iface:wlp3", &machine).is_some());
+        assert_eq!(unmet_on("!iface:tun0", &machine), None);
+        assert_eq!(unmet_on("!iface:wg*", &machine), Some("network interface wg* is up".to_string()));
+    }
+
+    #[test]
+    fn hostname_and_environment() {
+        let machine = machine();
+        assert_eq!(unmet_on("host:work-laptop, env:PROFILE=work", &machine), None);
+        assert_eq!(unmet_on("host:home", &machine), Some("this machine is not home".to_string()));
+        assert_eq!(unmet_on("env:PROFILE", &machine), None);
+        assert_eq!(unmet_on("env:EMPTY", &machine), Some("EMPTY is not set".to_string()));
+        assert_eq!(unmet_on("env:PROFILE=home", &machine), Some("PROFILE is not \"home\"".to_string()));
+        assert_eq!(unmet_on("!env:MISSING", &machine), None);
+    }
+
+    #[test]
+    fn reports_the_first_unmet_entry() {
+        let machine = machine();
+        assert_eq!(
+            unmet_on("host:work-laptop, iface:tun0, env:MISSING", &machine),
+            Some("network interface tun0 is not up".to_string())
+        );
+        assert!(unmet_on("iface", &machine).is_some());
+    }
+}
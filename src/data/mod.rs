@@ -1,2 +1,17 @@
+pub mod bangs;
 pub mod browser_repository;
+pub mod browser_import;
+pub mod config_export;
+pub mod config_file;
+pub mod delayed_open;
+pub mod environment;
+pub mod opensearch;
+pub mod post_form;
+pub mod read_later;
+pub mod routing;
+pub mod schedule;
+pub mod search;
+pub mod share;
+pub mod source_app;
 pub mod store;
+pub mod suggest;
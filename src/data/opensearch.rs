@@ -0,0 +1,476 @@
+// OpenSearch description import.
+//
+// Accepts a description URL, a web page that advertises one via
+// <link rel="search" type="application/opensearchdescription+xml">, or a local .xml file.
+// Both are read with quick-xml in a lenient mode, which also copes with HTML pages
+// when discovering the link.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use quick_xml::escape::resolve_html5_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::data::store::{EngineParam, SearchEngine};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const OPENSEARCH_TYPE: &str = "application/opensearchdescription+xml";
+
+#[derive(Debug, Clone, Default)]
+pub struct OpenSearchDescription {
+    pub short_name: String,
+    pub template: String, // converted to OpenNav's "{}" placeholder
+    pub method: String, // "GET" or "POST"
+    pub params: Vec<EngineParam>, // POST form fields; GET parameters are part of template
+    pub suggest_template: Option<String>,
+    pub image: Option<String>, // URL or data: URI
+}
+
+impl OpenSearchDescription {
+    pub fn to_engine(&self, keyword: &str, icon_path: Option<String>) -> SearchEngine {
+        SearchEngine {
+            name: self.short_name.clone(),
+            keyword: keyword.to_string(),
+            url: self.template.clone(),
+            icon_path,
+            suggest_url: self.suggest_template.clone(),
+            method: self.method.clone(),
+            params: self.params.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn suggested_keyword(&self) -> String {
+        keyword_from_name(&self.short_name)
+    }
+}
+
+/// A starting keyword for the user to adjust, e.g. "Wikipedia (en)" -> "wikipedia".
+pub fn keyword_from_name(name: &str) -> String {
+    name.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .take(10)
+        .collect()
+}
+
+// --- Tag reading ---
+
+#[derive(Debug)]
+enum Token {
+    Start { name: String, attrs: Vec<(String, String)>, self_closing: bool },
+    End(String),
+    Text(String),
+}
+
+// Element and attribute names are matched case-insensitively
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_lowercase()
+}
+
+fn start_token(tag: &BytesStart, self_closing: bool) -> Token {
+    let attrs = tag
+        .html_attributes()
+        .flatten()
+        .map(|a| {
+            let value = a.unescape_value_with(resolve_html5_entity).map(|v| v.into_owned());
+            (local_name(a.key.local_name().as_ref()), value.unwrap_or_else(|_| String::from_utf8_lossy(&a.value).into_owned()))
+        })
+        .collect();
+    Token::Start { name: local_name(tag.local_name().as_ref()), attrs, self_closing }
+}
+
+// quick-xml without end-tag checks, so unclosed HTML tags don't stop it either
+fn tokenize(doc: &str) -> Vec<Token> {
+    let mut reader = Reader::from_str(doc);
+    let config = reader.config_mut();
+    config.trim_text(true);
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+
+    let mut tokens = Vec::new();
+    loop {
+        let token = match reader.read_event() {
+            Ok(Event::Start(tag)) => start_token(&tag, false),
+            Ok(Event::Empty(tag)) => start_token(&tag, true),
+            Ok(Event::End(tag)) => Token::End(local_name(tag.local_name().as_ref())),
+            Ok(Event::Text(text)) => Token::Text(match text.unescape_with(resolve_html5_entity) {
+                Ok(t) => t.into_owned(),
+                Err(_) => String::from_utf8_lossy(&text).into_owned(),
+            }),
+            Ok(Event::CData(data)) => Token::Text(String::from_utf8_lossy(&data).trim().to_string()),
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+// --- Description parsing ---
+
+/// "{searchTerms}" becomes "{}"; optional parameters ("{startPage?}") are dropped
+/// and the common required ones get fixed values.
+pub fn convert_template(template: &str) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            out.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+        let param = &rest[open + 1..open + close];
+        let name = param.trim_end_matches('?').rsplit(':').next().unwrap_or(param);
+        let replacement = match name {
+            "searchTerms" => "{}",
+            "inputEncoding" | "outputEncoding" => "UTF-8",
+            "language" => "*",
+            "count" => "20",
+            "startIndex" | "startPage" if !param.ends_with('?') => "1",
+            _ => "",
+        };
+        out.push_str(replacement);
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn append_params(template: &str, params: &[(String, String)]) -> String {
+    let mut url = template.to_string();
+    for (name, value) in params {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(name);
+        url.push('=');
+        url.push_str(value);
+    }
+    url
+}
+
+// A <Url> element with its <Param> children
+struct UrlEntry {
+    kind: String,
+    method: String,
+    template: String,
+    params: Vec<(String, String)>,
+}
+
+pub fn parse_description(xml: &str) -> Result<OpenSearchDescription> {
+    let mut desc = OpenSearchDescription::default();
+    let mut capture: Option<&'static str> = None;
+    let mut image_width = 0u32;
+    let mut pending_image_width = 0u32;
+    let mut saw_root = false;
+
+    let mut current_url: Option<UrlEntry> = None;
+    let mut urls = Vec::new();
+
+    for token in tokenize(xml) {
+        match token {
+            Token::Start { name, attrs, self_closing } => match name.as_str() {
+                "opensearchdescription" => saw_root = true,
+                "shortname" => capture = Some("shortname"),
+                "image" => {
+                    capture = Some("image");
+                    pending_image_width = attr(&attrs, "width").and_then(|w| w.parse().ok()).unwrap_or(16);
+                }
+                "url" => {
+                    let entry = UrlEntry {
+                        kind: attr(&attrs, "type").unwrap_or("text/html").to_lowercase(),
+                        method: attr(&attrs, "method").unwrap_or("get").to_lowercase(),
+                        template: attr(&attrs, "template").unwrap_or_default().to_string(),
+                        params: Vec::new(),
+                    };
+                    if self_closing {
+                        urls.push(entry);
+                    } else {
+                        current_url = Some(entry);
+                    }
+                }
+                "param" => {
+                    if let (Some(url), Some(n), Some(v)) = (current_url.as_mut(), attr(&attrs, "name"), attr(&attrs, "value")) {
+                        url.params.push((n.to_string(), v.to_string()));
+                    }
+                }
+                _ => {}
+            },
+            Token::Text(text) => match capture {
+                Some("shortname") => desc.short_name = text,
+                Some("image") if pending_image_width >= image_width => {
+                    desc.image = Some(text);
+                    image_width = pending_image_width;
+                }
+                _ => {}
+            },
+            Token::End(name) => {
+                if name == "url" {
+                    if let Some(url) = current_url.take() {
+                        urls.push(url);
+                    }
+                }
+                capture = None;
+            }
+        }
+    }
+
+    if !saw_root {
+        bail!("Not an OpenSearch description");
+    }
+
+    for UrlEntry { kind, method, template, params } in urls {
+        match kind.as_str() {
+            // The <Param>s become form fields, sent by the page post_form writes
+            "text/html" if desc.template.is_empty() && method == "post" => {
+                desc.method = "POST".to_string();
+                desc.template = convert_template(&template);
+                desc.params = params
+                    .iter()
+                    .map(|(name, value)| EngineParam { name: name.clone(), value: convert_template(value) })
+                    .collect();
+            }
+            "text/html" if desc.template.is_empty() => {
+                desc.method = "GET".to_string();
+                desc.template = convert_template(&append_params(&template, &params));
+            }
+            "application/x-suggestions+json" if desc.suggest_template.is_none() => {
+                desc.suggest_template = Some(convert_template(&append_params(&template, &params)));
+            }
+            _ => {}
+        }
+    }
+
+    if desc.short_name.is_empty() {
+        bail!("OpenSearch description has no ShortName");
+    }
+    if !desc.template.contains("{}") && !desc.params.iter().any(|p| p.value.contains("{}")) {
+        bail!("OpenSearch description has no text/html search template");
+    }
+    Ok(desc)
+}
+
+/// Description URLs advertised by an HTML page, resolved against `base`.
+pub fn find_description_links(html: &str, base: &url::Url) -> Vec<String> {
+    tokenize(html)
+        .into_iter()
+        .filter_map(|t| match t {
+            Token::Start { name, attrs, .. } if name == "link" => Some(attrs),
+            _ => None,
+        })
+        .filter(|attrs| {
+            let rel_is_search = attr(attrs, "rel").is_some_and(|r| r.split_whitespace().any(|p| p.eq_ignore_ascii_case("search")));
+            let type_matches = attr(attrs, "type").is_some_and(|t| t.eq_ignore_ascii_case(OPENSEARCH_TYPE));
+            rel_is_search && type_matches
+        })
+        .filter_map(|attrs| attr(&attrs, "href").and_then(|h| base.join(h).ok()).map(|u| u.to_string()))
+        .collect()
+}
+
+// --- Loading ---
+
+fn http_get(url: &str) -> Result<String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("OpenNav/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let body = client
+        .get(url)
+        .send()
+        .with_context(|| format!("Could not fetch {}", url))?
+        .error_for_status()?
+        .text()?;
+    Ok(body)
+}
+
+fn local_path(input: &str) -> Option<PathBuf> {
+    if let Some(path) = input.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+    let path = Path::new(input);
+    (!input.contains("://") && path.exists()).then(|| path.to_path_buf())
+}
+
+/// Loads a description from a local file, a description URL, or a page linking to one.
+pub fn load(input: &str) -> Result<OpenSearchDescription> {
+    let input = input.trim();
+    if let Some(path) = local_path(input) {
+        let xml = std::fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))?;
+        return parse_description(&xml);
+    }
+
+    let page_url = if input.contains("://") { input.to_string() } else { format!("https://{}", input) };
+    let body = http_get(&page_url)?;
+    if let Ok(desc) = parse_description(&body) {
+        return Ok(desc);
+    }
+
+    let base = url::Url::parse(&page_url)?;
+    let link = find_description_links(&body, &base)
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No OpenSearch description found at {}", page_url))?;
+    parse_description(&http_get(&link)?)
+}
+
+// --- Engine icon ---
+
+/// Saves the description's Image (data: URI or URL) as the engine icon and returns its path.
+pub fn save_image(image: &str, keyword: &str) -> Result<String> {
+    let (bytes, mime) = if let Some(data) = image.strip_prefix("data:") {
+        let (meta, payload) = data.split_once(',').ok_or_else(|| anyhow!("Malformed data URI"))?;
+        let bytes = if meta.ends_with(";base64") {
+            let payload: String = payload.split_ascii_whitespace().collect();
+            base64::engine::general_purpose::STANDARD.decode(payload).context("Invalid base64 image")?
+        } else {
+            percent_encoding::percent_decode_str(payload).collect()
+        };
+        (bytes, meta.split(';').next().unwrap_or_default().to_string())
+    } else {
+        let client = reqwest::blocking::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+        let response = client.get(image).send()?.error_for_status()?;
+        let mime = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        (response.bytes()?.to_vec(), mime)
+    };
+
+    let ext = match mime.as_str() {
+        "image/png" => "png",
+        "image/svg+xml" => "svg",
+        "image/gif" => "gif",
+        "image/jpeg" => "jpg",
+        "image/webp" => "webp",
+        _ => "ico",
+    };
+
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("opennav");
+    path.push("icons");
+    std::fs::create_dir_all(&path)?;
+    let safe_keyword: String = keyword.chars().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_').collect();
+    path.push(format!("opensearch-{}.{}", safe_keyword, ext));
+    std::fs::write(&path, bytes)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIKIPEDIA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/" xmlns:moz="http://www.mozilla.org/2006/browser/search/">
+  <ShortName>Wikipedia (en)</ShortName>
+  <Description>Wikipedia (en)</Description>
+  <Image height="16" width="16" type="image/x-icon">https://en.wikipedia.org/static/favicon/wikipedia.ico</Image>
+  <Url type="text/html" method="get" template="https://en.wikipedia.org/w/index.php?title=Special:Search&amp;search={searchTerms}"/>
+  <Url type="application/x-suggestions+json" method="get" template="https://en.wikipedia.org/w/api.php?action=opensearch&amp;search={searchTerms}&amp;namespace=0"/>
+  <moz:SearchForm>https://en.wikipedia.org/wiki/Special:Search</moz:SearchForm>
+</OpenSearchDescription>"#;
+
+    #[test]
+    fn parses_a_get_description() {
+        let desc = parse_description(WIKIPEDIA).unwrap();
+        assert_eq!(desc.short_name, "Wikipedia (en)");
+        assert_eq!(desc.method, "GET");
+        assert_eq!(desc.template, "https://en.wikipedia.org/w/index.php?title=Special:Search&search={}");
+        assert_eq!(
+            desc.suggest_template.as_deref(),
+            Some("https://en.wikipedia.org/w/api.php?action=opensearch&search={}&namespace=0")
+        );
+        assert_eq!(desc.image.as_deref(), Some("https://en.wikipedia.org/static/favicon/wikipedia.ico"));
+        assert!(desc.params.is_empty());
+        assert_eq!(desc.suggested_keyword(), "wikipedia");
+    }
+
+    #[test]
+    fn parses_a_post_description() {
+        let xml = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Intranet</ShortName>
+  <Url type="text/html" method="POST" template="https://intranet.example/search">
+    <Param name="q" value="{searchTerms}"/>
+    <Param name="lang" value="{language}"/>
+    <Param name="source" value="opensearch"/>
+  </Url>
+</OpenSearchDescription>"#;
+        let desc = parse_description(xml).unwrap();
+        assert_eq!(desc.method, "POST");
+        assert_eq!(desc.template, "https://intranet.example/search");
+        let params: Vec<(&str, &str)> = desc.params.iter().map(|p| (p.name.as_str(), p.value.as_str())).collect();
+        assert_eq!(params, vec![("q", "{}"), ("lang", "*"), ("source", "opensearch")]);
+        let engine = desc.to_engine("intra", None);
+        assert!(engine.is_post());
+        assert_eq!(engine.params.len(), 3);
+    }
+
+    #[test]
+    fn get_params_are_appended_to_the_template() {
+        let xml = r#"<OpenSearchDescription><ShortName>Shop</ShortName>
+  <Url type="text/html" template="https://shop.example/find"><Param name="q" value="{searchTerms}"/><Param name="cat" value="all"/></Url>
+</OpenSearchDescription>"#;
+        assert_eq!(parse_description(xml).unwrap().template, "https://shop.example/find?q={}&cat=all");
+    }
+
+    #[test]
+    fn parses_namespaced_elements() {
+        let xml = r#"<os:OpenSearchDescription xmlns:os="http://a9.com/-/spec/opensearch/1.1/">
+  <os:ShortName>Docs</os:ShortName>
+  <os:Url type="text/html" template="https://docs.example/?q={os:searchTerms}"/>
+</os:OpenSearchDescription>"#;
+        let desc = parse_description(xml).unwrap();
+        assert_eq!(desc.short_name, "Docs");
+        assert_eq!(desc.template, "https://docs.example/?q={}");
+    }
+
+    #[test]
+    fn converts_template_parameters() {
+        assert_eq!(convert_template("https://s/?q={searchTerms}&p={startPage?}"), "https://s/?q={}&p=");
+        assert_eq!(convert_template("https://s/?q={searchTerms}&p={startPage}&n={count}"), "https://s/?q={}&p=1&n=20");
+        assert_eq!(convert_template("https://s/?q={searchTerms}&ie={inputEncoding}&x={custom:thing?}"), "https://s/?q={}&ie=UTF-8&x=");
+        assert_eq!(convert_template("https://s/?q={searchTerms"), "https://s/?q={searchTerms");
+    }
+
+    #[test]
+    fn rejects_incomplete_descriptions() {
+        assert!(parse_description("<html><body>Not it</body></html>").is_err());
+        assert!(parse_description(r#"<OpenSearchDescription><Url type="text/html" template="https://s/?q={searchTerms}"/></OpenSearchDescription>"#).is_err());
+        assert!(parse_description(r#"<OpenSearchDescription><ShortName>S</ShortName><Url type="application/x-suggestions+json" template="https://s/?q={searchTerms}"/></OpenSearchDescription>"#).is_err());
+    }
+
+    #[test]
+    fn finds_description_links_in_html() {
+        let html = r#"<!DOCTYPE html><html><head>
+<link rel="stylesheet" href="/style.css">
+<link rel="search" type="application/opensearchdescription+xml" href="/opensearch.xml" title="Site">
+<link rel="alternate search" type="application/OpenSearchDescription+xml" href="other/desc.xml">
+<link rel="search" type="text/html" href="/search">
+</head><body><p>Unclosed<br></body></html>"#;
+        let base = url::Url::parse("https://example.com/docs/index.html").unwrap();
+        assert_eq!(
+            find_description_links(html, &base),
+            vec!["https://example.com/opensearch.xml", "https://example.com/docs/other/desc.xml"]
+        );
+    }
+
+    #[test]
+    fn loads_a_local_file() {
+        let path = std::env::temp_dir().join(format!("opennav-opensearch-test-{}.xml", std::process::id()));
+        std::fs::write(&path, WIKIPEDIA).unwrap();
+        let from_path = load(path.to_str().unwrap());
+        let from_url = load(&format!("file://{}", path.display()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(from_path.unwrap().short_name, "Wikipedia (en)");
+        assert_eq!(from_url.unwrap().template, "https://en.wikipedia.org/w/index.php?title=Special:Search&search={}");
+    }
+}
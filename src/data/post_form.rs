@@ -0,0 +1,107 @@
+// POST searches: browsers can't be handed a POST request on the command line, so
+// OpenNav writes a small page that submits the form itself and opens that instead.
+
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// Pages are only needed until the browser has loaded them
+const PAGE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+fn pages_dir() -> PathBuf {
+    let mut path = dirs::cache_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("opennav");
+    path.push("post");
+    path
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// A page that POSTs `fields` to `action` as soon as it loads
+fn post_page_html(action: &str, fields: &[(String, String)]) -> String {
+    let inputs: String = fields
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "<input type=\"hidden\" name=\"{}\" value=\"{}\">\n",
+                escape_html(name),
+                escape_html(value)
+            )
+        })
+        .collect();
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Searching…</title></head>\n\
+         <body onload=\"document.forms[0].submit()\">\n\
+         <form method=\"post\" action=\"{}\">\n{}<noscript><button type=\"submit\">Search</button></noscript>\n</form>\n\
+         </body></html>\n",
+        escape_html(action),
+        inputs
+    )
+}
+
+/// Writes an auto-submitting page that POSTs `fields` to `action` and returns its file:// URL.
+pub fn write_post_page(action: &str, fields: &[(String, String)]) -> Result<String> {
+    let dir = pages_dir();
+    std::fs::create_dir_all(&dir)?;
+    remove_old_pages(&dir);
+    let html = post_page_html(action, fields);
+
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let path = dir.join(format!("search-{}.html", nanos));
+    std::fs::write(&path, html)?;
+
+    Ok(url::Url::from_file_path(&path)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| format!("file://{}", path.display())))
+}
+
+fn remove_old_pages(dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > PAGE_MAX_AGE);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_html_special_characters() {
+        assert_eq!(escape_html(r#"<a href="x">Tom & Jerry's</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(escape_html("&amp;"), "&amp;amp;");
+        assert_eq!(escape_html("plain text"), "plain text");
+    }
+
+    #[test]
+    fn page_posts_escaped_fields() {
+        let fields = vec![
+            ("q".to_string(), "\"><script>alert(1)</script>".to_string()),
+            ("lang".to_string(), "en".to_string()),
+        ];
+        let html = post_page_html("https://example.com/search?a=1&b=2", &fields);
+        assert!(html.contains("<form method=\"post\" action=\"https://example.com/search?a=1&amp;b=2\">"), "{}", html);
+        assert!(html.contains("<input type=\"hidden\" name=\"q\" value=\"&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;\">"), "{}", html);
+        assert!(html.contains("<input type=\"hidden\" name=\"lang\" value=\"en\">"), "{}", html);
+        assert!(!html.contains("<script>"));
+    }
+}
@@ -0,0 +1,106 @@
+// Read-later queue: links saved with Ctrl+D (or the share menu) instead of being
+// opened, kept in the reading_list table until opened or deleted.
+//
+// Also on the command line:
+//   opennav later list
+//   opennav later open ID
+//
+// A saved link only leaves the queue once a browser was launched for it; the
+// picker is started with `--from-later ID` so that cancelling it keeps the link.
+
+use anyhow::{bail, Context, Result};
+use crate::data::routing;
+use crate::data::source_app::SourceApp;
+use crate::data::store::{self, format_age, ReadingItem, Store};
+
+/// The app the item's link came from, for source-app routing rules.
+pub fn source_app(item: &ReadingItem) -> Option<SourceApp> {
+    item.source_app.as_deref().and_then(SourceApp::from_id)
+}
+
+/// Title, or the URL for items saved before it could be fetched.
+pub fn display_title(item: &ReadingItem) -> &str {
+    item.title.as_deref().unwrap_or(&item.url)
+}
+
+/// Takes a saved link out of the queue once it has been opened.
+pub fn mark_opened(id: i64) {
+    if let Err(e) = Store::new().and_then(|s| s.delete_reading_item(id)) {
+        log::warn!("Cannot remove saved link {} from the queue: {}", id, e);
+    }
+}
+
+fn find_item(store: &Store, id: &str) -> Result<ReadingItem> {
+    let id: i64 = id.parse().with_context(|| format!("\"{}\" is not an item number, see `opennav later list`", id))?;
+    let Some(item) = store.list_reading_items()?.into_iter().find(|i| i.id == id) else {
+        bail!("No saved link with number {}", id);
+    };
+    Ok(item)
+}
+
+/// The saved link for `opennav --from-later ID`, which opens the picker for it.
+/// None without the flag.
+pub fn from_later_arg(args: &[String]) -> Result<Option<ReadingItem>> {
+    if args.get(1).map(String::as_str) != Some("--from-later") {
+        return Ok(None);
+    }
+    let id = args.get(2).context("Usage: opennav --from-later ID")?;
+    find_item(&Store::new()?, id).map(Some)
+}
+
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    if args.get(1)?.as_str() != "later" {
+        return None;
+    }
+    let result = match (args.get(2).map(String::as_str), args.get(3)) {
+        (Some("list") | None, None) => cli_list(),
+        (Some("open"), Some(id)) => cli_open(id),
+        _ => {
+            eprintln!("Usage: opennav later list\n       opennav later open ID");
+            return Some(2);
+        }
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("opennav: {:#}", e);
+            Some(1)
+        }
+    }
+}
+
+fn cli_list() -> Result<()> {
+    let items = Store::new()?.list_reading_items()?;
+    if items.is_empty() {
+        println!("Nothing saved for later.");
+        return Ok(());
+    }
+    let now = store::now();
+    for item in &items {
+        let from = source_app(item).map(|s| format!(" · from {}", s.name)).unwrap_or_default();
+        println!("{:>4}  {}", item.id, display_title(item));
+        println!("      {} · {}{}", item.url, format_age(item.added_at, now), from);
+    }
+    Ok(())
+}
+
+// A matching routing rule opens it directly, otherwise the picker is started for
+// it and removes it from the queue if a browser is picked
+fn cli_open(id: &str) -> Result<()> {
+    let store = Store::new()?;
+    let item = find_item(&store, id)?;
+    if routing::try_route(&item.url, source_app(&item).as_ref()) {
+        store.delete_reading_item(item.id)?;
+        println!("Opened {}", item.url);
+        return Ok(());
+    }
+    // Outlives this process, so not the AppImage mount
+    let exe = crate::data::delayed_open::self_exe()?;
+    std::process::Command::new(exe)
+        .arg("--from-later")
+        .arg(item.id.to_string())
+        .spawn()
+        .context("Cannot start the picker")?;
+    println!("Choose a browser for {}", item.url);
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use crate::data::environment;
+use crate::data::schedule;
+use crate::data::search;
+use crate::data::source_app::SourceApp;
+use crate::data::store::{RoutingRule, Store};
+
+// A browser is "consistently" used for a domain once it has this many launches
+// and at least this share of all launches for the domain.
+const CONSISTENT_MIN_LAUNCHES: i64 = 5;
+const CONSISTENT_MIN_SHARE: f64 = 0.8;
+
+#[derive(Debug, Clone)]
+pub struct DomainSuggestion {
+    pub domain: String,
+    pub browser_id: String,
+    pub count: i64,
+    pub total: i64,
+}
+
+impl DomainSuggestion {
+    // True when the history is one-sided enough to offer a permanent rule
+    pub fn is_consistent(&self) -> bool {
+        self.count >= CONSISTENT_MIN_LAUNCHES
+            && self.total > 0
+            && self.count as f64 / self.total as f64 >= CONSISTENT_MIN_SHARE
+    }
+}
+
+/// Host of a URL as typed in the picker, without a leading "www.".
+/// Bare domains ("example.com/path") are accepted; search queries are not.
+pub fn domain_of(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let parsed = if input.contains("://") {
+        url::Url::parse(input).ok()?
+    } else if !input.contains(' ') && input.contains('.') {
+        url::Url::parse(&format!("https://{}", input)).ok()?
+    } else {
+        return None;
+    };
+
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+/// "github.com" matches github.com and any subdomain; "*.corp" matches anything under .corp.
+pub fn domain_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let pattern = pattern.strip_prefix("*.").unwrap_or(&pattern);
+    if pattern.is_empty() {
+        return false;
+    }
+    host == pattern || host.ends_with(&format!(".{}", pattern))
+}
+
+// An optional rule field, with blank values (as older versions stored them) unset
+fn non_blank(field: &Option<String>) -> Option<&str> {
+    field.as_deref().filter(|s| !s.trim().is_empty())
+}
+
+// Whether the rule applies to this link, leaving out machine-state conditions
+fn applies_to(rule: &RoutingRule, host: Option<&str>, source: Option<&SourceApp>) -> bool {
+    let rule_source = non_blank(&rule.source_app);
+    let domain_ok = match (rule.domain.trim(), host) {
+        ("", _) => rule_source.is_some(), // A rule needs a domain or a source app
+        (domain, Some(host)) => domain_matches(domain, host),
+        (_, None) => false,
+    };
+    let source_ok = rule_source.is_none_or(|pattern| source.is_some_and(|s| s.matches(pattern)));
+    let schedule_ok = non_blank(&rule.schedule).is_none_or(schedule::is_active);
+    domain_ok && source_ok && schedule_ok
+}
+
+// Longer (more specific) domain patterns first, then rules that also name the
+// source, then ones with a schedule, then ones with machine conditions
+fn rank(rule: &RoutingRule) -> (usize, bool, bool, bool) {
+    (
+        rule.domain.trim().trim_start_matches("*.").len(),
+        non_blank(&rule.source_app).is_some(),
+        non_blank(&rule.schedule).is_some(),
+        non_blank(&rule.requires).is_some(),
+    )
+}
+
+/// Rule for a URL opened from `source`. A rule's domain and source app must both
+/// match where set, its schedule must be active and its machine conditions must
+/// hold. The most specific rule wins (see `rank`), so "Mon-Fri 09:00-18:00 -> Work"
+/// beats an unscheduled rule for the same links.
+pub fn matching_rule<'a>(rules: &'a [RoutingRule], url: &str, source: Option<&SourceApp>) -> Option<&'a RoutingRule> {
+    if url.trim().is_empty() {
+        return None;
+    }
+    let host = domain_of(url);
+    rules
+        .iter()
+        .filter(|r| applies_to(r, host.as_deref(), source))
+        .filter(|r| non_blank(&r.requires).is_none_or(environment::holds_all))
+        .max_by_key(|r| rank(r))
+}
+
+/// A rule that would have routed the URL but whose machine conditions don't hold
+/// (e.g. *.corp needs the VPN and tun0 is down), with the reason. Only reported
+/// when it outranks whatever rule does apply.
+pub fn blocked_rule<'a>(rules: &'a [RoutingRule], url: &str, source: Option<&SourceApp>) -> Option<(&'a RoutingRule, String)> {
+    if url.trim().is_empty() {
+        return None;
+    }
+    let host = domain_of(url);
+    let (rule, reason) = rules
+        .iter()
+        .filter(|r| applies_to(r, host.as_deref(), source))
+        .filter_map(|r| non_blank(&r.requires).and_then(environment::unmet).map(|reason| (r, reason)))
+        .max_by_key(|(r, _)| rank(r))?;
+    match matching_rule(rules, url, source) {
+        Some(active) if rank(active) >= rank(rule) => None,
+        _ => Some((rule, reason)),
+    }
+}
+
+/// Browser most often used for the URL's domain, if any launches were recorded.
+pub fn suggest_for_url(store: &Store, url: &str) -> Option<DomainSuggestion> {
+    let domain = domain_of(url)?;
+    let stats = store.get_domain_stats(&domain).ok()?;
+    let total = stats.iter().map(|(_, c)| c).sum();
+    let (browser_id, count) = stats.into_iter().next()?;
+    if count <= 0 {
+        return None;
+    }
+    Some(DomainSuggestion { domain, browser_id, count, total })
+}
+
+/// Launches the URL directly if a routing rule matches. Returns false when the
+/// picker should be shown instead (no rule, or the rule's browser is gone).
+pub fn try_route(url: &str, source: Option<&SourceApp>) -> bool {
+    let Ok(store) = Store::new() else {
+        return false;
+    };
+    let rules = store.list_active_routing_rules().unwrap_or_default();
+    let Some(rule) = matching_rule(&rules, url, source) else {
+        return false;
+    };
+
+    match crate::data::browser_repository::launch_browser(&rule.browser_id, url) {
+        Ok(()) => {
+            let _ = store.record_launch(&rule.browser_id, url, "rule");
+            true
+        }
+        Err(e) => {
+            log::warn!("Routing rule for {} failed: {}", rule.domain, e);
+            false
+        }
+    }
+}
+
+/// Launches a search directly if its keyword engine has a preferred browser set to
+/// skip the picker ("yt cats" -> the YouTube engine's browser). Returns false otherwise.
+pub fn try_engine_route(input: &str) -> bool {
+    let Ok(store) = Store::new() else {
+        return false;
+    };
+    let engines = store.list_active_engines().unwrap_or_default();
+    let Some(engine) = search::keyword_engine(input, &engines) else {
+        return false;
+    };
+    let Some(browser_id) = engine.browser_id.as_deref().filter(|_| engine.launch_directly) else {
+        return false;
+    };
+
+    let groups = store.list_engine_groups().unwrap_or_default();
+    let urls = search::launch_targets(input, &engines, &groups, &store.default_engine_keyword());
+    let url_refs: Vec<&str> = urls.iter().map(|u| u.as_str()).collect();
+    match crate::data::browser_repository::launch_browser_urls(browser_id, &url_refs) {
+        Ok(()) => {
+            for url in &urls {
+                let _ = store.record_launch(browser_id, url, "engine");
+            }
+            true
+        }
+        Err(e) => {
+            log::warn!("Preferred browser for \"{}\" failed: {}", engine.keyword, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Set in no test environment, so "env:" conditions on it never hold
+    const UNSET_VAR: &str = "OPENNAV_TEST_UNSET_VARIABLE";
+
+    fn rule(id: i64, domain: &str, source_app: Option<&str>) -> RoutingRule {
+        RoutingRule {
+            id,
+            domain: domain.to_string(),
+            browser_id: format!("browser-{}", id),
+            source_app: source_app.map(str::to_string),
+            schedule: None,
+            requires: None,
+        }
+    }
+
+    fn matched(rules: &[RoutingRule], url: &str, source: Option<&str>) -> Option<i64> {
+        let source = source.and_then(SourceApp::from_id);
+        matching_rule(rules, url, source.as_ref()).map(|r| r.id)
+    }
+
+    #[test]
+    fn domains_match_subdomains_not_suffixes() {
+        assert!(domain_matches("github.com", "github.com"));
+        assert!(domain_matches("github.com", "gist.github.com"));
+        assert!(domain_matches("GitHub.com ", "api.github.com"));
+        assert!(!domain_matches("github.com", "notgithub.com"));
+        assert!(!domain_matches("github.com", "github.com.evil.example"));
+        assert!(domain_matches("*.corp", "wiki.corp"));
+        assert!(domain_matches("*.corp", "corp"));
+        assert!(!domain_matches("*.corp", "acmecorp"));
+        assert!(!domain_matches("", "github.com"));
+        assert!(!domain_matches("*.", "github.com"));
+    }
+
+    #[test]
+    fn domain_of_accepts_urls_and_bare_domains() {
+        assert_eq!(domain_of("https://www.GitHub.com/rust-lang"), Some("github.com".to_string()));
+        assert_eq!(domain_of("docs.rs/serde"), Some("docs.rs".to_string()));
+        assert_eq!(domain_of("rust borrow checker"), None);
+        assert_eq!(domain_of(""), None);
+    }
+
+    #[test]
+    fn most_specific_domain_wins() {
+        let rules = [rule(1, "*.corp", None), rule(2, "wiki.corp", None), rule(3, "github.com", None)];
+        assert_eq!(matched(&rules, "https://wiki.corp/page", None), Some(2));
+        assert_eq!(matched(&rules, "https://mail.corp/", None), Some(1));
+        assert_eq!(matched(&rules, "https://gist.github.com/", None), Some(3));
+        assert_eq!(matched(&rules, "https://example.com/", None), None);
+        assert_eq!(matched(&rules, "", None), None);
+    }
+
+    #[test]
+    fn source_app_rules() {
+        let rules = [rule(1, "github.com", None), rule(2, "github.com", Some("slack")), rule(3, "", Some("thunderbird"))];
+        assert_eq!(matched(&rules, "https://github.com/", Some("com.slack.Slack")), Some(2));
+        assert_eq!(matched(&rules, "https://github.com/", Some("firefox")), Some(1));
+        assert_eq!(matched(&rules, "https://github.com/", None), Some(1));
+        assert_eq!(matched(&rules, "https://example.com/", Some("thunderbird")), Some(3));
+        assert_eq!(matched(&rules, "https://example.com/", Some("slack")), None);
+        // Neither a domain nor a source: matches nothing
+        assert_eq!(matched(&[rule(4, "", None)], "https://example.com/", Some("slack")), None);
+    }
+
+    #[test]
+    fn blank_fields_do_not_make_a_rule_more_specific() {
+        let mut blank = rule(2, "github.com", Some(""));
+        blank.schedule = Some(" ".to_string());
+        blank.requires = Some(String::new());
+        let rules = [rule(1, "github.com", Some("slack")), blank.clone()];
+        assert_eq!(matched(&rules, "https://github.com/", Some("slack")), Some(1));
+        assert_eq!(matched(&rules, "https://github.com/", None), Some(2));
+        assert_eq!(rank(&blank), rank(&rule(3, "github.com", None)));
+
+        let normalized = blank.normalized();
+        assert_eq!((normalized.source_app, normalized.schedule, normalized.requires), (None, None, None));
+    }
+
+    #[test]
+    fn conditions_must_hold() {
+        let mut vpn = rule(2, "*.corp", None);
+        vpn.requires = Some(format!("env:{}", UNSET_VAR));
+        let mut fallback = rule(3, "*.corp", None);
+        fallback.requires = Some(format!("!env:{}", UNSET_VAR));
+        assert_eq!(matched(&[vpn.clone(), rule(1, "*.corp", None)], "https://wiki.corp/", None), Some(1));
+        assert_eq!(matched(&[vpn, fallback], "https://wiki.corp/", None), Some(3));
+    }
+
+    #[test]
+    fn blocked_rule_reports_unmet_conditions_that_outrank_the_match() {
+        let mut vpn = rule(2, "*.corp", None);
+        vpn.requires = Some(format!("env:{}", UNSET_VAR));
+
+        let rules = [rule(1, "*.corp", None), vpn.clone()];
+        let (blocked, reason) = blocked_rule(&rules, "https://wiki.corp/", None).unwrap();
+        assert_eq!(blocked.id, 2);
+        assert_eq!(reason, format!("{} is not set", UNSET_VAR));
+
+        // A more specific rule applies anyway
+        assert!(blocked_rule(&[rule(1, "wiki.corp", None), vpn.clone()], "https://wiki.corp/", None).is_none());
+        // Other domains aren't affected
+        assert!(blocked_rule(&[vpn], "https://example.com/", None).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+// Time-of-day and weekday conditions, written the way people say them:
+//
+//   "Mon-Fri 09:00-18:00"   weekdays during office hours
+//   "Sat,Sun"               all day at the weekend
+//   "22:00-06:00"           every night (ranges may wrap past midnight)
+//   "weekdays 08:30-12:00"  "weekdays", "weekends" and "daily" also work
+//
+// Evaluated against local time.
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    days: [bool; 7], // Monday first
+    start: u32,      // Minutes since midnight
+    end: u32,        // Exclusive; equal to start means all day
+}
+
+impl Schedule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().replace(['–', '—'], "-");
+        if text.is_empty() {
+            return Err("Schedule is empty".to_string());
+        }
+
+        let mut days = None;
+        let mut hours = None;
+        for part in text.split_whitespace() {
+            if part.contains(':') {
+                if hours.is_some() {
+                    return Err(format!("\"{}\": only one time range is supported", part));
+                }
+                hours = Some(parse_hours(part)?);
+            } else {
+                if days.is_some() {
+                    return Err(format!("\"{}\": give days as one list, e.g. Mon,Wed,Fri", part));
+                }
+                days = Some(parse_days(part)?);
+            }
+        }
+
+        let (start, end) = hours.unwrap_or((0, 0));
+        Ok(Schedule { days: days.unwrap_or([true; 7]), start, end })
+    }
+
+    /// `weekday` is 0 for Monday through 6 for Sunday.
+    pub fn is_active_at(&self, weekday: usize, minute: u32) -> bool {
+        let previous = (weekday + 6) % 7;
+        if self.start == self.end {
+            self.days[weekday]
+        } else if self.start < self.end {
+            self.days[weekday] && minute >= self.start && minute < self.end
+        } else {
+            // Overnight: belongs to the day it starts on
+            (self.days[weekday] && minute >= self.start) || (self.days[previous] && minute < self.end)
+        }
+    }
+
+    pub fn is_active_now(&self) -> bool {
+        let Ok(now) = gtk4::glib::DateTime::now_local() else {
+            return false;
+        };
+        let weekday = (now.day_of_week() - 1).clamp(0, 6) as usize;
+        let minute = (now.hour() * 60 + now.minute()) as u32;
+        self.is_active_at(weekday, minute)
+    }
+}
+
+/// Whether a stored schedule applies right now. Invalid schedules never do.
+pub fn is_active(text: &str) -> bool {
+    match Schedule::parse(text) {
+        Ok(schedule) => schedule.is_active_now(),
+        Err(e) => {
+            log::warn!("Ignoring schedule \"{}\": {}", text, e);
+            false
+        }
+    }
+}
+
+fn parse_days(text: &str) -> Result<[bool; 7], String> {
+    let mut days = [false; 7];
+    for item in text.to_lowercase().split(',').filter(|s| !s.is_empty()) {
+        match item {
+            "daily" | "everyday" => days = [true; 7],
+            "weekdays" => days[..5].iter_mut().for_each(|d| *d = true),
+            "weekends" | "weekend" => days[5..].iter_mut().for_each(|d| *d = true),
+            _ => {
+                let (first, last) = match item.split_once('-') {
+                    Some((a, b)) => (day_index(a)?, day_index(b)?),
+                    None => (day_index(item)?, day_index(item)?),
+                };
+                // Ranges may wrap, e.g. Fri-Mon
+                let mut day = first;
+                loop {
+                    days[day] = true;
+                    if day == last {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+        }
+    }
+    if !days.contains(&true) {
+        return Err(format!("\"{}\" names no days", text));
+    }
+    Ok(days)
+}
+
+fn day_index(name: &str) -> Result<usize, String> {
+    let name = name.trim();
+    let prefix: String = name.chars().take(3).collect();
+    DAY_NAMES
+        .iter()
+        .position(|d| prefix.len() >= 2 && d.starts_with(&prefix))
+        .ok_or_else(|| format!("Unknown day \"{}\"", name))
+}
+
+fn parse_hours(text: &str) -> Result<(u32, u32), String> {
+    let (start, end) = text
+        .split_once('-')
+        .ok_or_else(|| format!("\"{}\": expected a range like 09:00-18:00", text))?;
+    let start = parse_time(start)?;
+    if start == MINUTES_PER_DAY {
+        return Err(format!("\"{}\": 24:00 can only end a range", text));
+    }
+    Ok((start, parse_time(end)? % MINUTES_PER_DAY))
+}
+
+fn parse_time(text: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid time \"{}\"", text);
+    let (h, m) = text.trim().split_once(':').ok_or_else(invalid)?;
+    let h: u32 = h.parse().map_err(|_| invalid())?;
+    let m: u32 = m.parse().map_err(|_| invalid())?;
+    // 24:00 is allowed as the end of the day
+    if m > 59 || h > 24 || (h == 24 && m != 0) {
+        return Err(invalid());
+    }
+    Ok(h * 60 + m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MON: usize = 0;
+    const TUE: usize = 1;
+    const WED: usize = 2;
+    const THU: usize = 3;
+    const FRI: usize = 4;
+    const SAT: usize = 5;
+    const SUN: usize = 6;
+
+    fn at(h: u32, m: u32) -> u32 {
+        h * 60 + m
+    }
+
+    #[test]
+    fn office_hours_on_weekdays() {
+        let schedule = Schedule::parse("Mon-Fri 09:00-18:00").unwrap();
+        assert!(schedule.is_active_at(MON, at(9, 0)));
+        assert!(schedule.is_active_at(FRI, at(17, 59)));
+        assert!(!schedule.is_active_at(FRI, at(18, 0)));
+        assert!(!schedule.is_active_at(TUE, at(8, 59)));
+        assert!(!schedule.is_active_at(SAT, at(12, 0)));
+    }
+
+    #[test]
+    fn day_ranges_wrap_past_sunday() {
+        let schedule = Schedule::parse("Fri-Mon").unwrap();
+        for day in [FRI, SAT, SUN, MON] {
+            assert!(schedule.is_active_at(day, at(12, 0)), "day {}", day);
+        }
+        for day in [TUE, WED, THU] {
+            assert!(!schedule.is_active_at(day, at(12, 0)), "day {}", day);
+        }
+    }
+
+    #[test]
+    fn overnight_hours_belong_to_the_starting_day() {
+        let schedule = Schedule::parse("Fri 22:00-06:00").unwrap();
+        assert!(schedule.is_active_at(FRI, at(23, 30)));
+        assert!(schedule.is_active_at(SAT, at(5, 59)));
+        assert!(!schedule.is_active_at(SAT, at(6, 0)));
+        assert!(!schedule.is_active_at(SAT, at(22, 30)));
+        assert!(!schedule.is_active_at(FRI, at(1, 0)));
+    }
+
+    #[test]
+    fn days_without_hours_are_all_day() {
+        let schedule = Schedule::parse("Sat,Sun").unwrap();
+        assert!(schedule.is_active_at(SAT, at(0, 0)));
+        assert!(schedule.is_active_at(SUN, at(23, 59)));
+        assert!(!schedule.is_active_at(MON, at(0, 0)));
+
+        let whole_day = Schedule::parse("weekdays 00:00-24:00").unwrap();
+        assert!(whole_day.is_active_at(THU, at(0, 0)));
+        assert!(whole_day.is_active_at(THU, at(23, 59)));
+        assert!(!whole_day.is_active_at(SUN, at(12, 0)));
+    }
+
+    #[test]
+    fn range_may_end_at_midnight() {
+        let schedule = Schedule::parse("daily 18:00-24:00").unwrap();
+        assert!(schedule.is_active_at(TUE, at(23, 59)));
+        assert!(!schedule.is_active_at(TUE, at(0, 0)));
+        assert!(!schedule.is_active_at(TUE, at(17, 59)));
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        for text in ["", "24:00-06:00", "25:00-06:00", "09:60-10:00", "0900-1000", "Funday", "Mon Tue", "09:00-10:00 11:00-12:00"] {
+            assert!(Schedule::parse(text).is_err(), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,640 @@
+use crate::data::bangs::{self, Bang};
+use crate::data::post_form;
+use crate::data::store::{EngineGroup, SearchEngine};
+
+/// Same heuristic as `launch_browser`: a scheme, or a single word with a dot, is a URL.
+pub fn looks_like_url(text: &str) -> bool {
+    let text = text.trim();
+    text.contains("://") || (!text.contains(' ') && text.contains('.'))
+}
+
+/// A link worth offering from the clipboard: one http(s) URL, or "www." plus a
+/// domain. Stricter than `looks_like_url`, since copied text is rarely meant as one.
+pub fn clipboard_url(text: &str) -> Option<String> {
+    const MAX_LEN: usize = 2048;
+    let text = text.trim();
+    if text.is_empty() || text.len() > MAX_LEN || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let candidate = if text.starts_with("www.") { format!("https://{}", text) } else { text.to_string() };
+    let url = url::Url::parse(&candidate).ok()?;
+    let web = matches!(url.scheme(), "http" | "https");
+    (web && url.host_str().is_some_and(|h| !h.is_empty())).then_some(candidate)
+}
+
+/// "gh rust gtk" -> (GitHub engine, "rust gtk") if "gh" is a configured keyword.
+pub fn split_keyword<'a, 't>(text: &'t str, engines: &'a [SearchEngine]) -> Option<(&'a SearchEngine, &'t str)> {
+    let (first, rest) = text.trim_start().split_once(' ')?;
+    let engine = engines.iter().find(|e| e.keyword == first)?;
+    Some((engine, rest.trim()))
+}
+
+/// Engine and query for a search: the keyword's engine, otherwise the default engine.
+/// Returns `None` for URLs, empty input and bang searches.
+pub fn resolve_engine<'a>(
+    text: &'a str,
+    engines: &'a [SearchEngine],
+    default_keyword: &str,
+) -> Option<(&'a SearchEngine, &'a str)> {
+    let text = text.trim();
+    if text.is_empty() || looks_like_url(text) || find_known_bang(text, engines, &[]).is_some() {
+        return None;
+    }
+    if let Some(found) = split_keyword(text, engines) {
+        return Some(found);
+    }
+    let engine = engines.iter().find(|e| e.keyword == default_keyword)?;
+    Some((engine, text))
+}
+
+/// Where a search goes: a configured engine, an engine group or a bang from the bang database.
+pub enum SearchTarget<'a> {
+    Engine(&'a SearchEngine),
+    Group(&'a EngineGroup),
+    Bang(Bang),
+}
+
+// A bang in the text that resolves: user engines and groups (by keyword) take precedence over the database
+fn find_known_bang<'a>(
+    text: &str,
+    engines: &'a [SearchEngine],
+    groups: &'a [EngineGroup],
+) -> Option<(SearchTarget<'a>, String)> {
+    let (trigger, query) = bangs::find_bang(text)?;
+    if let Some(engine) = engines.iter().find(|e| e.keyword.eq_ignore_ascii_case(&trigger)) {
+        return Some((SearchTarget::Engine(engine), query));
+    }
+    if let Some(group) = groups.iter().find(|g| g.keyword.eq_ignore_ascii_case(&trigger)) {
+        return Some((SearchTarget::Group(group), query));
+    }
+    bangs::lookup(&trigger).map(|bang| (SearchTarget::Bang(bang), query))
+}
+
+/// The engine the user picked explicitly, by bang or keyword prefix (not the default engine).
+pub fn keyword_engine<'a>(text: &str, engines: &'a [SearchEngine]) -> Option<&'a SearchEngine> {
+    let text = text.trim();
+    if text.is_empty() || looks_like_url(text) {
+        return None;
+    }
+    match find_known_bang(text, engines, &[]) {
+        Some((SearchTarget::Engine(engine), _)) => Some(engine),
+        Some(_) => None,
+        None => split_keyword(text, engines).map(|(engine, _)| engine),
+    }
+}
+
+/// Target and query for a search, in order: a bang anywhere in the text ("!w rust",
+/// "rust !w"), a keyword prefix ("w rust", "dev rust"), then the default engine
+/// or group. Returns `None` for URLs and empty input.
+pub fn resolve_search<'a>(
+    text: &str,
+    engines: &'a [SearchEngine],
+    groups: &'a [EngineGroup],
+    default_keyword: &str,
+) -> Option<(SearchTarget<'a>, String)> {
+    let text = text.trim();
+    if text.is_empty() || looks_like_url(text) {
+        return None;
+    }
+    if let Some(found) = find_known_bang(text, engines, groups) {
+        return Some(found);
+    }
+    if let Some((engine, query)) = split_keyword(text, engines) {
+        return Some((SearchTarget::Engine(engine), query.to_string()));
+    }
+    if let Some((first, rest)) = text.split_once(' ') {
+        if let Some(group) = groups.iter().find(|g| g.keyword == first) {
+            return Some((SearchTarget::Group(group), rest.trim().to_string()));
+        }
+    }
+    if let Some(engine) = engines.iter().find(|e| e.keyword == default_keyword) {
+        return Some((SearchTarget::Engine(engine), text.to_string()));
+    }
+    let group = groups.iter().find(|g| g.keyword == default_keyword)?;
+    Some((SearchTarget::Group(group), text.to_string()))
+}
+
+/// Fills the URL-encoded query into a template ("{}" or OpenSearch "{searchTerms}").
+pub fn fill_template(template: &str, query: &str) -> String {
+    fill_placeholders(template, query, &[], true)
+}
+
+// The N of every "{N}" placeholder in a template
+fn arg_numbers(template: &str) -> impl Iterator<Item = usize> + '_ {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}')?.0.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+}
+
+// Highest "{N}" placeholder in a template, 0 without any
+fn max_arg(template: &str) -> usize {
+    arg_numbers(template).max().unwrap_or(0)
+}
+
+fn engine_templates(engine: &SearchEngine) -> impl Iterator<Item = &str> {
+    std::iter::once(engine.url.as_str()).chain(engine.params.iter().map(|p| p.value.as_str()))
+}
+
+// Highest "{N}" placeholder used by the engine's URL and parameters
+fn arg_count(engine: &SearchEngine) -> usize {
+    engine_templates(engine).map(max_arg).max().unwrap_or(0)
+}
+
+// The first "{N}" below the highest one that the engine never uses, e.g. 1 for
+// a URL with only "{2}"; such an argument would be dropped from every search
+fn skipped_arg(engine: &SearchEngine) -> Option<usize> {
+    let used: Vec<usize> = engine_templates(engine).flat_map(arg_numbers).collect();
+    (1..arg_count(engine)).find(|n| !used.contains(n))
+}
+
+/// The first "{N}" a search for `query` would leave empty, e.g. 2 for "PROJ"
+/// with "https://jira/{1}/search?q={2}".
+pub fn missing_arg(engine: &SearchEngine, query: &str) -> Option<usize> {
+    let count = arg_count(engine);
+    let given = split_args(query, count).iter().filter(|a| !a.is_empty()).count();
+    (given < count).then_some(given + 1)
+}
+
+/// Splits the query into at most `count` space-separated arguments; the last one
+/// takes the rest, e.g. "PROJ login bug" with two placeholders -> ["PROJ", "login bug"].
+pub fn split_args(query: &str, count: usize) -> Vec<String> {
+    if count == 0 {
+        return Vec::new();
+    }
+    query.splitn(count, ' ').map(|a| a.trim().to_string()).collect()
+}
+
+/// Replaces "{}"/"{searchTerms}" with the whole query and "{1}", "{2}"... with the
+/// arguments (empty when not given). Values are URL-encoded when `encode` is set.
+pub fn fill_placeholders(template: &str, query: &str, args: &[String], encode: bool) -> String {
+    let prepare = |value: &str| -> String {
+        if encode {
+            url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+        } else {
+            value.to_string()
+        }
+    };
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = &after[..end];
+        match name {
+            "" | "searchTerms" => out.push_str(&prepare(query)),
+            n if n.parse::<usize>().is_ok_and(|i| i > 0) => {
+                let i: usize = n.parse().unwrap_or(0);
+                out.push_str(&prepare(args.get(i - 1).map(|a| a.as_str()).unwrap_or("")));
+            }
+            // Not a placeholder, keep it as is
+            _ => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The URL a search with this engine opens (for POST engines, the local form page).
+pub fn engine_url(engine: &SearchEngine, query: &str) -> String {
+    if query.is_empty() {
+        // Nothing to search for ("!gh", "gh "): open the engine's site
+        if let Ok(url) = url::Url::parse(&fill_placeholders(&engine.url, "", &[], false)) {
+            return url.origin().ascii_serialization();
+        }
+    }
+
+    let (url, fields) = build_request(engine, query);
+    if !engine.is_post() {
+        return url;
+    }
+    match post_form::write_post_page(&url, &fields) {
+        Ok(page) => page,
+        Err(e) => {
+            log::warn!("Failed to write POST page for {}: {}", engine.keyword, e);
+            url
+        }
+    }
+}
+
+// The filled-in URL and form fields. A GET engine's fields are already in the
+// URL's query string, so none are returned for it.
+fn build_request(engine: &SearchEngine, query: &str) -> (String, Vec<(String, String)>) {
+    let args = split_args(query, arg_count(engine));
+    let url = fill_placeholders(&engine.url, query, &args, true);
+    let fields: Vec<(String, String)> = engine
+        .params
+        .iter()
+        .map(|p| (p.name.clone(), fill_placeholders(&p.value, query, &args, false)))
+        .collect();
+
+    if engine.is_post() || fields.is_empty() {
+        return (url, fields);
+    }
+    match url::Url::parse(&url) {
+        Ok(mut parsed) => {
+            parsed.query_pairs_mut().extend_pairs(fields.iter());
+            (parsed.to_string(), Vec::new())
+        }
+        Err(_) => (url, Vec::new()),
+    }
+}
+
+/// The URLs to open for the URL bar text: one per engine, several for a group.
+/// URLs and unresolvable input are returned unchanged for `launch_browser` to handle.
+pub fn launch_targets(
+    text: &str,
+    engines: &[SearchEngine],
+    groups: &[EngineGroup],
+    default_keyword: &str,
+) -> Vec<String> {
+    let Some((target, query)) = resolve_search(text, engines, groups, default_keyword) else {
+        return vec![text.trim().to_string()];
+    };
+    match target {
+        SearchTarget::Engine(engine) => vec![engine_url(engine, &query)],
+        // Members that no longer exist are skipped
+        SearchTarget::Group(group) => group
+            .members
+            .iter()
+            .filter_map(|keyword| engines.iter().find(|e| &e.keyword == keyword))
+            .map(|engine| engine_url(engine, &query))
+            .collect(),
+        // A bare bang ("!gh") opens the site itself
+        SearchTarget::Bang(bang) => match (&bang.domain, query.is_empty()) {
+            (Some(domain), true) => vec![format!("https://{}", domain)],
+            _ => vec![fill_template(&bang.url, &query)],
+        },
+    }
+}
+
+/// What a search would send, without side effects: the URL, or for POST engines
+/// the target and the form fields. Arguments the query leaves empty are pointed out.
+pub fn preview_search(engine: &SearchEngine, query: &str) -> String {
+    let (url, fields) = build_request(engine, query);
+    let mut preview = if engine.is_post() {
+        let lines: Vec<String> = fields.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        format!("POST {}\n{}", url, lines.join("\n"))
+    } else {
+        url
+    };
+    if let Some(n) = missing_arg(engine, query).filter(|_| !query.is_empty()) {
+        preview.push_str(&format!("\n{{{}}} is empty: this engine takes {} words.", n, arg_count(engine)));
+    }
+    preview
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineField {
+    Name,
+    Keyword,
+    Url,
+    SuggestUrl,
+}
+
+// Hosts people type without a dot; as keywords they'd shadow the site
+const BARE_HOSTNAMES: &[&str] = &["localhost"];
+
+fn has_placeholder(text: &str) -> bool {
+    text.contains("{}") || text.contains("{searchTerms}") || max_arg(text) > 0
+}
+
+/// What's wrong with a keyword for an engine or group, if anything. Engines and
+/// groups share one keyword namespace; `original_keyword` is the edited item's own.
+pub fn validate_keyword(
+    keyword: &str,
+    engines: &[SearchEngine],
+    groups: &[EngineGroup],
+    original_keyword: Option<&str>,
+) -> Option<String> {
+    let keyword = keyword.trim();
+    if keyword.is_empty() {
+        Some("Enter a keyword.".to_string())
+    } else if keyword.contains(char::is_whitespace) {
+        Some("The keyword can't contain spaces.".to_string())
+    } else if keyword.starts_with('!') {
+        Some("Leave out the \"!\", bangs work with every keyword.".to_string())
+    } else if keyword.contains('.') || keyword.contains(':') || BARE_HOSTNAMES.contains(&keyword.to_lowercase().as_str()) {
+        Some(format!("\"{}\" looks like a domain name and would be opened as a site.", keyword))
+    } else if original_keyword == Some(keyword) {
+        None
+    } else if let Some(other) = engines.iter().find(|e| e.keyword == keyword) {
+        Some(format!("\"{}\" is already used by {}.", keyword, other.name))
+    } else {
+        groups
+            .iter()
+            .find(|g| g.keyword == keyword)
+            .map(|group| format!("\"{}\" is already used by the group {}.", keyword, group.name))
+    }
+}
+
+/// Problems that would make the engine unusable, per field. `original_keyword` is
+/// the keyword being edited, which may be kept.
+pub fn validate_engine(
+    engine: &SearchEngine,
+    engines: &[SearchEngine],
+    groups: &[EngineGroup],
+    original_keyword: Option<&str>,
+) -> Vec<(EngineField, String)> {
+    let mut issues = Vec::new();
+
+    if engine.name.trim().is_empty() {
+        issues.push((EngineField::Name, "Enter a name.".to_string()));
+    }
+
+    if let Some(message) = validate_keyword(&engine.keyword, engines, groups, original_keyword) {
+        issues.push((EngineField::Keyword, message));
+    }
+
+    let url = engine.url.trim();
+    if url.is_empty() {
+        issues.push((EngineField::Url, "Enter the search URL.".to_string()));
+    } else {
+        match url::Url::parse(&fill_placeholders(url, "test", &[], true)) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some() => {}
+            Ok(_) => issues.push((EngineField::Url, "The URL must start with http:// or https://.".to_string())),
+            Err(e) => issues.push((EngineField::Url, format!("Not a valid URL ({}).", e))),
+        }
+        let in_params = engine.params.iter().any(|p| has_placeholder(&p.value));
+        if !has_placeholder(url) && !in_params {
+            issues.push((EngineField::Url, "Add {} where the search query goes.".to_string()));
+        } else if let Some(n) = skipped_arg(engine) {
+            issues.push((EngineField::Url, format!("{{{}}} is never used; number the arguments from {{1}} without gaps.", n)));
+        }
+    }
+
+    if let Some(suggest) = engine.suggest_url.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        if url::Url::parse(&fill_placeholders(suggest, "test", &[], true)).is_err() {
+            issues.push((EngineField::SuggestUrl, "Not a valid URL.".to_string()));
+        } else if !has_placeholder(suggest) {
+            issues.push((EngineField::SuggestUrl, "Add {} where the query goes.".to_string()));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::store::EngineParam;
+
+    fn engine(name: &str, keyword: &str, url: &str) -> SearchEngine {
+        SearchEngine {
+            name: name.to_string(),
+            keyword: keyword.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn engines() -> Vec<SearchEngine> {
+        vec![
+            engine("DuckDuckGo", "ddg", "https://duckduckgo.com/?q={}"),
+            engine("Docs.rs", "docs", "https://docs.rs/releases/search?query={}"),
+        ]
+    }
+
+    fn group(keyword: &str, members: &[&str]) -> EngineGroup {
+        EngineGroup {
+            name: keyword.to_string(),
+            keyword: keyword.to_string(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn targets(text: &str, engines: &[SearchEngine], groups: &[EngineGroup]) -> Vec<String> {
+        launch_targets(text, engines, groups, "ddg")
+    }
+
+    #[test]
+    fn finds_a_bang_at_the_start_or_the_end() {
+        assert_eq!(bangs::find_bang("!w rust lang"), Some(("w".to_string(), "rust lang".to_string())));
+        assert_eq!(bangs::find_bang("rust lang !W"), Some(("w".to_string(), "rust lang".to_string())));
+        assert_eq!(bangs::find_bang("!gh"), Some(("gh".to_string(), String::new())));
+        assert_eq!(bangs::find_bang("rust ! lang"), None);
+        assert_eq!(bangs::find_bang("rust lang"), None);
+    }
+
+    #[test]
+    fn resolves_bundled_bangs() {
+        let engines = engines();
+        let Some((SearchTarget::Bang(bang), query)) = find_known_bang("rust !w", &engines, &[]) else {
+            panic!("!w is not a bundled bang");
+        };
+        assert_eq!((bang.trigger.as_str(), query.as_str()), ("w", "rust"));
+        assert_eq!(targets("!w rust lang", &engines, &[]), vec!["https://en.wikipedia.org/wiki/Special:Search?search=rust+lang"]);
+        assert_eq!(targets("rust lang !w", &engines, &[]), targets("!w rust lang", &engines, &[]));
+    }
+
+    #[test]
+    fn user_engines_and_groups_take_precedence_over_bangs() {
+        let mut engines = engines();
+        engines.push(engine("Wiktionary", "w", "https://en.wiktionary.org/w/index.php?search={}"));
+        assert!(matches!(find_known_bang("!W rust", &engines, &[]), Some((SearchTarget::Engine(e), _)) if e.name == "Wiktionary"));
+        assert_eq!(targets("!w rust", &engines, &[]), vec!["https://en.wiktionary.org/w/index.php?search=rust"]);
+
+        let groups = [group("gh", &["docs"])];
+        assert!(matches!(find_known_bang("!gh rust", &engines, &groups), Some((SearchTarget::Group(g), _)) if g.keyword == "gh"));
+    }
+
+    #[test]
+    fn unknown_bangs_search_the_default_engine() {
+        let engines = engines();
+        assert!(find_known_bang("!nosuchbang rust", &engines, &[]).is_none());
+        assert_eq!(targets("!nosuchbang rust", &engines, &[]), vec!["https://duckduckgo.com/?q=%21nosuchbang+rust"]);
+    }
+
+    #[test]
+    fn bare_bang_opens_the_site() {
+        let engines = engines();
+        assert_eq!(targets("!gh", &engines, &[]), vec!["https://github.com"]);
+        assert_eq!(targets("!docs", &engines, &[]), vec!["https://docs.rs"]);
+    }
+
+    #[test]
+    fn bangs_are_not_keyword_or_default_searches() {
+        let engines = engines();
+        assert!(resolve_engine("!w rust", &engines, "ddg").is_none());
+        assert!(keyword_engine("!w rust", &engines).is_none());
+        assert_eq!(keyword_engine("rust !docs", &engines).map(|e| e.keyword.as_str()), Some("docs"));
+    }
+
+    #[test]
+    fn group_opens_each_member_and_skips_missing_ones() {
+        let engines = engines();
+        let groups = [group("dev", &["docs", "gone", "ddg"])];
+        let expected = vec!["https://docs.rs/releases/search?query=serde+json", "https://duckduckgo.com/?q=serde+json"];
+        assert_eq!(targets("dev serde json", &engines, &groups), expected);
+        assert_eq!(targets("!dev serde json", &engines, &groups), expected);
+        assert!(targets("dev rust", &engines, &[group("dev", &["gone"])]).is_empty());
+    }
+
+    #[test]
+    fn group_can_be_the_default() {
+        let engines = engines();
+        let groups = [group("dev", &["docs", "ddg"])];
+        assert!(matches!(resolve_search("serde json", &engines, &groups, "dev"), Some((SearchTarget::Group(g), q)) if g.keyword == "dev" && q == "serde json"));
+        assert_eq!(
+            launch_targets("serde json", &engines, &groups, "dev"),
+            vec!["https://docs.rs/releases/search?query=serde+json", "https://duckduckgo.com/?q=serde+json"]
+        );
+    }
+
+    fn param(name: &str, value: &str) -> EngineParam {
+        EngineParam {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn fills_numbered_arguments() {
+        let jira = engine("Jira", "jira", "https://jira/{1}/search?q={2}");
+        assert_eq!(split_args("PROJ login bug", 2), vec!["PROJ", "login bug"]);
+        assert_eq!(engine_url(&jira, "PROJ login bug"), "https://jira/PROJ/search?q=login+bug");
+        assert_eq!(fill_placeholders("{2}-{1}-{}-{x}", "a b", &["a".to_string(), "b".to_string()], false), "b-a-a b-{x}");
+        assert_eq!(fill_template("https://s/?q={searchTerms}", "a&b"), "https://s/?q=a%26b");
+    }
+
+    #[test]
+    fn fewer_arguments_than_placeholders_leave_them_empty() {
+        let jira = engine("Jira", "jira", "https://jira/{1}/search?q={2}");
+        assert_eq!(split_args("PROJ", 2), vec!["PROJ"]);
+        assert_eq!(engine_url(&jira, "PROJ"), "https://jira/PROJ/search?q=");
+        assert_eq!(missing_arg(&jira, "PROJ"), Some(2));
+        assert_eq!(missing_arg(&jira, "PROJ bug"), None);
+        assert!(preview_search(&jira, "PROJ").ends_with("{2} is empty: this engine takes 2 words."));
+        assert_eq!(preview_search(&jira, "PROJ bug"), "https://jira/PROJ/search?q=bug");
+    }
+
+    #[test]
+    fn argument_numbers_must_not_skip() {
+        let skipping = engine("Jira", "jira", "https://jira/search?q={2}");
+        assert_eq!(
+            validate_engine(&skipping, &[], &[], None),
+            vec![(EngineField::Url, "{1} is never used; number the arguments from {1} without gaps.".to_string())]
+        );
+        let mut in_params = engine("Jira", "jira", "https://jira/{1}/search");
+        in_params.params = vec![param("q", "{2}")];
+        assert!(validate_engine(&in_params, &[], &[], None).is_empty());
+    }
+
+    #[test]
+    fn get_params_are_added_to_the_query_string() {
+        let mut engine = engine("Search", "s", "https://example.com/search?lang=en");
+        engine.params = vec![param("q", "{}"), param("page", "1")];
+        assert_eq!(engine_url(&engine, "a b"), "https://example.com/search?lang=en&q=a+b&page=1");
+    }
+
+    #[test]
+    fn post_params_become_form_fields() {
+        let mut engine = engine("Search", "s", "https://example.com/search/{1}");
+        engine.method = "post".to_string();
+        engine.params = vec![param("q", "{2}"), param("all", "{}")];
+        let (url, fields) = build_request(&engine, "docs a&b c");
+        assert_eq!(url, "https://example.com/search/docs");
+        assert_eq!(
+            fields,
+            vec![("q".to_string(), "a&b c".to_string()), ("all".to_string(), "docs a&b c".to_string())]
+        );
+        assert_eq!(preview_search(&engine, "docs a&b c"), "POST https://example.com/search/docs\nq=a&b c\nall=docs a&b c");
+    }
+
+    fn issues(engine: &SearchEngine, original_keyword: Option<&str>) -> Vec<(EngineField, String)> {
+        validate_engine(engine, &engines(), &[group("dev", &["ddg", "docs"])], original_keyword)
+    }
+
+    fn keyword_issue(keyword: &str, original_keyword: Option<&str>) -> Option<String> {
+        issues(&engine("New", keyword, "https://new.example/?q={}"), original_keyword)
+            .into_iter()
+            .find(|(field, _)| *field == EngineField::Keyword)
+            .map(|(_, message)| message)
+    }
+
+    #[test]
+    fn valid_engine_has_no_issues() {
+        assert!(issues(&engine("Crates", "crates", "https://crates.io/search?q={}"), None).is_empty());
+        assert!(issues(&engine("Crates", "crates", "http://crates.io/search?q={searchTerms}"), None).is_empty());
+    }
+
+    #[test]
+    fn rejects_duplicate_keywords() {
+        assert_eq!(keyword_issue("docs", None), Some("\"docs\" is already used by Docs.rs.".to_string()));
+        assert_eq!(keyword_issue("dev", None), Some("\"dev\" is already used by the group dev.".to_string()));
+    }
+
+    #[test]
+    fn editing_may_keep_the_keyword_but_not_take_another() {
+        assert_eq!(keyword_issue("docs", Some("docs")), None);
+        assert_eq!(keyword_issue("ddg", Some("docs")), Some("\"ddg\" is already used by DuckDuckGo.".to_string()));
+        assert_eq!(keyword_issue("rs", Some("docs")), None);
+    }
+
+    #[test]
+    fn group_keywords_follow_engine_rules() {
+        let groups = [group("dev", &["ddg", "docs"])];
+        assert_eq!(validate_keyword("dev", &engines(), &groups, Some("dev")), None);
+        assert_eq!(validate_keyword("docs", &engines(), &groups, Some("dev")), Some("\"docs\" is already used by Docs.rs.".to_string()));
+        assert_eq!(validate_keyword("dev", &engines(), &groups, None), Some("\"dev\" is already used by the group dev.".to_string()));
+        assert!(validate_keyword("rust.dev", &engines(), &groups, None).is_some_and(|m| m.contains("looks like a domain name")));
+    }
+
+    #[test]
+    fn rejects_malformed_keywords() {
+        assert_eq!(keyword_issue("", None), Some("Enter a keyword.".to_string()));
+        assert_eq!(keyword_issue("my kw", None), Some("The keyword can't contain spaces.".to_string()));
+        assert!(keyword_issue("!kw", None).is_some_and(|m| m.starts_with("Leave out the \"!\"")));
+        for keyword in ["docs.rs", "example.com", "host:8080", "localhost", "LocalHost"] {
+            assert!(keyword_issue(keyword, None).is_some_and(|m| m.contains("looks like a domain name")), "{:?}", keyword);
+        }
+    }
+
+    #[test]
+    fn rejects_unusable_urls() {
+        let url_issues = |url: &str| -> Vec<String> {
+            issues(&engine("New", "new", url), None)
+                .into_iter()
+                .filter(|(field, _)| *field == EngineField::Url)
+                .map(|(_, message)| message)
+                .collect()
+        };
+        assert_eq!(url_issues(""), vec!["Enter the search URL."]);
+        assert_eq!(url_issues("ftp://files.example/?q={}"), vec!["The URL must start with http:// or https://."]);
+        assert_eq!(url_issues("file:///tmp/{}"), vec!["The URL must start with http:// or https://."]);
+        assert!(url_issues("example.com/?q={}")[0].starts_with("Not a valid URL"));
+        assert_eq!(url_issues("https://example.com/search"), vec!["Add {} where the search query goes."]);
+    }
+
+    #[test]
+    fn query_placeholder_may_be_in_params_only() {
+        let mut engine = engine("New", "new", "https://example.com/search");
+        engine.params = vec![param("q", "{}")];
+        assert!(issues(&engine, None).is_empty());
+        engine.params = vec![param("q", "fixed")];
+        assert_eq!(issues(&engine, None), vec![(EngineField::Url, "Add {} where the search query goes.".to_string())]);
+    }
+
+    #[test]
+    fn checks_the_suggestion_url() {
+        let mut engine = engine("New", "new", "https://example.com/?q={}");
+        engine.suggest_url = Some("https://example.com/suggest".to_string());
+        assert_eq!(issues(&engine, None), vec![(EngineField::SuggestUrl, "Add {} where the query goes.".to_string())]);
+        engine.suggest_url = Some("not a url {}".to_string());
+        assert_eq!(issues(&engine, None), vec![(EngineField::SuggestUrl, "Not a valid URL.".to_string())]);
+        engine.suggest_url = Some("  ".to_string());
+        assert!(issues(&engine, None).is_empty());
+    }
+
+    #[test]
+    fn requires_a_name() {
+        assert_eq!(issues(&engine(" ", "new", "https://example.com/?q={}"), None), vec![(EngineField::Name, "Enter a name.".to_string())]);
+    }
+}
@@ -0,0 +1,190 @@
+// Turning the incoming link into something to paste rather than open: a URL
+// without tracking parameters, a Markdown or HTML link, or the page title.
+
+use std::io::Read;
+use std::time::Duration;
+use quick_xml::escape::{resolve_html5_entity, unescape_with};
+use crate::data::post_form::escape_html;
+use crate::data::store::Store;
+
+const TITLE_TIMEOUT: Duration = Duration::from_secs(5);
+// The <title> is near the top; don't download whole pages for it
+const TITLE_READ_LIMIT: u64 = 256 * 1024;
+const TITLE_MAX_CHARS: usize = 200;
+
+// Query parameters that only identify the campaign or click
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "gclsrc", "dclid", "msclkid", "yclid", "twclid", "igshid", "mc_cid", "mc_eid",
+    "_hsenc", "_hsmi", "mkt_tok", "oly_anon_id", "oly_enc_id", "vero_id", "wickedid", "ref_src",
+    "ref_url", "spm", "si",
+];
+const TRACKING_PREFIXES: &[&str] = &["utm_", "pk_", "mtm_", "__hs"];
+
+fn percent_decode(text: &str) -> String {
+    url::form_urlencoded::parse(text.as_bytes()).map(|(name, _)| name.into_owned()).next().unwrap_or_default()
+}
+
+fn is_tracking(name: &str) -> bool {
+    let name = name.to_lowercase();
+    TRACKING_PARAMS.contains(&name.as_str()) || TRACKING_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+/// The URL without tracking parameters (utm_*, fbclid, gclid, ...). Text that is
+/// not a URL is returned trimmed.
+pub fn clean_url(input: &str) -> String {
+    let input = input.trim();
+    let Ok(mut url) = url::Url::parse(input) else {
+        return input.to_string();
+    };
+    // Filter the raw pairs so the kept ones stay encoded exactly as they were
+    let kept: Vec<&str> = url
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or_default();
+            !pair.is_empty() && !is_tracking(&percent_decode(name))
+        })
+        .collect();
+    let query = kept.join("&");
+    if url.query().is_some() {
+        url.set_query((!query.is_empty()).then_some(query.as_str()));
+    }
+    url.to_string()
+}
+
+/// Link text when the page title is unknown: host and path, "github.com/rust-lang/rust".
+pub fn fallback_title(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or_default().trim_start_matches("www.");
+            format!("{}{}", host, parsed.path().trim_end_matches('/'))
+        }
+        Err(_) => url.trim().to_string(),
+    }
+}
+
+pub fn markdown_link(url: &str, title: &str) -> String {
+    let title = title.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]");
+    let url = url.replace('(', "%28").replace(')', "%29").replace(' ', "%20");
+    format!("[{}]({})", title, url)
+}
+
+pub fn html_link(url: &str, title: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(title))
+}
+
+/// Setting: name copied links and saved items after the page's <title>. Off by
+/// default, since fetching a link the user chose not to open can use up one-time
+/// tokens, trigger tracking and reveal their IP; the host and path are used instead.
+pub const FETCH_TITLES_SETTING: &str = "fetch_page_titles";
+
+pub fn fetch_titles_enabled() -> bool {
+    Store::new()
+        .ok()
+        .and_then(|s| s.get_setting(FETCH_TITLES_SETTING).ok().flatten())
+        .is_some_and(|v| v == "1")
+}
+
+/// The page's <title>, or None when it can't be fetched in a few seconds.
+/// Blocking; run it off the UI thread. Only call it if fetch_titles_enabled().
+pub fn fetch_title(url: &str) -> Option<String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(TITLE_TIMEOUT)
+        .user_agent(concat!("OpenNav/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .ok()?;
+    let response = client.get(url).send().ok()?.error_for_status().ok()?;
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|t| t.contains("html"));
+    if !is_html {
+        return None;
+    }
+    let mut head = Vec::new();
+    response.take(TITLE_READ_LIMIT).read_to_end(&mut head).ok()?;
+    parse_title(&String::from_utf8_lossy(&head))
+}
+
+fn parse_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase(); // Same byte offsets as html
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let raw = &html[start..end];
+    let title = unescape_with(raw, resolve_html5_entity).unwrap_or(std::borrow::Cow::Borrowed(raw));
+    let title: String = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title: String = title.chars().take(TITLE_MAX_CHARS).collect();
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_tracking_parameters() {
+        assert_eq!(
+            clean_url("https://example.com/post?utm_source=news&id=42&fbclid=abc&UTM_Medium=mail&pk_campaign=x"),
+            "https://example.com/post?id=42"
+        );
+        assert_eq!(clean_url("https://example.com/?gclid=1&si=2&_hsenc=3&mc_eid=4"), "https://example.com/");
+        assert_eq!(clean_url("https://example.com/?%75tm_source=x&a=1"), "https://example.com/?a=1");
+    }
+
+    #[test]
+    fn keeps_other_parameters_and_the_fragment() {
+        assert_eq!(
+            clean_url("https://example.com/search?q=a%20b&utm_source=x&sort=new#results"),
+            "https://example.com/search?q=a%20b&sort=new#results"
+        );
+        assert_eq!(clean_url("https://example.com/p?utm_campaign=x#top"), "https://example.com/p#top");
+        assert_eq!(clean_url("https://example.com/?site=1&spmx=2"), "https://example.com/?site=1&spmx=2");
+    }
+
+    #[test]
+    fn drops_an_empty_query() {
+        assert_eq!(clean_url("https://example.com/page?"), "https://example.com/page");
+        assert_eq!(clean_url("https://example.com/page?&&"), "https://example.com/page");
+        assert_eq!(clean_url("https://example.com/page"), "https://example.com/page");
+    }
+
+    #[test]
+    fn leaves_non_urls_alone() {
+        assert_eq!(clean_url("  not a url?utm_source=x "), "not a url?utm_source=x");
+    }
+
+    #[test]
+    fn escapes_markdown_links() {
+        assert_eq!(markdown_link("https://example.com/a", "Title"), "[Title](https://example.com/a)");
+        assert_eq!(
+            markdown_link("https://en.wikipedia.org/wiki/Rust_(language) x", r"[a]\b"),
+            r"[\[a\]\\b](https://en.wikipedia.org/wiki/Rust_%28language%29%20x)"
+        );
+    }
+
+    #[test]
+    fn escapes_html_links() {
+        assert_eq!(
+            html_link("https://example.com/?a=1&b=\"2\"", "<Tom & Jerry>"),
+            "<a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">&lt;Tom &amp; Jerry&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn fallback_title_is_host_and_path() {
+        assert_eq!(fallback_title("https://www.github.com/rust-lang/rust/"), "github.com/rust-lang/rust");
+        assert_eq!(fallback_title("https://example.com/"), "example.com");
+        assert_eq!(fallback_title(" not a url "), "not a url");
+    }
+
+    #[test]
+    fn parses_page_titles() {
+        assert_eq!(parse_title("<html><head><TITLE lang=\"en\">\n  Rust &amp; Cargo\n</TITLE>"), Some("Rust & Cargo".to_string()));
+        assert_eq!(parse_title("<title></title>"), None);
+        assert_eq!(parse_title("<p>No title</p>"), None);
+        assert_eq!(parse_title(&format!("<title>{}</title>", "x".repeat(500))).map(|t| t.len()), Some(TITLE_MAX_CHARS));
+    }
+}
@@ -0,0 +1,120 @@
+// The application a link came from ("Opened from: Slack").
+//
+// Apps open links through xdg-open or a similar helper, so the parent process
+// chain is walked past known launch helpers and shells. Sandboxed apps that go
+// through the portal have no useful parent; for those the launcher field of a
+// startup-notification id ("launcher/launchee/pid-seq-host_TIMEn") is used.
+
+const MAX_ANCESTORS: usize = 8;
+
+// Process names that only pass the link on
+const LAUNCH_HELPERS: &[&str] = &[
+    "xdg-open", "gio", "gio-launch-desktop", "gvfs-open", "gnome-open", "kde-open", "kde-open5",
+    "kioclient", "kioclient5", "kioclient6", "exo-open", "exo-helper-2", "mimeopen", "env",
+    "sh", "bash", "dash", "zsh", "fish", "flatpak-spawn", "bwrap", "opennav",
+];
+
+// Reaching one of these means OpenNav was started by the desktop, not by an app
+const SESSION_ROOTS: &[&str] = &[
+    "systemd", "init", "dbus-daemon", "dbus-broker", "xdg-desktop-portal", "gnome-shell",
+    "plasmashell", "kwin_wayland", "kwin_x11", "krunner", "sway", "Hyprland", "xfce4-panel",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceApp {
+    pub id: String,   // Lowercase process or launcher name, what rules match on
+    pub name: String, // For display
+}
+
+impl SourceApp {
+    /// From a process name, app id or .desktop file name, e.g. an id saved earlier.
+    pub fn from_id(raw: &str) -> Option<Self> {
+        let id = raw
+            .rsplit('/')
+            .next()
+            .unwrap_or(raw)
+            .trim_end_matches(".desktop")
+            .trim()
+            .to_lowercase();
+        if id.is_empty() {
+            return None;
+        }
+        // Reverse-DNS ids ("com.slack.Slack") show their last part
+        let short = id.rsplit('.').next().unwrap_or(&id);
+        let mut chars = short.chars();
+        let name = chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        Some(SourceApp { id, name })
+    }
+
+    /// Whether a rule's source pattern names this app. "slack" matches the
+    /// process "slack" and the app id "com.slack.Slack".
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.trim().to_lowercase();
+        !pattern.is_empty()
+            && (self.id == pattern
+                || self.id.rsplit('.').next() == Some(pattern.as_str())
+                || self.name.to_lowercase() == pattern)
+    }
+}
+
+/// Detects the source application of this launch; call once at startup.
+pub fn detect() -> Option<SourceApp> {
+    from_process_tree().or_else(from_startup_id)
+}
+
+fn from_process_tree() -> Option<SourceApp> {
+    let mut pid = parent_pid("self")?;
+    for _ in 0..MAX_ANCESTORS {
+        if pid <= 1 {
+            return None;
+        }
+        let name = process_name(pid)?;
+        if SESSION_ROOTS.contains(&name.as_str()) {
+            return None;
+        }
+        if !LAUNCH_HELPERS.contains(&name.as_str()) {
+            return SourceApp::from_id(&name);
+        }
+        pid = parent_pid(&pid.to_string())?;
+    }
+    None
+}
+
+// The executable's file name, falling back to comm (truncated to 15 bytes)
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .or_else(|| {
+            std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                .ok()
+                .map(|comm| comm.trim().to_string())
+        })
+}
+
+fn parent_pid(pid: &str) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // "pid (comm) state ppid ...": comm may contain spaces and parentheses
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn from_startup_id() -> Option<SourceApp> {
+    ["DESKTOP_STARTUP_ID", "XDG_ACTIVATION_TOKEN"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|token| {
+            // Only the startup-notification format names the launcher; most
+            // activation tokens are opaque
+            let (launcher, rest) = token.split_once('/')?;
+            rest.contains('/').then_some(())?;
+            let launcher = launcher.trim();
+            if SESSION_ROOTS.contains(&launcher) {
+                return None;
+            }
+            SourceApp::from_id(launcher)
+        })
+}
@@ -1,5 +1,8 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use crate::data::config_export::PortableConfig;
+use crate::data::config_file;
 
 use once_cell::sync::Lazy;
 
@@ -13,6 +16,172 @@ static DB_PATH: Lazy<PathBuf> = Lazy::new(|| {
     path
 });
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchEngine {
+    pub name: String,
+    pub keyword: String,
+    pub url: String, // "{}" is replaced by the query, "{1}", "{2}"... by its space-separated arguments
+    pub icon_path: Option<String>,
+    #[serde(default)]
+    pub suggest_url: Option<String>, // OpenSearch suggestions endpoint, "{}" is the query
+    #[serde(default)]
+    pub method: String, // "GET" (also when empty) or "POST"
+    #[serde(default)]
+    pub params: Vec<EngineParam>, // Form fields (POST) or extra query parameters (GET)
+    #[serde(default)]
+    pub browser_id: Option<String>, // Preferred browser for searches using this keyword
+    #[serde(default)]
+    pub launch_directly: bool, // Open in the preferred browser without showing the picker
+}
+
+impl SearchEngine {
+    pub fn is_post(&self) -> bool {
+        self.method.eq_ignore_ascii_case("POST")
+    }
+
+    fn method_or_get(&self) -> &str {
+        if self.is_post() { "POST" } else { "GET" }
+    }
+
+    // NULL when there are none, so plain engines look the same as before
+    fn params_json(&self) -> Option<String> {
+        if self.params.is_empty() {
+            None
+        } else {
+            serde_json::to_string(&self.params).ok()
+        }
+    }
+}
+
+// A name=value pair sent with a search; the value may contain placeholders
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineParam {
+    pub name: String,
+    pub value: String,
+}
+
+// Sends links for a domain (and its subdomains), or links coming from an
+// application, straight to a browser. When both are set both must match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    #[serde(default)]
+    pub id: i64,
+    pub domain: String, // "github.com", "*.corp" or empty for any domain
+    pub browser_id: String,
+    #[serde(default)]
+    pub source_app: Option<String>, // "slack", see source_app::SourceApp::matches
+    #[serde(default)]
+    pub schedule: Option<String>, // Only applies while active, e.g. "Mon-Fri 09:00-18:00"
+    #[serde(default)]
+    pub requires: Option<String>, // Machine conditions, e.g. "iface:tun0", see environment
+}
+
+impl RoutingRule {
+    /// Blank source, schedule and conditions stored as unset, so they can't make
+    /// a rule look more specific than it is.
+    pub fn normalized(mut self) -> Self {
+        for field in [&mut self.source_app, &mut self.schedule, &mut self.requires] {
+            if field.as_deref().is_some_and(|s| s.trim().is_empty()) {
+                *field = None;
+            }
+        }
+        self
+    }
+}
+
+// A keyword that searches several engines at once, e.g. "dev" -> docs.rs, crates.io, GitHub
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineGroup {
+    pub name: String,
+    pub keyword: String,
+    pub members: Vec<String>, // Engine keywords, in opening order
+}
+
+// Launch count of one browser for one site, as kept in domain_stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainStat {
+    pub domain: String,
+    pub browser_id: String,
+    pub usage_count: i64,
+    pub last_used: i64,
+}
+
+// One launched link, for the history view (Ctrl+H)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    pub url: String,
+    pub browser_id: String,
+    pub mode: String, // "normal", "rule" (routing rule), "engine" (engine's preferred browser) or "scheduled" (Open at…)
+}
+
+// A link saved to read later instead of being opened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingItem {
+    pub id: i64,
+    pub url: String,
+    pub title: Option<String>,
+    pub source_app: Option<String>, // Application the link came from, when known
+    pub added_at: i64,
+}
+
+// A link to open in a browser at a later time ("Open at…")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOpen {
+    pub id: i64,
+    pub url: String,
+    pub browser_id: String,
+    pub due_at: i64,
+}
+
+// Oldest history rows beyond this are pruned on insert
+const HISTORY_LIMIT: i64 = 5000;
+
+// Trash entries are dropped on startup after this long
+const TRASH_KEEP_SECS: i64 = 7 * 24 * 60 * 60;
+
+// Payload of a "stats" trash entry: the values a reset overwrote
+#[derive(Serialize, Deserialize)]
+struct StatsSnapshot {
+    recent: bool,                       // last_used was reset, else usage counts
+    browsers: Vec<(String, i64, i64)>,  // (id, usage_count, last_used)
+    domains: Vec<DomainStat>,
+}
+
+/// Unix time in seconds, as stored in the timestamp columns.
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// "just now", "5 min ago", "3 h ago", "2 days ago"
+pub fn format_age(timestamp: i64, now: i64) -> String {
+    let secs = (now - timestamp).max(0);
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", secs / 60),
+        3600..=86_399 => format!("{} h ago", secs / 3600),
+        _ => {
+            let days = secs / 86_400;
+            if days == 1 { "yesterday".to_string() } else { format!("{} days ago", days) }
+        }
+    }
+}
+
+// (name, keyword, url, suggest_url)
+const DEFAULT_ENGINES: &[(&str, &str, &str, Option<&str>)] = &[
+    ("Google", "g", "https://www.google.com/search?q={}", Some("https://suggestqueries.google.com/complete/search?client=firefox&q={}")),
+    ("DuckDuckGo", "d", "https://duckduckgo.com/?q={}", Some("https://duckduckgo.com/ac/?q={}&type=list")),
+    ("Bing", "b", "https://www.bing.com/search?q={}", Some("https://api.bing.com/osjson.aspx?query={}")),
+    ("Brave", "br", "https://search.brave.com/search?q={}", Some("https://search.brave.com/api/suggest?q={}")),
+    ("Ecosia", "e", "https://www.ecosia.org/search?q={}", Some("https://ac.ecosia.org/autocomplete?q={}&type=list")),
+    ("YouTube", "yt", "https://www.youtube.com/results?search_query={}", Some("https://suggestqueries.google.com/complete/search?client=firefox&ds=yt&q={}")),
+    ("GitHub", "gh", "https://github.com/search?q={}", None),
+];
+
 pub struct Store {
     conn: Connection,
 }
@@ -25,6 +194,14 @@ impl Store {
         Ok(store)
     }
 
+    // A fresh database that lives as long as the store
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> Result<Self> {
+        let mut store = Store { conn: Connection::open_in_memory()? };
+        store.init()?;
+        Ok(store)
+    }
+
     fn init(&mut self) -> Result<()> {
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS browser_stats (
@@ -35,15 +212,185 @@ impl Store {
             )",
             [],
         )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS search_engines (
+                keyword TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                url TEXT NOT NULL,
+                icon_path TEXT,
+                suggest_url TEXT,
+                method TEXT NOT NULL DEFAULT 'GET',
+                params TEXT,
+                browser_id TEXT,
+                launch_directly INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        // (domain, browser) launch counts, used to suggest a browser per site
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS domain_stats (
+                domain TEXT NOT NULL,
+                browser_id TEXT NOT NULL,
+                usage_count INTEGER DEFAULT 0,
+                last_used INTEGER DEFAULT 0,
+                PRIMARY KEY (domain, browser_id)
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS routing_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                domain TEXT NOT NULL,
+                browser_id TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                url TEXT NOT NULL,
+                browser_id TEXT NOT NULL,
+                mode TEXT NOT NULL DEFAULT 'normal'
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS reading_list (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                title TEXT,
+                source_app TEXT,
+                added_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduled_opens (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                browser_id TEXT NOT NULL,
+                due_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Members are stored as a JSON array of engine keywords
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS engine_groups (
+                keyword TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                members TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Deleted engines, removed pins and reset stats, kept so they can be undone.
+        // payload is JSON whose shape depends on kind ("engine", "pin", "stats").
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS trash (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                label TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                deleted_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute("DELETE FROM trash WHERE deleted_at < ?1", params![now() - TRASH_KEEP_SECS])?;
+
+        // Migration: suggestion endpoint per engine
+        if !self.has_column("search_engines", "suggest_url")? {
+            self.conn.execute("ALTER TABLE search_engines ADD COLUMN suggest_url TEXT", [])?;
+            // Fill it in for untouched defaults
+            for (_, keyword, url, suggest_url) in DEFAULT_ENGINES {
+                self.conn.execute(
+                    "UPDATE search_engines SET suggest_url = ?1 WHERE keyword = ?2 AND url = ?3",
+                    params![suggest_url, keyword, url],
+                )?;
+            }
+        }
+
+        // Migration: POST and multi-parameter engines
+        if !self.has_column("search_engines", "method")? {
+            self.conn.execute("ALTER TABLE search_engines ADD COLUMN method TEXT NOT NULL DEFAULT 'GET'", [])?;
+        }
+        if !self.has_column("search_engines", "params")? {
+            self.conn.execute("ALTER TABLE search_engines ADD COLUMN params TEXT", [])?;
+        }
+
+        // Migration: preferred browser per engine
+        if !self.has_column("search_engines", "browser_id")? {
+            self.conn.execute("ALTER TABLE search_engines ADD COLUMN browser_id TEXT", [])?;
+            self.conn.execute("ALTER TABLE search_engines ADD COLUMN launch_directly INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+
+        // Migration: rules matching the application a link came from
+        if !self.has_column("routing_rules", "source_app")? {
+            self.conn.execute("ALTER TABLE routing_rules ADD COLUMN source_app TEXT", [])?;
+        }
+
+        // Migration: rules that only apply at certain times
+        if !self.has_column("routing_rules", "schedule")? {
+            self.conn.execute("ALTER TABLE routing_rules ADD COLUMN schedule TEXT", [])?;
+        }
+
+        // Migration: rules depending on machine state (VPN interface, hostname, env)
+        if !self.has_column("routing_rules", "requires")? {
+            self.conn.execute("ALTER TABLE routing_rules ADD COLUMN requires TEXT", [])?;
+        }
+
+        // Browsers preferred at certain times, e.g. a work profile during office
+        // hours. They sort right after pinned browsers while the schedule is active.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS browser_schedules (
+                browser_id TEXT PRIMARY KEY,
+                schedule TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Seed default engines on first run only, so deleted defaults stay deleted
+        let engine_count: i64 = self.conn.query_row("SELECT COUNT(*) FROM search_engines", [], |row| row.get(0))?;
+        if engine_count == 0 {
+            for (name, keyword, url, suggest_url) in DEFAULT_ENGINES {
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO search_engines (keyword, name, url, icon_path, suggest_url) VALUES (?1, ?2, ?3, NULL, ?4)",
+                    params![keyword, name, url, suggest_url],
+                )?;
+            }
+        }
         Ok(())
     }
 
+    fn has_column(&self, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        for name in names {
+            if name? == column {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     pub fn increment_usage(&self, id: &str) -> Result<()> {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-            
+        let now = now();
+
         self.conn.execute(
             "INSERT INTO browser_stats (id, usage_count, is_pinned, last_used)
              VALUES (?1, 1, 0, ?2)
@@ -97,6 +444,12 @@ impl Store {
         Ok(stats)
     }
 
+    pub fn list_pinned(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM browser_stats WHERE is_pinned = 1 ORDER BY id")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
     pub fn clear_stats(&self) -> Result<()> {
         self.conn.execute(
             "UPDATE browser_stats SET usage_count = 0, last_used = 0",
@@ -104,4 +457,710 @@ impl Store {
         )?;
         Ok(())
     }
+
+    // Resets return the trash id of the snapshot taken first, for restore_trash
+    pub fn reset_recent_stats(&self) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.snapshot_stats(true)?;
+        tx.execute("UPDATE browser_stats SET last_used = 0", [])?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn reset_frequent_stats(&self) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.snapshot_stats(false)?;
+        tx.execute("UPDATE browser_stats SET usage_count = 0", [])?;
+        tx.execute("DELETE FROM domain_stats", [])?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn snapshot_stats(&self, recent: bool) -> Result<i64> {
+        let browsers = self
+            .get_stats()?
+            .into_iter()
+            .map(|(id, count, _, last)| (id, count, last))
+            .collect();
+        let domains = if recent { Vec::new() } else { self.list_domain_stats()? };
+        let snapshot = StatsSnapshot { recent, browsers, domains };
+        let label = if recent { "Recent usage" } else { "Usage counts" };
+        self.add_trash("stats", label, &serde_json::to_string(&snapshot).unwrap_or_default())
+    }
+
+    // Records a launch: bumps the browser's usage, the (domain, browser) pair and the history.
+    pub fn record_launch(&self, id: &str, url: &str, mode: &str) -> Result<()> {
+        self.increment_usage(id)?;
+        if let Some(domain) = crate::data::routing::domain_of(url) {
+            self.record_domain_usage(&domain, id)?;
+        }
+        if !url.trim().is_empty() {
+            self.add_history(url.trim(), id, mode)?;
+        }
+        Ok(())
+    }
+
+    pub fn record_domain_usage(&self, domain: &str, id: &str) -> Result<()> {
+        let now = now();
+
+        self.conn.execute(
+            "INSERT INTO domain_stats (domain, browser_id, usage_count, last_used)
+             VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT(domain, browser_id) DO UPDATE SET
+                usage_count = usage_count + 1,
+                last_used = ?3",
+            params![domain, id, now],
+        )?;
+        Ok(())
+    }
+
+    // (browser_id, usage_count) for a domain, most used first
+    pub fn get_domain_stats(&self, domain: &str) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT browser_id, usage_count FROM domain_stats
+             WHERE domain = ?1 ORDER BY usage_count DESC, last_used DESC",
+        )?;
+        let rows = stmt.query_map(params![domain], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn list_domain_stats(&self) -> Result<Vec<DomainStat>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT domain, browser_id, usage_count, last_used FROM domain_stats ORDER BY domain, usage_count DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(DomainStat {
+                domain: row.get(0)?,
+                browser_id: row.get(1)?,
+                usage_count: row.get(2)?,
+                last_used: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // History
+    pub fn add_history(&self, url: &str, browser_id: &str, mode: &str) -> Result<()> {
+        let now = now();
+
+        self.conn.execute(
+            "INSERT INTO history (timestamp, url, browser_id, mode) VALUES (?1, ?2, ?3, ?4)",
+            params![now, url, browser_id, mode],
+        )?;
+        self.conn.execute(
+            "DELETE FROM history WHERE id <= (SELECT MAX(id) FROM history) - ?1",
+            params![HISTORY_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    // Newest first
+    pub fn list_history(&self, limit: i64) -> Result<Vec<HistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, url, browser_id, mode FROM history ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(HistoryEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                url: row.get(2)?,
+                browser_id: row.get(3)?,
+                mode: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Distinct URLs with how often they were opened, most used first (for autocompletion)
+    pub fn list_history_urls(&self, limit: i64) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT url, COUNT(*) AS uses FROM history
+             GROUP BY url ORDER BY uses DESC, MAX(timestamp) DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn delete_history_entry(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM history WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Empties the history, keeping it in the trash. Returns the trash id.
+    pub fn clear_history(&self) -> Result<i64> {
+        let entries = self.list_history(HISTORY_LIMIT)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.add_trash("history", "History", &serde_json::to_string(&entries).unwrap_or_default())?;
+        tx.execute("DELETE FROM history", [])?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    // Reading list
+    pub fn add_reading_item(&self, url: &str, title: Option<&str>, source_app: Option<&str>) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO reading_list (url, title, source_app, added_at) VALUES (?1, ?2, ?3, ?4)",
+            params![url, title, source_app, now()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    // Newest first
+    pub fn list_reading_items(&self) -> Result<Vec<ReadingItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, url, title, source_app, added_at FROM reading_list ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ReadingItem {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                title: row.get(2)?,
+                source_app: row.get(3)?,
+                added_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn delete_reading_item(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM reading_list WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Scheduled opens
+    pub fn add_scheduled_open(&self, url: &str, browser_id: &str, due_at: i64) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO scheduled_opens (url, browser_id, due_at) VALUES (?1, ?2, ?3)",
+            params![url, browser_id, due_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    // Soonest first
+    pub fn list_scheduled_opens(&self) -> Result<Vec<ScheduledOpen>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, url, browser_id, due_at FROM scheduled_opens ORDER BY due_at, id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ScheduledOpen {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                browser_id: row.get(2)?,
+                due_at: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Removes it and says whether it was still there, so a daemon and a timer
+    // firing at the same time don't both open it
+    pub fn claim_scheduled_open(&self, id: i64) -> Result<bool> {
+        Ok(self.conn.execute("DELETE FROM scheduled_opens WHERE id = ?1", params![id])? == 1)
+    }
+
+    // Settings
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+    }
+
+    // config.toml's default_engine, else the "search_engine" setting, which older
+    // versions stored as an engine name
+    pub fn default_engine_keyword(&self) -> String {
+        if let Some(keyword) = config_file::default_engine() {
+            return keyword;
+        }
+        match self.get_setting("search_engine").ok().flatten() {
+            None => "g".to_string(),
+            Some(k) => match k.as_str() {
+                "Google" => "g",
+                "DuckDuckGo" => "d",
+                "Bing" => "b",
+                "Brave" => "br",
+                "Ecosia" => "e",
+                k => k,
+            }
+            .to_string(),
+        }
+    }
+
+    pub fn list_settings(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    // Settings whose key starts with `prefix` ("keybinding.")
+    pub fn list_settings_with_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT key, value FROM settings WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key")?;
+        let rows = stmt.query_map(params![prefix], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = ?2",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_setting(&self, key: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
+    // Search Engines
+    pub fn list_engines(&self) -> Result<Vec<SearchEngine>> {
+        let mut stmt = self.conn.prepare("SELECT name, keyword, url, icon_path, suggest_url, method, params, browser_id, launch_directly FROM search_engines ORDER BY name")?;
+        let rows = stmt.query_map([], |row| {
+            let params: Option<String> = row.get(6)?;
+            Ok(SearchEngine {
+                name: row.get(0)?,
+                keyword: row.get(1)?,
+                url: row.get(2)?,
+                icon_path: row.get(3)?,
+                suggest_url: row.get(4)?,
+                method: row.get(5)?,
+                params: params.and_then(|p| serde_json::from_str(&p).ok()).unwrap_or_default(),
+                browser_id: row.get(7)?,
+                launch_directly: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // What searches actually use: the table with config.toml's engines layered over it
+    pub fn list_active_engines(&self) -> Result<Vec<SearchEngine>> {
+        Ok(config_file::layer_engines(self.list_engines()?))
+    }
+
+    pub fn add_engine(&self, engine: &SearchEngine) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO search_engines (keyword, name, url, icon_path, suggest_url, method, params, browser_id, launch_directly) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![engine.keyword, engine.name, engine.url, engine.icon_path, engine.suggest_url, engine.method_or_get(), engine.params_json(), engine.browser_id, engine.launch_directly],
+        )?;
+        Ok(())
+    }
+
+    // Keyed by the original keyword so the keyword itself can be edited; groups
+    // listing the engine follow the new keyword
+    pub fn update_engine(&self, original_keyword: &str, engine: &SearchEngine) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE search_engines SET keyword = ?1, name = ?2, url = ?3, icon_path = ?4, suggest_url = ?5, method = ?6, params = ?7, browser_id = ?8, launch_directly = ?9 WHERE keyword = ?10",
+            params![engine.keyword, engine.name, engine.url, engine.icon_path, engine.suggest_url, engine.method_or_get(), engine.params_json(), engine.browser_id, engine.launch_directly, original_keyword],
+        )?;
+        if engine.keyword != original_keyword {
+            for mut group in self.list_engine_groups()? {
+                if !group.members.iter().any(|m| m == original_keyword) {
+                    continue;
+                }
+                for member in group.members.iter_mut().filter(|m| *m == original_keyword) {
+                    *member = engine.keyword.clone();
+                }
+                tx.execute(
+                    "UPDATE engine_groups SET members = ?1 WHERE keyword = ?2",
+                    params![serde_json::to_string(&group.members).unwrap_or_default(), group.keyword],
+                )?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn delete_engine(&self, keyword: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM search_engines WHERE keyword = ?1", params![keyword])?;
+        Ok(())
+    }
+
+    // Adds engines in one transaction. Engines listed in `replace` overwrite an
+    // existing engine with the same keyword; other keyword clashes are skipped.
+    pub fn import_engines(&self, engines: &[SearchEngine], replace: &[String]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut imported = 0;
+        for engine in engines {
+            let sql = if replace.contains(&engine.keyword) {
+                "INSERT OR REPLACE INTO search_engines (keyword, name, url, icon_path, suggest_url, method, params, browser_id, launch_directly) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            } else {
+                "INSERT OR IGNORE INTO search_engines (keyword, name, url, icon_path, suggest_url, method, params, browser_id, launch_directly) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            };
+            imported += tx.execute(
+                sql,
+                params![engine.keyword, engine.name, engine.url, engine.icon_path, engine.suggest_url, engine.method_or_get(), engine.params_json(), engine.browser_id, engine.launch_directly],
+            )?;
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+
+    // Engine Groups
+    pub fn list_engine_groups(&self) -> Result<Vec<EngineGroup>> {
+        let mut stmt = self.conn.prepare("SELECT name, keyword, members FROM engine_groups ORDER BY name")?;
+        let rows = stmt.query_map([], |row| {
+            let members: String = row.get(2)?;
+            Ok(EngineGroup {
+                name: row.get(0)?,
+                keyword: row.get(1)?,
+                members: serde_json::from_str(&members).unwrap_or_default(),
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn add_engine_group(&self, group: &EngineGroup) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO engine_groups (keyword, name, members) VALUES (?1, ?2, ?3)",
+            params![group.keyword, group.name, serde_json::to_string(&group.members).unwrap_or_default()],
+        )?;
+        Ok(())
+    }
+
+    // Keyed by the original keyword so the keyword itself can be edited
+    pub fn update_engine_group(&self, original_keyword: &str, group: &EngineGroup) -> Result<()> {
+        self.conn.execute(
+            "UPDATE engine_groups SET keyword = ?1, name = ?2, members = ?3 WHERE keyword = ?4",
+            params![group.keyword, group.name, serde_json::to_string(&group.members).unwrap_or_default(), original_keyword],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_engine_group(&self, keyword: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM engine_groups WHERE keyword = ?1", params![keyword])?;
+        Ok(())
+    }
+
+    // Routing Rules
+    pub fn list_routing_rules(&self) -> Result<Vec<RoutingRule>> {
+        let mut stmt = self.conn.prepare("SELECT id, domain, browser_id, source_app, schedule, requires FROM routing_rules ORDER BY domain, source_app")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RoutingRule {
+                id: row.get(0)?,
+                domain: row.get(1)?,
+                browser_id: row.get(2)?,
+                source_app: row.get(3)?,
+                schedule: row.get(4)?,
+                requires: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Stored rules plus config.toml's, as used for routing
+    pub fn list_active_routing_rules(&self) -> Result<Vec<RoutingRule>> {
+        Ok(config_file::layer_rules(self.list_routing_rules()?))
+    }
+
+    pub fn add_routing_rule(&self, rule: &RoutingRule) -> Result<i64> {
+        let rule = rule.clone().normalized();
+        self.conn.execute(
+            "INSERT INTO routing_rules (domain, browser_id, source_app, schedule, requires) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![rule.domain, rule.browser_id, rule.source_app, rule.schedule, rule.requires],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn delete_routing_rule(&self, id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM routing_rules WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Browser Schedules: (browser_id, schedule)
+    pub fn list_browser_schedules(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT browser_id, schedule FROM browser_schedules ORDER BY browser_id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn set_browser_schedule(&self, browser_id: &str, schedule: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO browser_schedules (browser_id, schedule) VALUES (?1, ?2)
+             ON CONFLICT(browser_id) DO UPDATE SET schedule = ?2",
+            params![browser_id, schedule],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_browser_schedule(&self, browser_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM browser_schedules WHERE browser_id = ?1", params![browser_id])?;
+        Ok(())
+    }
+
+    // Trash
+    fn add_trash(&self, kind: &str, label: &str, payload: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO trash (kind, label, payload, deleted_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, label, payload, now()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    // Deletes an engine, keeping it in the trash. Returns the trash id.
+    pub fn trash_engine(&self, keyword: &str) -> Result<i64> {
+        let engine = self
+            .list_engines()?
+            .into_iter()
+            .find(|e| e.keyword == keyword)
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.add_trash("engine", &engine.name, &serde_json::to_string(&engine).unwrap_or_default())?;
+        tx.execute("DELETE FROM search_engines WHERE keyword = ?1", params![keyword])?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    // Unpins a browser, keeping the pin in the trash. Returns the trash id.
+    pub fn trash_pin(&self, browser_id: &str) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.add_trash("pin", browser_id, &serde_json::to_string(browser_id).unwrap_or_default())?;
+        tx.execute("UPDATE browser_stats SET is_pinned = 0 WHERE id = ?1", params![browser_id])?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    // Puts a trashed item back and removes it from the trash. Returns its kind.
+    // A restored engine doesn't overwrite one added with the same keyword since;
+    // restored usage counts are added to launches made after the reset.
+    pub fn restore_trash(&self, id: i64) -> Result<String> {
+        let (kind, payload): (String, String) = self.conn.query_row(
+            "SELECT kind, payload FROM trash WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let invalid = |e: serde_json::Error| rusqlite::Error::ToSqlConversionFailure(Box::new(e));
+
+        let tx = self.conn.unchecked_transaction()?;
+        match kind.as_str() {
+            "engine" => {
+                let engine: SearchEngine = serde_json::from_str(&payload).map_err(invalid)?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO search_engines (keyword, name, url, icon_path, suggest_url, method, params, browser_id, launch_directly) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![engine.keyword, engine.name, engine.url, engine.icon_path, engine.suggest_url, engine.method_or_get(), engine.params_json(), engine.browser_id, engine.launch_directly],
+                )?;
+            }
+            "pin" => {
+                let browser_id: String = serde_json::from_str(&payload).map_err(invalid)?;
+                tx.execute(
+                    "INSERT INTO browser_stats (id, usage_count, is_pinned, last_used)
+                     VALUES (?1, 0, 1, 0)
+                     ON CONFLICT(id) DO UPDATE SET is_pinned = 1",
+                    params![browser_id],
+                )?;
+            }
+            "history" => {
+                // Ids are never reused (AUTOINCREMENT), so launches made since stay newer
+                let entries: Vec<HistoryEntry> = serde_json::from_str(&payload).map_err(invalid)?;
+                for entry in &entries {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO history (id, timestamp, url, browser_id, mode) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![entry.id, entry.timestamp, entry.url, entry.browser_id, entry.mode],
+                    )?;
+                }
+            }
+            "stats" => {
+                let snapshot: StatsSnapshot = serde_json::from_str(&payload).map_err(invalid)?;
+                for (browser_id, count, last) in &snapshot.browsers {
+                    if snapshot.recent {
+                        tx.execute(
+                            "UPDATE browser_stats SET last_used = MAX(last_used, ?2) WHERE id = ?1",
+                            params![browser_id, last],
+                        )?;
+                    } else {
+                        tx.execute(
+                            "UPDATE browser_stats SET usage_count = usage_count + ?2 WHERE id = ?1",
+                            params![browser_id, count],
+                        )?;
+                    }
+                }
+                for stat in &snapshot.domains {
+                    tx.execute(
+                        "INSERT INTO domain_stats (domain, browser_id, usage_count, last_used)
+                         VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT(domain, browser_id) DO UPDATE SET
+                            usage_count = usage_count + ?3,
+                            last_used = MAX(last_used, ?4)",
+                        params![stat.domain, stat.browser_id, stat.usage_count, stat.last_used],
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        tx.execute("DELETE FROM trash WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(kind)
+    }
+
+    // Portable configuration (see config_export). With `replace` the current
+    // settings, engines, groups, pins, rules and per-site stats are dropped first;
+    // otherwise entries from the file are added or overwrite those with the same key.
+    pub fn import_config(&self, config: &PortableConfig, replace: bool) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if replace {
+            tx.execute("DELETE FROM settings", [])?;
+            tx.execute("DELETE FROM search_engines", [])?;
+            tx.execute("DELETE FROM engine_groups", [])?;
+            tx.execute("DELETE FROM routing_rules", [])?;
+            tx.execute("DELETE FROM browser_schedules", [])?;
+            tx.execute("DELETE FROM domain_stats", [])?;
+            tx.execute("UPDATE browser_stats SET is_pinned = 0", [])?;
+        }
+
+        for (key, value) in &config.settings {
+            tx.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = ?2",
+                params![key, value],
+            )?;
+        }
+        for engine in &config.engines {
+            tx.execute(
+                "INSERT OR REPLACE INTO search_engines (keyword, name, url, icon_path, suggest_url, method, params, browser_id, launch_directly) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![engine.keyword, engine.name, engine.url, engine.icon_path, engine.suggest_url, engine.method_or_get(), engine.params_json(), engine.browser_id, engine.launch_directly],
+            )?;
+        }
+        for group in &config.engine_groups {
+            tx.execute(
+                "INSERT OR REPLACE INTO engine_groups (keyword, name, members) VALUES (?1, ?2, ?3)",
+                params![group.keyword, group.name, serde_json::to_string(&group.members).unwrap_or_default()],
+            )?;
+        }
+        for rule in config.routing_rules.iter().cloned().map(RoutingRule::normalized) {
+            tx.execute(
+                "INSERT INTO routing_rules (domain, browser_id, source_app, schedule, requires)
+                 SELECT ?1, ?2, ?3, ?4, ?5 WHERE NOT EXISTS
+                    (SELECT 1 FROM routing_rules
+                     WHERE domain = ?1 AND browser_id = ?2 AND source_app IS ?3 AND schedule IS ?4 AND requires IS ?5)",
+                params![rule.domain, rule.browser_id, rule.source_app, rule.schedule, rule.requires],
+            )?;
+        }
+        for (browser_id, schedule) in &config.browser_schedules {
+            tx.execute(
+                "INSERT INTO browser_schedules (browser_id, schedule) VALUES (?1, ?2)
+                 ON CONFLICT(browser_id) DO UPDATE SET schedule = ?2",
+                params![browser_id, schedule],
+            )?;
+        }
+        for id in &config.pinned_browsers {
+            tx.execute(
+                "INSERT INTO browser_stats (id, usage_count, is_pinned, last_used)
+                 VALUES (?1, 0, 1, 0)
+                 ON CONFLICT(id) DO UPDATE SET is_pinned = 1",
+                params![id],
+            )?;
+        }
+        // Counts from another machine don't add up with local ones, keep the larger
+        for stat in &config.domain_stats {
+            tx.execute(
+                "INSERT INTO domain_stats (domain, browser_id, usage_count, last_used)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(domain, browser_id) DO UPDATE SET
+                    usage_count = MAX(usage_count, ?3),
+                    last_used = MAX(last_used, ?4)",
+                params![stat.domain, stat.browser_id, stat.usage_count, stat.last_used],
+            )?;
+        }
+        tx.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imported_keyword_clashes_are_skipped_unless_replaced() {
+        let store = Store::in_memory().unwrap();
+        let engine = |keyword: &str, url: &str| SearchEngine {
+            name: format!("Imported {}", keyword),
+            keyword: keyword.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        };
+        let imported = store
+            .import_engines(
+                &[
+                    engine("g", "https://skipped.example/?q={}"),
+                    engine("gh", "https://replaced.example/?q={}"),
+                    engine("cr", "https://crates.io/search?q={}"),
+                ],
+                &["gh".to_string()],
+            )
+            .unwrap();
+        assert_eq!(imported, 2);
+
+        let engines = store.list_engines().unwrap();
+        let url = |keyword: &str| engines.iter().find(|e| e.keyword == keyword).map(|e| e.url.clone());
+        assert_eq!(url("g"), Some("https://www.google.com/search?q={}".to_string()));
+        assert_eq!(url("gh"), Some("https://replaced.example/?q={}".to_string()));
+        assert_eq!(url("cr"), Some("https://crates.io/search?q={}".to_string()));
+    }
+
+    fn group(keyword: &str, members: &[&str]) -> EngineGroup {
+        EngineGroup {
+            name: keyword.to_string(),
+            keyword: keyword.to_string(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn renaming_an_engine_updates_group_members() {
+        let store = Store::in_memory().unwrap();
+        store.add_engine_group(&group("dev", &["gh", "ddg", "gh"])).unwrap();
+        store.add_engine_group(&group("web", &["g", "b"])).unwrap();
+
+        let mut github = store.list_engines().unwrap().into_iter().find(|e| e.keyword == "gh").unwrap();
+        github.keyword = "github".to_string();
+        store.update_engine("gh", &github).unwrap();
+
+        let groups = store.list_engine_groups().unwrap();
+        let members = |keyword: &str| groups.iter().find(|g| g.keyword == keyword).unwrap().members.clone();
+        assert_eq!(members("dev"), vec!["github", "ddg", "github"]);
+        assert_eq!(members("web"), vec!["g", "b"]);
+        assert!(store.list_engines().unwrap().iter().any(|e| e.keyword == "github"));
+        assert!(!store.list_engines().unwrap().iter().any(|e| e.keyword == "gh"));
+    }
+
+    #[test]
+    fn updating_an_engine_in_place_keeps_groups() {
+        let store = Store::in_memory().unwrap();
+        store.add_engine_group(&group("dev", &["gh", "ddg"])).unwrap();
+        let mut github = store.list_engines().unwrap().into_iter().find(|e| e.keyword == "gh").unwrap();
+        github.name = "GitHub Code".to_string();
+        store.update_engine("gh", &github).unwrap();
+        assert_eq!(store.list_engine_groups().unwrap()[0].members, vec!["gh", "ddg"]);
+    }
+
+    #[test]
+    fn cleared_history_can_be_restored() {
+        let store = Store::in_memory().unwrap();
+        store.add_history("https://example.com/a", "firefox.desktop", "normal").unwrap();
+        store.add_history("https://example.com/b", "firefox.desktop", "rule").unwrap();
+        let trash_id = store.clear_history().unwrap();
+        assert!(store.list_history(10).unwrap().is_empty());
+
+        store.add_history("https://example.com/c", "epiphany.desktop", "normal").unwrap();
+        assert_eq!(store.restore_trash(trash_id).unwrap(), "history");
+        let urls: Vec<String> = store.list_history(10).unwrap().into_iter().map(|e| e.url).collect();
+        assert_eq!(urls, vec!["https://example.com/c", "https://example.com/b", "https://example.com/a"]);
+    }
+
+    #[test]
+    fn blank_rule_fields_are_stored_unset() {
+        let store = Store::in_memory().unwrap();
+        store
+            .add_routing_rule(&RoutingRule {
+                id: 0,
+                domain: "github.com".to_string(),
+                browser_id: "firefox.desktop".to_string(),
+                source_app: Some(String::new()),
+                schedule: Some(" ".to_string()),
+                requires: Some("iface:tun0".to_string()),
+            })
+            .unwrap();
+        let rule = &store.list_routing_rules().unwrap()[0];
+        assert_eq!((rule.source_app.as_deref(), rule.schedule.as_deref()), (None, None));
+        assert_eq!(rule.requires.as_deref(), Some("iface:tun0"));
+    }
 }
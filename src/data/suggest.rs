@@ -0,0 +1,153 @@
+// Live search suggestions from an engine's OpenSearch suggestion endpoint.
+//
+// Only used when the user opted in ("search_suggestions" setting), since every
+// typed query is sent to the engine. Runs blocking; callers move it off the main thread.
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+const SUGGEST_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_REMOTE_SUGGESTIONS: usize = 6;
+
+/// Fills the query into a suggestion URL template ("{}" or OpenSearch "{searchTerms}").
+pub fn build_suggest_url(template: &str, query: &str) -> String {
+    crate::data::search::fill_template(template, query)
+}
+
+/// Parses the OpenSearch suggestions format `["query", ["s1", "s2", ...], ...]`.
+/// DuckDuckGo's `[{"phrase": "s1"}, ...]` is accepted as well.
+pub fn parse_suggestions(body: &str) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return Vec::new();
+    };
+    let Some(items) = value.as_array() else {
+        return Vec::new();
+    };
+
+    let strings: Vec<String> = match items.get(1).and_then(|v| v.as_array()) {
+        Some(list) => list.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        None => items
+            .iter()
+            .filter_map(|v| v.get("phrase").and_then(|p| p.as_str()).map(String::from))
+            .collect(),
+    };
+
+    strings.into_iter().take(MAX_REMOTE_SUGGESTIONS).collect()
+}
+
+pub fn fetch_suggestions(template: &str, query: &str) -> Result<Vec<String>> {
+    let url = build_suggest_url(template, query);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(SUGGEST_TIMEOUT)
+        .user_agent(concat!("OpenNav/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let body = client
+        .get(&url)
+        .send()
+        .with_context(|| format!("Suggestion request to {} failed", url))?
+        .error_for_status()?
+        .text()?;
+
+    Ok(parse_suggestions(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    // Answers one request on a local port with `response`, or holds it unanswered
+    // when None. Returns a suggestion template for it and the request line.
+    fn serve_once(response: Option<String>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let template = format!("http://{}/complete?q={{}}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line != "\r\n" {
+                line.clear();
+            }
+            let _ = tx.send(request_line.trim().to_string());
+            match response {
+                Some(response) => reader.get_mut().write_all(response.as_bytes()).unwrap(),
+                None => std::thread::sleep(SUGGEST_TIMEOUT * 2),
+            }
+        });
+        (template, rx)
+    }
+
+    fn json_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/x-suggestions+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    #[test]
+    fn parses_opensearch_suggestions() {
+        assert_eq!(parse_suggestions(r#"["rust", ["rust lang", "rustup"]]"#), vec!["rust lang", "rustup"]);
+    }
+
+    #[test]
+    fn parses_suggestions_with_descriptions_and_urls() {
+        let body = r#"["rust", ["Rust", "Rust (film)"], ["A language", "A film"], ["https://a/1", "https://a/2"]]"#;
+        assert_eq!(parse_suggestions(body), vec!["Rust", "Rust (film)"]);
+    }
+
+    #[test]
+    fn parses_duckduckgo_phrases() {
+        assert_eq!(parse_suggestions(r#"[{"phrase": "rust"}, {"phrase": "rustc"}]"#), vec!["rust", "rustc"]);
+    }
+
+    #[test]
+    fn skips_non_string_suggestions_and_caps_the_count() {
+        assert_eq!(parse_suggestions(r#"["q", ["a", 1, null, "b"]]"#), vec!["a", "b"]);
+        let many = r#"["q", ["1", "2", "3", "4", "5", "6", "7", "8"]]"#;
+        assert_eq!(parse_suggestions(many).len(), MAX_REMOTE_SUGGESTIONS);
+    }
+
+    #[test]
+    fn invalid_bodies_give_no_suggestions() {
+        for body in ["", "not json", "{}", r#"{"q": ["a"]}"#, r#"["q"]"#, r#"["q", "a"]"#] {
+            assert!(parse_suggestions(body).is_empty(), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn fetches_suggestions_for_the_query() {
+        let (template, request) = serve_once(Some(json_response("200 OK", r#"["a b", ["a b c"]]"#)));
+        assert_eq!(fetch_suggestions(&template, "a b").unwrap(), vec!["a b c"]);
+        assert_eq!(request.recv().unwrap(), "GET /complete?q=a+b HTTP/1.1");
+    }
+
+    #[test]
+    fn http_errors_are_reported() {
+        let (template, _request) = serve_once(Some(json_response("500 Internal Server Error", "[]")));
+        let error = fetch_suggestions(&template, "rust").unwrap_err();
+        assert!(error.to_string().contains("500"), "{:#}", error);
+    }
+
+    #[test]
+    fn unreachable_endpoints_are_reported() {
+        // Bound and dropped: nothing listens on the port any more
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert!(fetch_suggestions(&format!("http://127.0.0.1:{}/?q={{}}", port), "rust").is_err());
+    }
+
+    #[test]
+    fn slow_endpoints_time_out() {
+        let (template, _request) = serve_once(None);
+        let started = std::time::Instant::now();
+        assert!(fetch_suggestions(&template, "rust").is_err());
+        assert!(started.elapsed() < SUGGEST_TIMEOUT * 2);
+    }
+}
@@ -6,8 +6,40 @@ mod ui;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let url = args.get(1).cloned();
+
+    // Config export/import, the read-later commands and the scheduled-link daemon
+    // run without opening a window
+    if let Some(code) = data::config_export::run_cli(&args) {
+        std::process::exit(code);
+    }
+    if let Some(code) = data::read_later::run_cli(&args) {
+        std::process::exit(code);
+    }
+    if let Some(code) = data::delayed_open::run_cli(&args) {
+        std::process::exit(code);
+    }
+
+    // `--from-later ID` opens a saved link, keeping the app it was saved from
+    let from_later = match data::read_later::from_later_arg(&args) {
+        Ok(item) => item,
+        Err(e) => {
+            eprintln!("opennav: {:#}", e);
+            std::process::exit(1);
+        }
+    };
+    let url = match &from_later {
+        Some(item) => Some(item.url.clone()),
+        None => args.get(1).cloned(),
+    };
+    // The app that opened the link, for source-app routing rules and the picker
+    let source = match &from_later {
+        Some(item) => data::read_later::source_app(item),
+        None => data::source_app::detect(),
+    };
+    if let Some(ref s) = source {
+        log::info!("Opened from {} ({})", s.name, s.id);
+    }
     
-    let app = app::App::new(url);
+    let app = app::App::new(url, source, from_later.map(|item| item.id));
     app.run();
 }
@@ -0,0 +1,499 @@
+// Autocompletion popover for the URL entry.
+//
+// Suggests previously opened URLs and domains from the launch history, and search
+// engine keywords (with the engine's name) while the first word is being typed.
+// With live search enabled, queries also get suggestions from the engine's
+// suggestion endpoint (debounced, fetched off the main thread).
+// Tab accepts the highlighted suggestion, Ctrl+N / Ctrl+P move the highlight.
+// Down is left alone so it keeps moving focus to the browser list.
+
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Entry, Label, ListBox, Orientation, Popover};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::data::{routing, search, suggest};
+use crate::data::store::{SearchEngine, Store};
+
+const MAX_SUGGESTIONS: usize = 8;
+const MAX_DOMAINS: usize = 3;
+// How many distinct history URLs are loaded when the picker opens
+const HISTORY_URL_LIMIT: i64 = 1000;
+// Wait for a typing pause before asking the engine
+const SUGGEST_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suggestion {
+    Url(String),
+    Domain(String),
+    Engine { keyword: String, name: String },
+    Search(String), // from the engine's suggestion endpoint
+}
+
+impl Suggestion {
+    // Text placed in the entry when the suggestion is accepted
+    fn completion_text(&self) -> String {
+        match self {
+            Suggestion::Url(url) => url.clone(),
+            Suggestion::Domain(domain) => domain.clone(),
+            Suggestion::Engine { keyword, .. } => format!("{} ", keyword),
+            Suggestion::Search(text) => text.clone(),
+        }
+    }
+
+    fn markup(&self) -> String {
+        let esc = |s: &str| gtk4::glib::markup_escape_text(s).to_string();
+        match self {
+            Suggestion::Url(url) => esc(url),
+            Suggestion::Domain(domain) => format!("<b>{}</b>", esc(domain)),
+            Suggestion::Engine { keyword, name } => {
+                format!("<tt>{}</tt>  <span color='gray'>Search {}</span>", esc(keyword), esc(name))
+            }
+            Suggestion::Search(text) => esc(text),
+        }
+    }
+
+    fn icon_name(&self) -> &'static str {
+        match self {
+            Suggestion::Url(_) => "document-open-recent-symbolic",
+            Suggestion::Domain(_) => "network-server-symbolic",
+            Suggestion::Engine { .. } => "system-search-symbolic",
+            Suggestion::Search(_) => "edit-find-symbolic",
+        }
+    }
+}
+
+// "https://www.example.com/x" -> "example.com/x", for prefix matching what people type
+fn strip_scheme(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    rest.strip_prefix("www.").unwrap_or(rest)
+}
+
+/// Suggestions for the current entry text. `history` is (url, uses), most used first.
+pub fn compute_suggestions(text: &str, history: &[(String, i64)], engines: &[SearchEngine]) -> Vec<Suggestion> {
+    let text = text.trim_start();
+    let typed = text.to_lowercase();
+    if typed.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut out = Vec::new();
+
+    // Engine keywords: "g" -> g, gh...; "gh " / "gh rust" -> the GitHub engine itself
+    match typed.split_once(' ') {
+        Some((first, _)) => {
+            if let Some(e) = engines.iter().find(|e| e.keyword.to_lowercase() == first) {
+                out.push(Suggestion::Engine { keyword: e.keyword.clone(), name: e.name.clone() });
+            }
+            // Queries don't get URL suggestions
+            return out;
+        }
+        None => {
+            // Shortest keyword first, so an exact match ("g") leads
+            let mut matching: Vec<&SearchEngine> = engines
+                .iter()
+                .filter(|e| e.keyword.to_lowercase().starts_with(&typed))
+                .collect();
+            matching.sort_by_key(|e| e.keyword.len());
+            for e in matching {
+                out.push(Suggestion::Engine { keyword: e.keyword.clone(), name: e.name.clone() });
+            }
+        }
+    }
+
+    // Domains, aggregated from history in usage order
+    let mut domains: Vec<String> = Vec::new();
+    for (url, _) in history {
+        if let Some(domain) = routing::domain_of(url) {
+            if domain.starts_with(&typed) && domain != typed && !domains.contains(&domain) {
+                domains.push(domain);
+                if domains.len() == MAX_DOMAINS {
+                    break;
+                }
+            }
+        }
+    }
+    out.extend(domains.into_iter().map(Suggestion::Domain));
+
+    // Full URLs: prefix matches first, then URLs that merely contain the text
+    let typed_stripped = strip_scheme(&typed);
+    let (prefix, contains): (Vec<&String>, Vec<&String>) = history
+        .iter()
+        .map(|(url, _)| url)
+        .filter(|url| url.to_lowercase() != typed)
+        .filter(|url| url.to_lowercase().contains(typed_stripped))
+        .partition(|url| strip_scheme(&url.to_lowercase()).starts_with(typed_stripped));
+    out.extend(prefix.into_iter().chain(contains).map(|u| Suggestion::Url(u.clone())));
+
+    out.truncate(MAX_SUGGESTIONS);
+    out
+}
+
+pub struct UrlCompletion {
+    entry: gtk4::glib::WeakRef<Entry>,
+    popover: Popover,
+    list_box: ListBox,
+    suggestions: RefCell<Vec<Suggestion>>,
+    history: Vec<(String, i64)>,
+    engines: Rc<RefCell<Vec<SearchEngine>>>,
+    default_keyword: Rc<RefCell<String>>,
+    // Set while we change the entry text ourselves, so accepting doesn't reopen the popover
+    suppress: Cell<bool>,
+    // Live search (opt-in): results apply only while the entry still holds `remote_for`
+    live_search: bool,
+    remote: RefCell<Vec<String>>,
+    remote_for: RefCell<String>,
+    pending: RefCell<Option<gtk4::glib::SourceId>>,
+    generation: Cell<u64>,
+}
+
+impl UrlCompletion {
+    pub fn attach(entry: &Entry, engines: Rc<RefCell<Vec<SearchEngine>>>, default_keyword: Rc<RefCell<String>>) -> Rc<Self> {
+        let list_box = ListBox::new();
+        list_box.set_selection_mode(gtk4::SelectionMode::Single);
+        list_box.set_can_focus(false);
+        list_box.add_css_class("completion-list");
+
+        let popover = Popover::builder()
+            .has_arrow(false)
+            .autohide(false)
+            .can_focus(false)
+            .position(gtk4::PositionType::Bottom)
+            .halign(Align::Start)
+            .child(&list_box)
+            .build();
+        popover.add_css_class("url-completion");
+        popover.set_parent(entry);
+
+        let store = Store::new().ok();
+        let history = store
+            .as_ref()
+            .and_then(|s| s.list_history_urls(HISTORY_URL_LIMIT).ok())
+            .unwrap_or_default();
+        let live_search = store
+            .as_ref()
+            .and_then(|s| s.get_setting("search_suggestions").ok().flatten())
+            .is_some_and(|v| v == "1");
+
+        let completion = Rc::new(UrlCompletion {
+            entry: entry.downgrade(),
+            popover,
+            list_box,
+            suggestions: RefCell::new(Vec::new()),
+            history,
+            engines,
+            default_keyword,
+            suppress: Cell::new(false),
+            live_search,
+            remote: RefCell::new(Vec::new()),
+            remote_for: RefCell::new(String::new()),
+            pending: RefCell::new(None),
+            generation: Cell::new(0),
+        });
+
+        // Recompute on typing
+        let weak = Rc::downgrade(&completion);
+        entry.connect_changed(move |_| {
+            if let Some(c) = weak.upgrade() {
+                c.update();
+            }
+        });
+
+        // Clicking a suggestion accepts it
+        let weak = Rc::downgrade(&completion);
+        completion.list_box.connect_row_activated(move |_, row| {
+            if let Some(c) = weak.upgrade() {
+                c.list_box.select_row(Some(row));
+                c.accept();
+            }
+        });
+
+        // Hide when focus leaves the entry (e.g. Down into the list)
+        let focus = gtk4::EventControllerFocus::new();
+        let weak = Rc::downgrade(&completion);
+        focus.connect_leave(move |_| {
+            if let Some(c) = weak.upgrade() {
+                c.hide();
+            }
+        });
+        entry.add_controller(focus);
+
+        // Tab accepts, Ctrl+N / Ctrl+P move the highlight
+        let keys = gtk4::EventControllerKey::new();
+        let weak = Rc::downgrade(&completion);
+        keys.connect_key_pressed(move |_, key, _, modifiers| {
+            let Some(c) = weak.upgrade() else {
+                return gtk4::glib::Propagation::Proceed;
+            };
+            if !c.is_open() {
+                return gtk4::glib::Propagation::Proceed;
+            }
+            let ctrl = modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK);
+            if key == gtk4::gdk::Key::Tab || key == gtk4::gdk::Key::ISO_Left_Tab {
+                c.accept();
+                return gtk4::glib::Propagation::Stop;
+            }
+            if ctrl && key == gtk4::gdk::Key::n {
+                c.move_selection(1);
+                return gtk4::glib::Propagation::Stop;
+            }
+            if ctrl && key == gtk4::gdk::Key::p {
+                c.move_selection(-1);
+                return gtk4::glib::Propagation::Stop;
+            }
+            gtk4::glib::Propagation::Proceed
+        });
+        entry.add_controller(keys);
+
+        // The popover is parented manually, so it has to be unparented manually too
+        let popover_weak = completion.popover.downgrade();
+        entry.connect_destroy(move |_| {
+            if let Some(p) = popover_weak.upgrade() {
+                p.unparent();
+            }
+        });
+
+        completion
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popover.is_visible()
+    }
+
+    pub fn hide(&self) {
+        self.popover.popdown();
+    }
+
+    fn update(self: &Rc<Self>) {
+        let Some(entry) = self.entry.upgrade() else {
+            return;
+        };
+        if self.suppress.get() || entry.focus_child().is_none() {
+            self.hide();
+            return;
+        }
+
+        let text = entry.text().to_string();
+        let mut suggestions = compute_suggestions(&text, &self.history, &self.engines.borrow());
+
+        if self.live_search {
+            if *self.remote_for.borrow() == text {
+                let remote = self.remote.borrow();
+                // Fill the remaining slots, but always show a few engine suggestions
+                let room = MAX_SUGGESTIONS.saturating_sub(suggestions.len()).max(remote.len().min(3));
+                suggestions.extend(remote.iter().take(room).cloned().map(Suggestion::Search));
+            } else {
+                self.schedule_fetch(&text);
+            }
+        }
+
+        self.list_box.remove_all();
+        for suggestion in &suggestions {
+            let hbox = GtkBox::new(Orientation::Horizontal, 8);
+            hbox.set_margin_top(4);
+            hbox.set_margin_bottom(4);
+            hbox.set_margin_start(6);
+            hbox.set_margin_end(6);
+            hbox.append(&gtk4::Image::from_icon_name(suggestion.icon_name()));
+            let label = Label::new(None);
+            label.set_markup(&suggestion.markup());
+            label.set_halign(Align::Start);
+            label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+            label.set_max_width_chars(50);
+            hbox.append(&label);
+            self.list_box.append(&hbox);
+        }
+        if let Some(first) = self.list_box.row_at_index(0) {
+            self.list_box.select_row(Some(&first));
+        }
+
+        let empty = suggestions.is_empty();
+        *self.suggestions.borrow_mut() = suggestions;
+        if empty {
+            self.hide();
+        } else {
+            self.popover.popup();
+        }
+    }
+
+    // Debounced fetch of engine suggestions for `text`; stale responses are dropped
+    fn schedule_fetch(self: &Rc<Self>, text: &str) {
+        if let Some(source) = self.pending.borrow_mut().take() {
+            source.remove();
+        }
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+
+        let (template, query, prefix) = {
+            let engines = self.engines.borrow();
+            let default_keyword = self.default_keyword.borrow();
+            let Some((engine, query)) = search::resolve_engine(text, &engines, &default_keyword) else {
+                return;
+            };
+            let Some(template) = engine.suggest_url.clone() else {
+                return;
+            };
+            // Keep the keyword when the user typed one ("gh rust" -> "gh rustup")
+            let prefix = match search::split_keyword(text, &engines) {
+                Some((e, _)) => format!("{} ", e.keyword),
+                None => String::new(),
+            };
+            (template, query.to_string(), prefix)
+        };
+        if query.is_empty() {
+            return;
+        }
+
+        let weak = Rc::downgrade(self);
+        let text = text.to_string();
+        let source = gtk4::glib::timeout_add_local_once(SUGGEST_DEBOUNCE, move || {
+            let Some(c) = weak.upgrade() else {
+                return;
+            };
+            c.pending.borrow_mut().take();
+
+            let handle = gtk4::gio::spawn_blocking(move || suggest::fetch_suggestions(&template, &query));
+            let weak = Rc::downgrade(&c);
+            gtk4::glib::spawn_future_local(async move {
+                let results = match handle.await {
+                    Ok(Ok(results)) => results,
+                    Ok(Err(e)) => {
+                        log::debug!("Search suggestions failed: {}", e);
+                        return;
+                    }
+                    Err(_) => return,
+                };
+                let Some(c) = weak.upgrade() else {
+                    return;
+                };
+                if c.generation.get() != generation {
+                    return;
+                }
+                *c.remote.borrow_mut() = results.into_iter().map(|s| format!("{}{}", prefix, s)).collect();
+                *c.remote_for.borrow_mut() = text;
+                c.update();
+            });
+        });
+        *self.pending.borrow_mut() = Some(source);
+    }
+
+    fn move_selection(&self, delta: i32) {
+        let count = self.suggestions.borrow().len() as i32;
+        if count == 0 {
+            return;
+        }
+        let current = self.list_box.selected_row().map(|r| r.index()).unwrap_or(0);
+        let next = (current + delta).rem_euclid(count);
+        if let Some(row) = self.list_box.row_at_index(next) {
+            self.list_box.select_row(Some(&row));
+        }
+    }
+
+    // Puts the highlighted suggestion into the entry
+    fn accept(self: &Rc<Self>) {
+        let index = self.list_box.selected_row().map(|r| r.index()).unwrap_or(0);
+        let text = self.suggestions.borrow().get(index as usize).map(|s| s.completion_text());
+        let (Some(text), Some(entry)) = (text, self.entry.upgrade()) else {
+            return;
+        };
+
+        self.suppress.set(true);
+        entry.set_text(&text);
+        entry.set_position(-1);
+        self.suppress.set(false);
+        self.hide();
+
+        // Keep suggesting after a keyword, e.g. show the engine for "gh "
+        if text.ends_with(' ') {
+            self.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engines() -> Vec<SearchEngine> {
+        let engine = |keyword: &str, name: &str| SearchEngine {
+            name: name.to_string(),
+            keyword: keyword.to_string(),
+            url: format!("https://{}.example/?q={{}}", keyword),
+            ..Default::default()
+        };
+        vec![engine("gh", "GitHub"), engine("g", "Google")]
+    }
+
+    fn history() -> Vec<(String, i64)> {
+        [
+            ("https://docs.rs/gix", 9),
+            ("https://github.com/rust-lang/rust", 5),
+            ("https://gitlab.com/inkscape/inkscape", 3),
+            ("https://github.com/serde-rs/serde", 2),
+        ]
+        .iter()
+        .map(|(url, uses)| (url.to_string(), *uses))
+        .collect()
+    }
+
+    fn urls(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions
+            .iter()
+            .filter_map(|s| match s {
+                Suggestion::Url(url) => Some(url.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keywords_come_first_then_domains() {
+        let suggestions = compute_suggestions("g", &history(), &engines());
+        assert_eq!(
+            suggestions[..4],
+            [
+                Suggestion::Engine { keyword: "g".to_string(), name: "Google".to_string() },
+                Suggestion::Engine { keyword: "gh".to_string(), name: "GitHub".to_string() },
+                Suggestion::Domain("github.com".to_string()),
+                Suggestion::Domain("gitlab.com".to_string()),
+            ]
+        );
+        assert!(compute_suggestions("GH", &history(), &engines()).contains(&Suggestion::Engine { keyword: "gh".to_string(), name: "GitHub".to_string() }));
+    }
+
+    #[test]
+    fn prefix_matches_rank_above_substring_matches() {
+        // docs.rs/gix is used most but only contains "gi"
+        let suggestions = compute_suggestions("gi", &history(), &engines());
+        assert_eq!(
+            urls(&suggestions),
+            vec![
+                "https://github.com/rust-lang/rust",
+                "https://gitlab.com/inkscape/inkscape",
+                "https://github.com/serde-rs/serde",
+                "https://docs.rs/gix",
+            ]
+        );
+        assert_eq!(urls(&compute_suggestions("https://www.github.com/s", &history(), &engines())), vec!["https://github.com/serde-rs/serde"]);
+    }
+
+    #[test]
+    fn domains_are_suggested_once() {
+        let suggestions = compute_suggestions("github", &history(), &engines());
+        let domains: Vec<&Suggestion> = suggestions.iter().filter(|s| matches!(s, Suggestion::Domain(_))).collect();
+        assert_eq!(domains, vec![&Suggestion::Domain("github.com".to_string())]);
+        // Neither the typed domain nor the typed URL is offered back
+        assert!(!compute_suggestions("github.com", &history(), &engines()).contains(&Suggestion::Domain("github.com".to_string())));
+        assert!(urls(&compute_suggestions("https://docs.rs/gix", &history(), &engines())).is_empty());
+    }
+
+    #[test]
+    fn search_queries_get_no_url_completions() {
+        assert_eq!(
+            compute_suggestions("gh rust", &history(), &engines()),
+            vec![Suggestion::Engine { keyword: "gh".to_string(), name: "GitHub".to_string() }]
+        );
+        assert!(compute_suggestions("rust github", &history(), &engines()).is_empty());
+        assert!(compute_suggestions("  ", &history(), &engines()).is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, DropDown, Label, Orientation, Window};
+use crate::data::config_export::{self, ImportMode, PortableConfig};
+use crate::data::store::Store;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// DropDown positions in the import preview
+const MODE_MERGE: u32 = 0;
+const MODE_REPLACE: u32 = 1;
+
+/// "Configuration" row for Settings: export to / import from a portable file.
+/// `on_imported` runs after an import was applied.
+pub fn build_config_transfer_ui<F: Fn() + 'static>(on_imported: F) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 10);
+    let label = Label::new(Some("Configuration file"));
+    label.set_hexpand(true);
+    label.set_halign(Align::Start);
+    row.append(&label);
+
+    let export_btn = Button::with_label("Export…");
+    export_btn.set_tooltip_text(Some("Save settings, engines, pins and routing data to a file"));
+    let import_btn = Button::with_label("Import…");
+    import_btn.set_tooltip_text(Some("Load a configuration exported from another OpenNav"));
+    row.append(&export_btn);
+    row.append(&import_btn);
+
+    let status = Label::new(None);
+    status.add_css_class("dim-label");
+    status.set_halign(Align::Start);
+    status.set_wrap(true);
+    status.set_visible(false);
+
+    let container = GtkBox::new(Orientation::Vertical, 6);
+    container.append(&row);
+    container.append(&status);
+
+    let status_for_export = status.clone();
+    export_btn.connect_clicked(move |btn| {
+        let chooser = gtk4::FileDialog::builder()
+            .title("Export Configuration")
+            .initial_name("opennav-config.json")
+            .build();
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        let status = status_for_export.clone();
+        chooser.save(parent.as_ref(), None::<&gtk4::gio::Cancellable>, move |res| {
+            let Some(path) = res.ok().and_then(|f| f.path()) else {
+                return;
+            };
+            let result = Store::new()
+                .map_err(anyhow::Error::from)
+                .and_then(|store| config_export::export(&store))
+                .and_then(|config| config_export::write_file(&config, &path));
+            status.set_text(&match result {
+                Ok(()) => format!("Exported to {}", path.display()),
+                Err(e) => format!("Export failed: {:#}", e),
+            });
+            status.set_visible(true);
+        });
+    });
+
+    let on_imported = Rc::new(on_imported);
+    import_btn.connect_clicked(move |btn| {
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("OpenNav configuration"));
+        filter.add_pattern("*.json");
+        let filters = gtk4::gio::ListStore::new::<gtk4::FileFilter>();
+        filters.append(&filter);
+
+        let chooser = gtk4::FileDialog::builder()
+            .title("Import Configuration")
+            .filters(&filters)
+            .build();
+        let Some(parent) = btn.root().and_then(|r| r.downcast::<Window>().ok()) else {
+            return;
+        };
+        let status = status.clone();
+        let on_imported = on_imported.clone();
+        let parent_for_preview = parent.clone();
+        chooser.open(Some(&parent), None::<&gtk4::gio::Cancellable>, move |res| {
+            let Some(path) = res.ok().and_then(|f| f.path()) else {
+                return;
+            };
+            match config_export::read_file(&path) {
+                Ok(config) => {
+                    let on_imported = on_imported.clone();
+                    show_import_preview(&parent_for_preview, path, config, move || on_imported());
+                }
+                Err(e) => {
+                    status.set_text(&format!("Import failed: {:#}", e));
+                    status.set_visible(true);
+                }
+            }
+        });
+    });
+
+    container
+}
+
+// Shows what the file would change and applies it in the chosen mode
+fn show_import_preview<F: Fn() + 'static>(parent: &Window, path: PathBuf, config: PortableConfig, on_done: F) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Import Configuration")
+        .default_width(450)
+        .build();
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 12);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    dialog.set_child(Some(&vbox));
+
+    let title = Label::new(Some(&format!("<b>{}</b>", gtk4::glib::markup_escape_text(&path.to_string_lossy()))));
+    title.set_use_markup(true);
+    title.set_halign(Align::Start);
+    title.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    vbox.append(&title);
+
+    let mode_row = GtkBox::new(Orientation::Horizontal, 10);
+    let mode_label = Label::new(Some("Mode"));
+    mode_label.set_hexpand(true);
+    mode_label.set_halign(Align::Start);
+    let mode = DropDown::from_strings(&["Merge with current configuration", "Replace current configuration"]);
+    mode.set_selected(MODE_MERGE);
+    mode_row.append(&mode_label);
+    mode_row.append(&mode);
+    vbox.append(&mode_row);
+
+    let summary = Label::new(None);
+    summary.set_halign(Align::Start);
+    summary.set_wrap(true);
+    summary.set_xalign(0.0);
+    vbox.append(&summary);
+
+    let apply_btn = Button::with_label("Apply");
+    apply_btn.add_css_class("suggested-action");
+    apply_btn.set_halign(Align::End);
+    vbox.append(&apply_btn);
+
+    let config = Rc::new(config);
+    let selected_mode = {
+        let mode = mode.clone();
+        move || if mode.selected() == MODE_REPLACE { ImportMode::Replace } else { ImportMode::Merge }
+    };
+
+    let refresh = {
+        let config = config.clone();
+        let summary = summary.clone();
+        let apply_btn = apply_btn.clone();
+        let selected_mode = selected_mode.clone();
+        move || {
+            let mode = selected_mode();
+            match Store::new().map_err(anyhow::Error::from).and_then(|s| config_export::preview(&s, &config)) {
+                Ok(preview) => summary.set_text(&preview.lines(mode).join("\n")),
+                Err(e) => summary.set_text(&format!("Cannot read the current configuration: {:#}", e)),
+            }
+            if mode == ImportMode::Replace {
+                apply_btn.add_css_class("destructive-action");
+                apply_btn.remove_css_class("suggested-action");
+            } else {
+                apply_btn.add_css_class("suggested-action");
+                apply_btn.remove_css_class("destructive-action");
+            }
+        }
+    };
+    refresh();
+    mode.connect_selected_notify(move |_| refresh());
+
+    let dialog_weak = dialog.downgrade();
+    apply_btn.connect_clicked(move |_| {
+        let result = Store::new()
+            .map_err(anyhow::Error::from)
+            .and_then(|store| config_export::apply(&store, &config, selected_mode()));
+        match result {
+            Ok(()) => {
+                on_done();
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+            }
+            Err(e) => summary.set_text(&format!("Import failed: {:#}", e)),
+        }
+    });
+
+    dialog.present();
+}
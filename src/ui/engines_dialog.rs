@@ -1,11 +1,19 @@
 use gtk4::prelude::*;
-use gtk4::{Align, Box as GtkBox, Button, Entry, Image, Label, ListBox, ListBoxRow, Orientation, Window, ScrolledWindow};
-use crate::data::store::{Store, SearchEngine};
+use gtk4::{Align, Box as GtkBox, Button, CheckButton, Entry, Image, Label, ListBox, ListBoxRow, Orientation, Window, ScrolledWindow};
+use crate::data::store::{EngineGroup, EngineParam, Store, SearchEngine};
+use crate::data::browser_repository;
 use crate::data::icons;
+use crate::data::opensearch;
+use crate::data::search;
+use crate::ui::undo_bar::UndoBar;
+use std::cell::RefCell;
+use std::rc::Rc;
 // use std::rc::Rc; // Unused
 
-pub fn build_engine_management_ui() -> GtkBox {
+pub fn build_engine_management_ui(undo: &UndoBar) -> GtkBox {
     let container = GtkBox::new(Orientation::Vertical, 10);
+    // Engine changes refresh the groups, which mark members that no longer exist
+    let (group_section, groups) = build_group_section();
     // container.set_margin_all(0); // Embedded, let parent handle outer margins
 
     // Header / Toolbar
@@ -16,6 +24,11 @@ pub fn build_engine_management_ui() -> GtkBox {
     label.set_halign(Align::Start);
     toolbar.append(&label);
     
+    // Import keyword searches configured in installed browsers
+    let import_btn = Button::with_label("Import");
+    import_btn.set_tooltip_text(Some("Import from Firefox and Chromium"));
+    toolbar.append(&import_btn);
+    
     // Add Button (Header style)
     let add_btn = Button::with_label("Add"); // Minimal text, or icon "list-add-symbolic"
     add_btn.add_css_class("suggested-action");
@@ -35,43 +48,328 @@ pub fn build_engine_management_ui() -> GtkBox {
     list_box.add_css_class("content"); // Clean look
     scrolled.set_child(Some(&list_box));
     container.append(&scrolled);
+
+    let file_engines = crate::data::config_file::current().engines.len();
+    if file_engines > 0 {
+        let note = Label::new(Some(&format!(
+            "{} more engines come from config.toml and take precedence over engines here with the same keyword.",
+            file_engines
+        )));
+        note.add_css_class("dim-label");
+        note.set_wrap(true);
+        note.set_halign(Align::Start);
+        container.append(&note);
+    }
+    if let Some(keyword) = crate::data::config_file::default_engine() {
+        let note = Label::new(Some(&format!("The default engine (\"{}\") is set in config.toml.", keyword)));
+        note.add_css_class("dim-label");
+        note.set_wrap(true);
+        note.set_halign(Align::Start);
+        container.append(&note);
+    }
     
     // Logic to populate list
     let populate_list = {
         let list_box = list_box.clone();
-        move || {
-            // Clear
-            while let Some(child) = list_box.first_child() {
-                list_box.remove(&child);
-            }
-            if let Ok(store) = Store::new() {
-                if let Ok(engines) = store.list_engines() {
-                    for engine in engines {
-                        add_row(&list_box, engine);
-                    }
-                }
-            }
-        }
+        let undo = undo.clone();
+        let groups = groups.clone();
+        move || reload_engines(&list_box, &undo, &groups)
     };
     
     populate_list();
     
+    let populate_list = Rc::new(populate_list);
+    import_btn.connect_clicked(move |btn| {
+        let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        if let Some(parent) = root {
+            let populate_list = populate_list.clone();
+            crate::ui::import_dialog::show_import_dialog(&parent, move || populate_list());
+        }
+    });
+    
     // Add Handler
     let list_box_clone = list_box.clone();
+    let undo_for_add = undo.clone();
+    let groups_for_add = groups.clone();
     // We need parent for dialog? We can get root from widget
     
     add_btn.connect_clicked(move |btn| {
         // Find Toplevel for dialog parent
         let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
         if let Some(parent) = root {
-            show_add_edit_dialog(&parent, list_box_clone.clone(), None);
+            show_add_edit_dialog(&parent, list_box_clone.clone(), None, &undo_for_add, &groups_for_add);
         }
     });
 
+    container.append(&group_section);
+
     container
 }
 
-fn add_row(list_box: &ListBox, engine: SearchEngine) {
+// "Engine Groups": one keyword opening several engines' searches at once.
+// Returns the section and its list.
+fn build_group_section() -> (GtkBox, ListBox) {
+    let container = GtkBox::new(Orientation::Vertical, 10);
+    
+    let toolbar = GtkBox::new(Orientation::Horizontal, 10);
+    let label = Label::new(Some("<b>Engine Groups</b>"));
+    label.set_use_markup(true);
+    label.set_hexpand(true);
+    label.set_halign(Align::Start);
+    toolbar.append(&label);
+    
+    let add_btn = Button::with_label("Add Group");
+    add_btn.set_width_request(100);
+    toolbar.append(&add_btn);
+    container.append(&toolbar);
+    
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    list_box.add_css_class("content");
+    list_box.add_css_class("frame");
+    container.append(&list_box);
+    
+    populate_groups(&list_box);
+    
+    let list_box_for_add = list_box.clone();
+    add_btn.connect_clicked(move |btn| {
+        let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        if let Some(parent) = root {
+            show_group_dialog(&parent, list_box_for_add.clone(), None);
+        }
+    });
+    
+    (container, list_box)
+}
+
+fn populate_groups(list_box: &ListBox) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    let Ok(store) = Store::new() else {
+        return;
+    };
+    let groups = store.list_engine_groups().unwrap_or_default();
+    if groups.is_empty() {
+        let empty = Label::new(Some("No groups yet. A group keyword (e.g. \"dev rust\") opens every engine in the group."));
+        empty.add_css_class("dim-label");
+        empty.set_wrap(true);
+        empty.set_margin_top(12);
+        empty.set_margin_bottom(12);
+        list_box.append(&empty);
+        return;
+    }
+    let keywords: Vec<String> = store.list_active_engines().unwrap_or_default().into_iter().map(|e| e.keyword).collect();
+    for group in groups {
+        add_group_row(list_box, group, &keywords);
+    }
+}
+
+// Members whose engine was deleted are struck through; searches skip them
+fn add_group_row(list_box: &ListBox, group: EngineGroup, keywords: &[String]) {
+    let row = ListBoxRow::new();
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_top(12);
+    hbox.set_margin_bottom(12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    
+    let icon = Image::from_icon_name("view-list-symbolic");
+    icon.set_pixel_size(32);
+    hbox.append(&icon);
+    
+    let vbox_info = GtkBox::new(Orientation::Vertical, 2);
+    vbox_info.set_hexpand(true);
+    let name_label = Label::builder().label(&group.name).halign(Align::Start).build();
+    name_label.add_css_class("heading");
+    let missing: Vec<&str> = group.members.iter().filter(|m| !keywords.contains(m)).map(String::as_str).collect();
+    let members_markup = group
+        .members
+        .iter()
+        .map(|m| {
+            let escaped = gtk4::glib::markup_escape_text(m);
+            if missing.contains(&m.as_str()) { format!("<s>{}</s>", escaped) } else { escaped.to_string() }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let members_label = Label::builder()
+         .label(&format!("<tt>{}</tt>  <span color='gray'>{}</span>", group.keyword, members_markup))
+         .halign(Align::Start)
+         .use_markup(true)
+         .ellipsize(gtk4::pango::EllipsizeMode::End)
+         .build();
+    members_label.add_css_class("caption");
+    if !missing.is_empty() {
+        members_label.set_tooltip_text(Some(&format!("No engine with keyword {}", missing.join(", "))));
+    }
+    vbox_info.append(&name_label);
+    vbox_info.append(&members_label);
+    hbox.append(&vbox_info);
+    
+    let edit_btn = Button::from_icon_name("document-edit-symbolic");
+    edit_btn.set_tooltip_text(Some("Edit Group"));
+    edit_btn.add_css_class("flat");
+    let group_clone = group.clone();
+    let lb_weak_edit = list_box.downgrade();
+    edit_btn.connect_clicked(move |btn| {
+        let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        if let (Some(parent), Some(lb)) = (root, lb_weak_edit.upgrade()) {
+            show_group_dialog(&parent, lb, Some(group_clone.clone()));
+        }
+    });
+    hbox.append(&edit_btn);
+    
+    let del_btn = Button::from_icon_name("user-trash-symbolic");
+    del_btn.add_css_class("destructive-action");
+    del_btn.set_tooltip_text(Some("Delete Group"));
+    let lb_weak_del = list_box.downgrade();
+    del_btn.connect_clicked(move |_| {
+        if let Ok(store) = Store::new() {
+            if store.delete_engine_group(&group.keyword).is_ok() {
+                if let Some(lb) = lb_weak_del.upgrade() {
+                    populate_groups(&lb);
+                }
+            }
+        }
+    });
+    hbox.append(&del_btn);
+    
+    row.set_child(Some(&hbox));
+    list_box.append(&row);
+}
+
+fn show_group_dialog(parent: &Window, list_box: ListBox, edit_target: Option<EngineGroup>) {
+    let is_edit = edit_target.is_some();
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title(if is_edit { "Edit Engine Group" } else { "Add Engine Group" })
+        .default_width(400)
+        .default_height(450)
+        .build();
+    
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+             if let Some(d) = d_weak.upgrade() { d.close(); }
+             return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+    
+    let vbox = GtkBox::new(Orientation::Vertical, 15);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    dialog.set_child(Some(&vbox));
+    
+    let name_entry = Entry::builder().placeholder_text("Name (e.g. Rust Dev)").build();
+    let kw_entry = Entry::builder().placeholder_text("Keyword (e.g. dev)").build();
+    if let Some(ref g) = edit_target {
+        name_entry.set_text(&g.name);
+        kw_entry.set_text(&g.keyword);
+    }
+    vbox.append(&Label::new(Some("Name")));
+    vbox.append(&name_entry);
+    vbox.append(&Label::new(Some("Keyword (Alias)")));
+    vbox.append(&kw_entry);
+    
+    // Members, opened in list order
+    vbox.append(&Label::new(Some("Engines")));
+    let engines = Store::new().and_then(|s| s.list_engines()).unwrap_or_default();
+    let members_box = GtkBox::new(Orientation::Vertical, 4);
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_min_content_height(150);
+    scrolled.add_css_class("frame");
+    scrolled.set_child(Some(&members_box));
+    vbox.append(&scrolled);
+    
+    let checks: Vec<(String, CheckButton)> = engines
+        .iter()
+        .map(|e| {
+            let check = CheckButton::with_label(&format!("{} ({})", e.name, e.keyword));
+            check.set_margin_start(6);
+            if let Some(ref g) = edit_target {
+                check.set_active(g.members.contains(&e.keyword));
+            }
+            members_box.append(&check);
+            (e.keyword.clone(), check)
+        })
+        .collect();
+    
+    let error_label = Label::new(None);
+    error_label.add_css_class("error");
+    error_label.set_wrap(true);
+    error_label.set_visible(false);
+    vbox.append(&error_label);
+    
+    let save_btn = Button::with_label(if is_edit { "Update Group" } else { "Save Group" });
+    save_btn.add_css_class("suggested-action");
+    vbox.append(&save_btn);
+    
+    let dialog_weak = dialog.downgrade();
+    let original_keyword = edit_target.map(|g| g.keyword);
+    save_btn.connect_clicked(move |_| {
+        let name = name_entry.text().trim().to_string();
+        let keyword = kw_entry.text().trim().to_string();
+        let members: Vec<String> = checks
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(kw, _)| kw.clone())
+            .collect();
+        
+        let show_error = |msg: &str| {
+            error_label.set_text(msg);
+            error_label.set_visible(true);
+        };
+        if name.is_empty() {
+            show_error("Enter a name.");
+            return;
+        }
+        
+        let store = match Store::new() {
+            Ok(store) => store,
+            Err(e) => {
+                show_error(&format!("Could not save the group: {}", e));
+                return;
+            }
+        };
+        // Same rules as engine keywords, in the namespace they share
+        let engines = store.list_engines().unwrap_or_default();
+        let groups = store.list_engine_groups().unwrap_or_default();
+        if let Some(message) = search::validate_keyword(&keyword, &engines, &groups, original_keyword.as_deref()) {
+            show_error(&message);
+            return;
+        }
+        if members.len() < 2 {
+            show_error("Select at least two engines.");
+            return;
+        }
+        
+        let group = EngineGroup { name, keyword, members };
+        let res = match original_keyword {
+            Some(ref orig_kw) => store.update_engine_group(orig_kw, &group),
+            None => store.add_engine_group(&group),
+        };
+        match res {
+            Ok(()) => {
+                populate_groups(&list_box);
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+            }
+            Err(e) => show_error(&format!("Could not save the group: {}", e)),
+        }
+    });
+    
+    dialog.present();
+}
+
+fn add_row(list_box: &ListBox, engine: SearchEngine, undo: &UndoBar, groups: &ListBox) {
     let row = ListBoxRow::new();
     let hbox = GtkBox::new(Orientation::Horizontal, 12);
     hbox.set_margin_top(12);
@@ -95,8 +393,9 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     let name_label = Label::builder().label(&engine.name).halign(Align::Start).build();
     name_label.add_css_class("heading");
     
+    let method = if engine.is_post() { "POST " } else { "" };
     let kw_label = Label::builder()
-         .label(&format!("<tt>{}</tt>  <span color='gray'>{}</span>", engine.keyword, engine.url))
+         .label(&format!("<tt>{}</tt>  <span color='gray'>{}{}</span>", engine.keyword, method, gtk4::glib::markup_escape_text(&engine.url)))
          .halign(Align::Start)
          .use_markup(true)
          .ellipsize(gtk4::pango::EllipsizeMode::End)
@@ -107,16 +406,9 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     vbox_info.append(&kw_label);
     hbox.append(&vbox_info);
     
-    // Set Default Logic
-    let is_default_check = {
-        let keyword = engine.keyword.clone();
-        if let Ok(store) = Store::new() {
-             let current = store.get_setting("search_engine").ok().flatten().unwrap_or("g".to_string());
-             current == keyword
-        } else {
-             false
-        }
-    };
+    // Set Default Logic; a default_engine in config.toml overrides the setting
+    let is_default_check = Store::new().is_ok_and(|store| store.default_engine_keyword() == engine.keyword);
+    let default_in_file = crate::data::config_file::default_engine().is_some();
     
     if is_default_check {
         let def_lbl = Label::new(Some("<i>(Default)</i>"));
@@ -126,11 +418,14 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     } else {
         // Tick button to make default
         let make_def_btn = Button::from_icon_name("emblem-ok-symbolic"); // Tick
-        make_def_btn.set_tooltip_text(Some("Set as Default"));
+        make_def_btn.set_tooltip_text(Some(if default_in_file { "The default engine is set in config.toml" } else { "Set as Default" }));
+        make_def_btn.set_sensitive(!default_in_file);
         make_def_btn.add_css_class("flat");
         let keyword = engine.keyword.clone();
         
         let lb_weak = list_box.downgrade();
+        let undo = undo.clone();
+        let groups = groups.clone();
         
         make_def_btn.connect_clicked(move |btn| {
              if let Ok(store) = Store::new() {
@@ -174,7 +469,7 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
                      // Repopulate
                      if let Ok(engines) = store.list_engines() {
                          for engine in engines {
-                             add_row(&lb, engine);
+                             add_row(&lb, engine, &undo, &groups);
                          }
                      }
                  }
@@ -190,13 +485,15 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     
     let engine_clone = engine.clone();
     let lb_weak_edit = list_box.downgrade();
+    let undo_for_edit = undo.clone();
+    let groups_for_edit = groups.clone();
     
     edit_btn.connect_clicked(move |btn| {
          // Find root
          let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
          if let Some(parent) = root {
               if let Some(lb) = lb_weak_edit.upgrade() {
-                  show_add_edit_dialog(&parent, lb, Some(engine_clone.clone())); // Pass clone for editing
+                  show_add_edit_dialog(&parent, lb, Some(engine_clone.clone()), &undo_for_edit, &groups_for_edit); // Pass clone for editing
               }
          }
     });
@@ -208,16 +505,30 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     del_btn.set_tooltip_text(Some("Delete Engine"));
     
     let keyword_del = engine.keyword.clone();
+    let name_del = engine.name.clone();
     let lb_weak_del = list_box.downgrade(); // duplicate weak ref
+    let undo_for_del = undo.clone();
+    let groups_for_del = groups.clone();
     
+    // Deleted engines go to the trash; the undo bar puts them back
     del_btn.connect_clicked(move |btn| {
           if let Ok(store) = Store::new() {
-              if store.delete_engine(&keyword_del).is_ok() {
+              if let Ok(trash_id) = store.trash_engine(&keyword_del) {
                   if let Some(row_widget) = btn.ancestor(ListBoxRow::static_type()) {
                       if let Some(lb) = lb_weak_del.upgrade() {
                           lb.remove(&row_widget);
                       }
                   }
+                  // Groups keep the keyword, so undoing brings the engine back into them
+                  populate_groups(&groups_for_del);
+                  let lb_weak = lb_weak_del.clone();
+                  let undo = undo_for_del.clone();
+                  let groups = groups_for_del.clone();
+                  undo_for_del.show(&format!("Deleted \"{}\"", name_del), trash_id, move || {
+                      if let Some(lb) = lb_weak.upgrade() {
+                          reload_engines(&lb, &undo, &groups);
+                      }
+                  });
               }
           }
     });
@@ -227,7 +538,7 @@ fn add_row(list_box: &ListBox, engine: SearchEngine) {
     list_box.append(&row);
 }
 
-fn show_add_edit_dialog(parent: &Window, list_box: ListBox, edit_target: Option<SearchEngine>) {
+fn show_add_edit_dialog(parent: &Window, list_box: ListBox, edit_target: Option<SearchEngine>, undo: &UndoBar, groups: &ListBox) {
     let is_edit = edit_target.is_some();
     let title = if is_edit { "Edit Search Engine" } else { "Add Search Engine" };
     
@@ -236,7 +547,7 @@ fn show_add_edit_dialog(parent: &Window, list_box: ListBox, edit_target: Option<
         .modal(true)
         .title(title)
         .default_width(400)
-        .default_height(350)
+        .default_height(600)
         .build();
 
     // Add Esc handler for dialog
@@ -256,31 +567,268 @@ fn show_add_edit_dialog(parent: &Window, list_box: ListBox, edit_target: Option<
     vbox.set_margin_bottom(20);
     vbox.set_margin_start(20);
     vbox.set_margin_end(20);
-    dialog.set_child(Some(&vbox));
+    // The form is taller than small screens with all sections expanded
+    let form_scroll = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .propagate_natural_height(true)
+        .child(&vbox)
+        .build();
+    dialog.set_child(Some(&form_scroll));
     
     // Fields
     let name_entry = Entry::builder().placeholder_text("Name (e.g. GitHub)").build();
     let kw_entry = Entry::builder().placeholder_text("Keyword (e.g. gh)").build();
     let url_entry = Entry::builder().placeholder_text("URL (e.g. https://github.com?q={})").build();
+    let suggest_entry = Entry::builder().placeholder_text("Optional (e.g. https://duckduckgo.com/ac/?q={}&type=list)").build();
+    let method_dropdown = gtk4::DropDown::from_strings(&["GET", "POST"]);
+    let params_view = gtk4::TextView::new();
+    
+    // Preferred browser: position 0 is "no preference"
+    let browsers = browser_repository::get_installed_browsers();
+    let mut browser_names = vec!["Any (show the picker)"];
+    browser_names.extend(browsers.iter().map(|b| b.name.as_str()));
+    let browser_dropdown = gtk4::DropDown::from_strings(&browser_names);
+    if let Some(pos) = edit_target
+        .as_ref()
+        .and_then(|e| e.browser_id.as_ref())
+        .and_then(|id| browsers.iter().position(|b| &b.id == id))
+    {
+        browser_dropdown.set_selected(pos as u32 + 1);
+    }
+    let launch_check = CheckButton::with_label("Open directly without showing the picker");
+    launch_check.set_sensitive(browser_dropdown.selected() > 0);
+    let launch_for_dropdown = launch_check.clone();
+    browser_dropdown.connect_selected_notify(move |dd| {
+        launch_for_dropdown.set_sensitive(dd.selected() > 0);
+    });
+    params_view.set_monospace(true);
+    params_view.set_top_margin(6);
+    params_view.set_bottom_margin(6);
+    params_view.set_left_margin(6);
     
     // Pre-fill if editing
     if let Some(ref e) = edit_target {
         name_entry.set_text(&e.name);
         kw_entry.set_text(&e.keyword);
         url_entry.set_text(&e.url);
+        suggest_entry.set_text(e.suggest_url.as_deref().unwrap_or(""));
+        method_dropdown.set_selected(if e.is_post() { 1 } else { 0 });
+        launch_check.set_active(e.launch_directly);
+        params_view.buffer().set_text(&params_to_text(&e.params));
         // Disable keyword editing effectively? Or allow it? 
         // If we allow, we need to handle PK change. update_engine handles it if logic supports it.
         // For simplicity, let's allow it.
     }
     
+    // Icon saved from an imported OpenSearch description (used instead of the favicon)
+    let imported_icon = Rc::new(RefCell::new(None::<String>));
+    
+    if !is_edit {
+        let (name_entry, kw_entry, url_entry, suggest_entry) =
+            (name_entry.clone(), kw_entry.clone(), url_entry.clone(), suggest_entry.clone());
+        let (method_dropdown, params_view, imported_icon) = (method_dropdown.clone(), params_view.clone(), imported_icon.clone());
+        vbox.append(&build_opensearch_import(&dialog, move |desc, icon| {
+            name_entry.set_text(&desc.short_name);
+            if kw_entry.text().is_empty() {
+                kw_entry.set_text(&desc.suggested_keyword());
+            }
+            url_entry.set_text(&desc.template);
+            suggest_entry.set_text(desc.suggest_template.as_deref().unwrap_or(""));
+            method_dropdown.set_selected(if desc.method == "POST" { 1 } else { 0 });
+            params_view.buffer().set_text(&params_to_text(&desc.params));
+            *imported_icon.borrow_mut() = icon;
+        }));
+        vbox.append(&gtk4::Separator::new(Orientation::Horizontal));
+    }
+    
+    // Inline error under each validated field
+    let error_label = || {
+        let label = Label::new(None);
+        label.set_halign(Align::Start);
+        label.set_wrap(true);
+        label.add_css_class("error");
+        label.add_css_class("caption");
+        label.set_visible(false);
+        label
+    };
+    let name_error = error_label();
+    let kw_error = error_label();
+    let url_error = error_label();
+    let suggest_error = error_label();
+    let save_error = error_label();
+    
     vbox.append(&Label::new(Some("Name")));
     vbox.append(&name_entry);
+    vbox.append(&name_error);
     
     vbox.append(&Label::new(Some("Keyword (Alias)")));
     vbox.append(&kw_entry);
+    vbox.append(&kw_error);
     
     vbox.append(&Label::new(Some("Search URL (use {} for query)")));
     vbox.append(&url_entry);
+    vbox.append(&url_error);
+    
+    vbox.append(&Label::new(Some("Suggestions URL (OpenSearch JSON, optional)")));
+    vbox.append(&suggest_entry);
+    vbox.append(&suggest_error);
+    
+    vbox.append(&Label::new(Some("Preferred Browser")));
+    vbox.append(&browser_dropdown);
+    vbox.append(&launch_check);
+    
+    // POST and multi-parameter engines (e.g. internal portals)
+    let advanced = gtk4::Expander::new(Some("Method and Parameters"));
+    advanced.set_expanded(method_dropdown.selected() == 1 || params_view.buffer().char_count() > 0);
+    let advanced_box = GtkBox::new(Orientation::Vertical, 8);
+    advanced_box.set_margin_top(8);
+    let method_row = GtkBox::new(Orientation::Horizontal, 10);
+    let method_label = Label::new(Some("HTTP Method"));
+    method_label.set_hexpand(true);
+    method_label.set_halign(Align::Start);
+    method_row.append(&method_label);
+    method_row.append(&method_dropdown);
+    advanced_box.append(&method_row);
+    let params_label = Label::new(Some("Parameters, one name=value per line (form fields for POST)"));
+    params_label.set_halign(Align::Start);
+    advanced_box.append(&params_label);
+    let params_scroll = ScrolledWindow::new();
+    params_scroll.set_min_content_height(80);
+    params_scroll.add_css_class("frame");
+    params_scroll.set_child(Some(&params_view));
+    advanced_box.append(&params_scroll);
+    let placeholder_help = Label::new(Some("{} is the whole query. {1}, {2}… are its space-separated words, the last one takes the rest: \"jira PROJ login bug\" gives {1} = PROJ, {2} = login bug."));
+    placeholder_help.set_wrap(true);
+    placeholder_help.set_halign(Align::Start);
+    placeholder_help.add_css_class("dim-label");
+    placeholder_help.add_css_class("caption");
+    advanced_box.append(&placeholder_help);
+    advanced.set_child(Some(&advanced_box));
+    vbox.append(&advanced);
+    // Switching to POST (e.g. an imported POST engine) shows the form fields
+    let advanced_for_method = advanced.clone();
+    method_dropdown.connect_selected_notify(move |d| {
+        if d.selected() == 1 {
+            advanced_for_method.set_expanded(true);
+        }
+    });
+    
+    // Preview: the URL a test query produces
+    let test_row = GtkBox::new(Orientation::Horizontal, 6);
+    let test_entry = Entry::builder().text("test query").hexpand(true).build();
+    test_entry.set_tooltip_text(Some("Test Query"));
+    let test_btn = Button::with_label("Test in Browser");
+    test_btn.set_tooltip_text(Some("Opens the test query in the preferred browser (or the first installed one)"));
+    test_row.append(&test_entry);
+    test_row.append(&test_btn);
+    vbox.append(&Label::new(Some("Preview")));
+    vbox.append(&test_row);
+    let preview_label = Label::new(None);
+    preview_label.set_halign(Align::Start);
+    preview_label.set_wrap(true);
+    preview_label.set_wrap_mode(gtk4::pango::WrapMode::WordChar);
+    preview_label.set_selectable(true);
+    preview_label.add_css_class("dim-label");
+    preview_label.add_css_class("caption");
+    vbox.append(&preview_label);
+    
+    // The engine as currently entered (the icon is resolved on save)
+    let read_engine = {
+        let (name_entry, kw_entry, url_entry, suggest_entry) =
+            (name_entry.clone(), kw_entry.clone(), url_entry.clone(), suggest_entry.clone());
+        let (method_dropdown, params_view, browser_dropdown, launch_check) =
+            (method_dropdown.clone(), params_view.clone(), browser_dropdown.clone(), launch_check.clone());
+        let browsers = browsers.clone();
+        Rc::new(move || {
+            let buffer = params_view.buffer();
+            let browser_id = match browser_dropdown.selected() {
+                0 => None,
+                i => browsers.get(i as usize - 1).map(|b| b.id.clone()),
+            };
+            SearchEngine {
+                name: name_entry.text().trim().to_string(),
+                keyword: kw_entry.text().trim().to_string(),
+                url: url_entry.text().trim().to_string(),
+                icon_path: None,
+                suggest_url: Some(suggest_entry.text().trim().to_string()).filter(|s| !s.is_empty()),
+                method: if method_dropdown.selected() == 1 { "POST" } else { "GET" }.to_string(),
+                params: parse_params(&buffer.text(&buffer.start_iter(), &buffer.end_iter(), false)),
+                launch_directly: browser_id.is_some() && launch_check.is_active(),
+                browser_id,
+            }
+        })
+    };
+    
+    let original_keyword = edit_target.map(|e| e.keyword);
+    let existing_engines = Store::new().and_then(|s| s.list_engines()).unwrap_or_default();
+    let existing_groups = Store::new().and_then(|s| s.list_engine_groups()).unwrap_or_default();
+    // "Enter a ..." errors only show up once the user tried to save
+    let save_attempted = Rc::new(std::cell::Cell::new(false));
+    
+    // Updates the error labels and the preview; returns whether the engine can be saved
+    let validate = {
+        let read_engine = read_engine.clone();
+        let original_keyword = original_keyword.clone();
+        let save_attempted = save_attempted.clone();
+        let test_entry = test_entry.clone();
+        let test_btn = test_btn.clone();
+        Rc::new(move || -> bool {
+            let engine = read_engine();
+            let issues = search::validate_engine(&engine, &existing_engines, &existing_groups, original_keyword.as_deref());
+            let message_for = |field: search::EngineField| issues.iter().find(|(f, _)| *f == field).map(|(_, m)| m.clone());
+            
+            for (field, label, value) in [
+                (search::EngineField::Name, &name_error, &engine.name),
+                (search::EngineField::Keyword, &kw_error, &engine.keyword),
+                (search::EngineField::Url, &url_error, &engine.url),
+            ] {
+                let message = message_for(field);
+                label.set_text(message.as_deref().unwrap_or(""));
+                label.set_visible(message.is_some() && (save_attempted.get() || !value.is_empty()));
+            }
+            let suggest_message = message_for(search::EngineField::SuggestUrl);
+            suggest_error.set_text(suggest_message.as_deref().unwrap_or(""));
+            suggest_error.set_visible(suggest_message.is_some());
+            
+            let url_ok = message_for(search::EngineField::Url).is_none();
+            if url_ok {
+                preview_label.set_text(&search::preview_search(&engine, test_entry.text().trim()));
+            } else {
+                preview_label.set_text("Enter a valid search URL to see a preview.");
+            }
+            test_btn.set_sensitive(url_ok);
+            issues.is_empty()
+        })
+    };
+    
+    for entry in [&name_entry, &kw_entry, &url_entry, &suggest_entry, &test_entry] {
+        let validate = validate.clone();
+        entry.connect_changed(move |_| {
+            validate();
+        });
+    }
+    let validate_for_method = validate.clone();
+    method_dropdown.connect_selected_notify(move |_| {
+        validate_for_method();
+    });
+    let validate_for_params = validate.clone();
+    params_view.buffer().connect_changed(move |_| {
+        validate_for_params();
+    });
+    validate();
+    
+    // Opens the test query in the preferred browser, or the first installed one
+    let read_for_test = read_engine.clone();
+    test_btn.connect_clicked(move |_| {
+        let engine = read_for_test();
+        let Some(browser_id) = engine.browser_id.clone().or_else(|| browsers.first().map(|b| b.id.clone())) else {
+            return;
+        };
+        let url = search::engine_url(&engine, test_entry.text().trim());
+        if let Err(e) = browser_repository::launch_browser(&browser_id, &url) {
+            log::warn!("Test search failed: {}", e);
+        }
+    });
     
     // Save
     let btn_label = if is_edit { "Update Engine" } else { "Save Engine" };
@@ -288,57 +836,171 @@ fn show_add_edit_dialog(parent: &Window, list_box: ListBox, edit_target: Option<
     save_btn.add_css_class("suggested-action");
     
     let dialog_weak = dialog.downgrade();
-    let original_keyword = edit_target.map(|e| e.keyword);
+    let undo = undo.clone();
+    let groups = groups.clone();
     
     save_btn.connect_clicked(move |_| {
-        let name = name_entry.text().to_string();
-        let keyword = kw_entry.text().to_string();
-        let url = url_entry.text().to_string();
-        
-        if name.is_empty() || keyword.is_empty() || url.is_empty() {
-            return; // TODO: Show error
+        save_attempted.set(true);
+        if !validate() {
+            return;
         }
+        let mut engine = read_engine();
         
         // Try fetch icon (only if new or changed? for now always fetch if logical)
         // If editing, maybe we preserve existing icon if URL didn't change?
         // Let's just fetch, it caches anyway in `icons.rs`.
-        let icon_path = icons::fetch_favicon(&url).ok();
-        
-        let engine = SearchEngine {
-            name,
-            keyword: keyword.clone(),
-            url,
-            icon_path
+        engine.icon_path = match imported_icon.borrow().clone() {
+            Some(path) => Some(path),
+            None => icons::fetch_favicon(&engine.url).ok(),
         };
         
-        if let Ok(store) = Store::new() {
-            let res = if let Some(ref orig_kw) = original_keyword {
-                 store.update_engine(orig_kw, &engine)
-            } else {
-                 store.add_engine(&engine)
-            };
-            
-            if res.is_ok() {
-                // Refresh List by clearing and re-adding?
-                // Actually, `add_row` appends. We need to replace or refresh fully.
-                // Since this dialog doesn't know about loop, we should ideally trigger full refresh on listbox again.
-                // Re-using the logic from Set Default:
-                while let Some(child) = list_box.first_child() {
-                     list_box.remove(&child);
-                }
-                if let Ok(engines) = store.list_engines() {
-                     for engine in engines {
-                         add_row(&list_box, engine);
-                     }
-                }
-                
+        let res = Store::new().and_then(|store| match original_keyword {
+            Some(ref orig_kw) => store.update_engine(orig_kw, &engine),
+            None => store.add_engine(&engine),
+        });
+        match res {
+            Ok(()) => {
+                reload_engines(&list_box, &undo, &groups);
                 if let Some(d) = dialog_weak.upgrade() {
                     d.close();
                 }
             }
+            Err(e) => {
+                save_error.set_text(&format!("Could not save the engine: {}", e));
+                save_error.set_visible(true);
+            }
         }
     });
     
+    vbox.append(&save_error);
     vbox.append(&save_btn);
     dialog.present();
 }
+
+fn reload_engines(list_box: &ListBox, undo: &UndoBar, groups: &ListBox) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    for engine in Store::new().and_then(|s| s.list_engines()).unwrap_or_default() {
+        add_row(list_box, engine, undo, groups);
+    }
+    populate_groups(groups);
+}
+
+fn params_to_text(params: &[EngineParam]) -> String {
+    params.iter().map(|p| format!("{}={}", p.name, p.value)).collect::<Vec<_>>().join("\n")
+}
+
+// "name=value" lines; blank lines and lines without a name are ignored
+fn parse_params(text: &str) -> Vec<EngineParam> {
+    text.lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once('=').unwrap_or((line, ""));
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some(EngineParam { name: name.to_string(), value: value.trim().to_string() })
+        })
+        .collect()
+}
+
+// "Import from OpenSearch": fetches/parses a description and hands it to
+// `on_import` with the path of its saved icon, to fill the dialog fields
+fn build_opensearch_import<F: Fn(&opensearch::OpenSearchDescription, Option<String>) + 'static>(
+    dialog: &Window,
+    on_import: F,
+) -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 6);
+    
+    let header = Label::new(Some("Import from OpenSearch"));
+    header.set_halign(Align::Start);
+    container.append(&header);
+    
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    let source_entry = Entry::builder()
+        .placeholder_text("Site, description URL or .xml file")
+        .hexpand(true)
+        .build();
+    let file_btn = Button::from_icon_name("document-open-symbolic");
+    file_btn.set_tooltip_text(Some("Choose .xml File"));
+    let import_btn = Button::with_label("Import");
+    row.append(&source_entry);
+    row.append(&file_btn);
+    row.append(&import_btn);
+    container.append(&row);
+    
+    let status = Label::new(None);
+    status.set_halign(Align::Start);
+    status.set_wrap(true);
+    status.add_css_class("dim-label");
+    status.set_visible(false);
+    container.append(&status);
+    
+    // Local file picker
+    let dialog_weak = dialog.downgrade();
+    let source_for_file = source_entry.clone();
+    let import_for_file = import_btn.clone();
+    file_btn.connect_clicked(move |_| {
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("OpenSearch description"));
+        filter.add_pattern("*.xml");
+        let filters = gtk4::gio::ListStore::new::<gtk4::FileFilter>();
+        filters.append(&filter);
+        
+        let chooser = gtk4::FileDialog::builder()
+            .title("Choose OpenSearch Description")
+            .filters(&filters)
+            .build();
+        let source = source_for_file.clone();
+        let import = import_for_file.clone();
+        chooser.open(dialog_weak.upgrade().as_ref(), None::<&gtk4::gio::Cancellable>, move |res| {
+            if let Some(path) = res.ok().and_then(|f| f.path()) {
+                source.set_text(&path.to_string_lossy());
+                import.emit_clicked();
+            }
+        });
+    });
+    
+    // Fetch + parse off the main thread, then fill the form
+    let on_import = Rc::new(on_import);
+    let source_for_import = source_entry.clone();
+    import_btn.connect_clicked(move |btn| {
+        let input = source_for_import.text().trim().to_string();
+        if input.is_empty() {
+            return;
+        }
+        btn.set_sensitive(false);
+        status.set_text("Importing…");
+        status.set_visible(true);
+        
+        let handle = gtk4::gio::spawn_blocking(move || {
+            let desc = opensearch::load(&input)?;
+            let icon = desc
+                .image
+                .as_deref()
+                .and_then(|img| opensearch::save_image(img, &desc.suggested_keyword()).ok());
+            anyhow::Ok((desc, icon))
+        });
+        
+        let btn = btn.clone();
+        let status = status.clone();
+        let on_import = on_import.clone();
+        gtk4::glib::spawn_future_local(async move {
+            match handle.await {
+                Ok(Ok((desc, icon))) => {
+                    on_import(&desc, icon);
+                    status.set_text(&format!("Imported \"{}\". Review the keyword and save.", desc.short_name));
+                }
+                Ok(Err(e)) => status.set_text(&format!("Import failed: {}", e)),
+                Err(_) => status.set_text("Import failed"),
+            }
+            btn.set_sensitive(true);
+        });
+    });
+    
+    let import_for_enter = import_btn.clone();
+    source_entry.connect_activate(move |_| import_for_enter.emit_clicked());
+    
+    container
+}
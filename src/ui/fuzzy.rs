@@ -0,0 +1,219 @@
+// Fuzzy matcher used by the browser list (and anything else that filters by typing).
+//
+// Every query character has to appear in the text in order, but not necessarily
+// next to each other. Matches are scored so that consecutive runs and word starts
+// win: "gcw" scores well against "Google Chrome Work" because each letter starts a word.
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 24;
+const BONUS_WORD_START: i64 = 20;
+const BONUS_FIRST_CHAR: i64 = 8;
+const PENALTY_GAP: i64 = 1;
+const PENALTY_LEADING: i64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices (not byte offsets) of the matched characters in the text.
+    pub positions: Vec<usize>,
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_word_start(chars: &[char], idx: usize) -> bool {
+    if idx == 0 {
+        return true;
+    }
+    let prev = chars[idx - 1];
+    let cur = chars[idx];
+    if !prev.is_alphanumeric() {
+        return true;
+    }
+    // camelCase / PascalCase boundaries (e.g. "BraveBrowser")
+    prev.is_lowercase() && cur.is_uppercase()
+}
+
+/// Scores `query` against `text`. Returns `None` when not every query character
+/// can be found (in order). An empty query matches everything with score 0.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(fold).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| fold(*c)).collect();
+    let n = query.len();
+    let m = chars.len();
+    if n > m {
+        return None;
+    }
+
+    // best[i][j]: best score with query[i] matched at text[j] (None = impossible)
+    // from[i][j]: text index that query[i - 1] was matched at for that score
+    let mut best = vec![vec![None::<i64>; m]; n];
+    let mut from = vec![vec![0usize; m]; n];
+
+    for i in 0..n {
+        // Running max of (best[i - 1][k] + k * PENALTY_GAP) over k < j - 1,
+        // which lets the gap penalty be applied in O(1) per cell.
+        let mut running: Option<(i64, usize)> = None;
+
+        for j in 0..m {
+            if i > 0 && j >= 2 {
+                if let Some(prev) = best[i - 1][j - 2] {
+                    let candidate = prev + (j as i64 - 2) * PENALTY_GAP;
+                    if running.is_none_or(|(r, _)| candidate > r) {
+                        running = Some((candidate, j - 2));
+                    }
+                }
+            }
+
+            if lower[j] != query[i] {
+                continue;
+            }
+
+            let mut bonus = SCORE_MATCH;
+            if is_word_start(&chars, j) {
+                bonus += BONUS_WORD_START;
+            }
+
+            if i == 0 {
+                if j == 0 {
+                    bonus += BONUS_FIRST_CHAR;
+                }
+                best[i][j] = Some(bonus - j as i64 * PENALTY_LEADING);
+                continue;
+            }
+
+            // Either continue a run from j - 1, or jump over a gap from an earlier match.
+            let mut cell: Option<(i64, usize)> = None;
+            if j >= 1 {
+                if let Some(prev) = best[i - 1][j - 1] {
+                    cell = Some((prev + BONUS_CONSECUTIVE, j - 1));
+                }
+            }
+            if let Some((r, k)) = running {
+                let gapped = r - (j as i64 - 1) * PENALTY_GAP;
+                if cell.is_none_or(|(c, _)| gapped > c) {
+                    cell = Some((gapped, k));
+                }
+            }
+
+            if let Some((score, k)) = cell {
+                best[i][j] = Some(score + bonus);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    // Pick the best end position for the last query char, then walk back.
+    let (mut end, score) = best[n - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (j, s)))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+
+    let mut positions = vec![0usize; n];
+    for i in (0..n).rev() {
+        positions[i] = end;
+        if i > 0 {
+            end = from[i][end];
+        }
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Builds Pango markup for `text`, highlighting the chars at `positions`.
+pub fn highlight_markup(text: &str, positions: &[usize]) -> String {
+    let mut markup = String::new();
+    let mut run = String::new();
+    let mut in_run = false;
+
+    let flush = |markup: &mut String, run: &mut String, highlighted: bool| {
+        if run.is_empty() {
+            return;
+        }
+        let escaped = gtk4::glib::markup_escape_text(run);
+        if highlighted {
+            markup.push_str(&format!("<span foreground='#f0e68c' underline='single'>{}</span>", escaped));
+        } else {
+            markup.push_str(&escaped);
+        }
+        run.clear();
+    };
+
+    for (idx, c) in text.chars().enumerate() {
+        let matched = positions.contains(&idx);
+        if matched != in_run {
+            flush(&mut markup, &mut run, in_run);
+            in_run = matched;
+        }
+        run.push(c);
+    }
+    flush(&mut markup, &mut run, in_run);
+
+    markup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(query: &str, text: &str) -> Vec<usize> {
+        fuzzy_match(query, text).unwrap().positions
+    }
+
+    #[test]
+    fn matches_query_chars_in_order() {
+        assert!(fuzzy_match("frx", "Firefox").is_some());
+        assert!(fuzzy_match("xf", "Firefox").is_none());
+        assert!(fuzzy_match("chromez", "Chromium").is_none());
+        assert!(fuzzy_match("firefox nightly", "Firefox").is_none());
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_match("", "Firefox"), Some(FuzzyMatch { score: 0, positions: Vec::new() }));
+        assert_eq!(fuzzy_match("  ", ""), Some(FuzzyMatch { score: 0, positions: Vec::new() }));
+    }
+
+    #[test]
+    fn prefers_word_starts() {
+        assert_eq!(positions("gcw", "Google Chrome Work"), vec![0, 7, 14]);
+        let words = fuzzy_match("gcw", "Google Chrome Work").unwrap().score;
+        let inside = fuzzy_match("gcw", "Bigcrewlist").unwrap().score;
+        assert!(words > inside, "{} <= {}", words, inside);
+    }
+
+    #[test]
+    fn camel_case_boundary_counts_as_word_start() {
+        assert_eq!(positions("b", "abcBrowser"), vec![3]);
+        assert_eq!(positions("bb", "BraveBrowser"), vec![0, 5]);
+    }
+
+    #[test]
+    fn prefers_consecutive_runs() {
+        assert_eq!(positions("rom", "xrxomxrom"), vec![6, 7, 8]);
+        assert_eq!(positions("ab", "a_ab"), vec![2, 3]);
+    }
+
+    #[test]
+    fn folds_case_and_counts_chars_not_bytes() {
+        assert_eq!(positions("FIREFOX", "firefox"), (0..7).collect::<Vec<_>>());
+        assert_eq!(positions("éd", "Microsoft Édge"), vec![10, 11]);
+        assert_eq!(positions("ÖL", "Browser Ölkanne"), vec![8, 9]);
+    }
+
+    #[test]
+    fn highlight_markup_wraps_matched_runs_and_escapes() {
+        let span = |s: &str| format!("<span foreground='#f0e68c' underline='single'>{}</span>", s);
+        assert_eq!(highlight_markup("abc", &[0, 1]), format!("{}c", span("ab")));
+        assert_eq!(highlight_markup("A&B", &[0, 2]), format!("{}&amp;{}", span("A"), span("B")));
+        assert_eq!(highlight_markup("Édge <dev>", &[0]), format!("{}dge &lt;dev&gt;", span("É")));
+        assert_eq!(highlight_markup("plain", &[]), "plain");
+    }
+}
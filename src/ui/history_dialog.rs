@@ -0,0 +1,251 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Entry, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Window};
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::data::browser_repository::{self, Browser};
+use crate::data::store::{self, format_age, HistoryEntry, Store};
+use crate::ui::undo_bar::UndoBar;
+
+// How many recent launches the dialog loads
+const HISTORY_VIEW_LIMIT: i64 = 500;
+
+// Every whitespace-separated term must appear in the URL or browser name
+fn entry_matches(entry: &HistoryEntry, browser_name: &str, filter: &str) -> bool {
+    let haystack = format!("{} {}", entry.url, browser_name).to_lowercase();
+    filter
+        .split_whitespace()
+        .all(|term| haystack.contains(&term.to_lowercase()))
+}
+
+fn browser_name<'a>(browsers: &'a [Browser], id: &'a str) -> &'a str {
+    browsers.iter().find(|b| b.id == id).map(|b| b.name.as_str()).unwrap_or(id)
+}
+
+/// Searchable list of recently launched links.
+/// Activating a row hands its URL to `on_pick` (the picker puts it in the URL bar
+/// so it can be opened in any browser); the reopen button relaunches it directly.
+pub fn show_history_dialog<F: Fn(&str) + 'static>(parent: &Window, browsers: Rc<Vec<Browser>>, on_pick: F) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("History")
+        .default_width(550)
+        .default_height(450)
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+
+    let search_entry = Entry::builder()
+        .placeholder_text("Filter history...")
+        .primary_icon_name("system-search-symbolic")
+        .build();
+    vbox.append(&search_entry);
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vexpand(true)
+        .build();
+    scrolled.add_css_class("frame");
+
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::Browse);
+    scrolled.set_child(Some(&list_box));
+    vbox.append(&scrolled);
+
+    // Entries in row order, so filter/activate can map a row index back to its entry
+    let entries = Rc::new(RefCell::new(Vec::new()));
+    populate(&list_box, &entries, &browsers, parent);
+
+    // Shown when history is empty or nothing matches the filter
+    let empty = Label::new(Some("No links found."));
+    empty.add_css_class("dim-label");
+    empty.set_margin_top(20);
+    empty.set_margin_bottom(20);
+    list_box.set_placeholder(Some(&empty));
+
+    // Filtering
+    let entries_filter = entries.clone();
+    let browsers_filter = browsers.clone();
+    let search_for_filter = search_entry.clone();
+    list_box.set_filter_func(move |row| {
+        let filter = search_for_filter.text();
+        let entries = entries_filter.borrow();
+        match entries.get(row.index() as usize) {
+            Some(e) => entry_matches(e, browser_name(&browsers_filter, &e.browser_id), &filter),
+            None => true,
+        }
+    });
+
+    let lb_for_search = list_box.clone();
+    search_entry.connect_changed(move |_| {
+        lb_for_search.invalidate_filter();
+    });
+
+    // Activating a row: hand the URL back to the picker
+    let entries_activate = entries.clone();
+    let dialog_weak = dialog.downgrade();
+    list_box.connect_row_activated(move |_, row| {
+        let url = entries_activate.borrow().get(row.index() as usize).map(|e| e.url.clone());
+        if let Some(url) = url {
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+            on_pick(&url);
+        }
+    });
+
+    // Enter in the filter picks the first visible row
+    let lb_for_enter = list_box.clone();
+    search_entry.connect_activate(move |_| {
+        if let Some(row) = first_visible_row(&lb_for_enter) {
+            row.emit_activate();
+        }
+    });
+
+    // Esc closes, Down moves from the filter into the list
+    let d_weak = dialog.downgrade();
+    let lb_for_keys = list_box.clone();
+    let search_weak = search_entry.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        if key == gtk4::gdk::Key::Down {
+            let in_search = search_weak.upgrade().is_some_and(|s| {
+                d_weak
+                    .upgrade()
+                    .and_then(|d| gtk4::prelude::GtkWindowExt::focus(&d))
+                    .is_some_and(|f| f == *s.upcast_ref::<gtk4::Widget>() || f.is_ancestor(&s))
+            });
+            if in_search {
+                if let Some(row) = first_visible_row(&lb_for_keys) {
+                    row.grab_focus();
+                    return gtk4::glib::Propagation::Stop;
+                }
+            }
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    // Clear; the history goes to the trash, so the undo bar can bring it back
+    let undo_bar = UndoBar::new();
+    vbox.append(undo_bar.widget());
+    let clear_btn = Button::with_label("Clear History");
+    clear_btn.add_css_class("destructive-action");
+    clear_btn.set_halign(Align::End);
+    let entries_clear = entries.clone();
+    let lb_clear = list_box.clone();
+    let parent_clear = parent.clone();
+    let undo_for_clear = undo_bar.clone();
+    clear_btn.connect_clicked(move |_| {
+        let Ok(trash_id) = Store::new().and_then(|s| s.clear_history()) else {
+            return;
+        };
+        entries_clear.borrow_mut().clear();
+        lb_clear.remove_all();
+        let (list_box, entries, browsers, parent) = (lb_clear.clone(), entries_clear.clone(), browsers.clone(), parent_clear.clone());
+        undo_for_clear.show("History cleared", trash_id, move || populate(&list_box, &entries, &browsers, &parent));
+    });
+    vbox.append(&clear_btn);
+
+    dialog.set_child(Some(&vbox));
+    dialog.present();
+    search_entry.grab_focus();
+}
+
+// (Re)loads the history into the list, e.g. after a cleared history was restored
+fn populate(list_box: &ListBox, entries: &Rc<RefCell<Vec<HistoryEntry>>>, browsers: &[Browser], parent: &Window) {
+    list_box.remove_all();
+    *entries.borrow_mut() = Store::new()
+        .and_then(|s| s.list_history(HISTORY_VIEW_LIMIT))
+        .unwrap_or_default();
+
+    let now = store::now();
+
+    for entry in entries.borrow().iter() {
+        let row = ListBoxRow::new();
+        let hbox = GtkBox::new(Orientation::Horizontal, 12);
+        hbox.set_margin_top(8);
+        hbox.set_margin_bottom(8);
+        hbox.set_margin_start(12);
+        hbox.set_margin_end(12);
+
+        let info = GtkBox::new(Orientation::Vertical, 2);
+        info.set_hexpand(true);
+        let url_label = Label::builder()
+            .label(&entry.url)
+            .halign(Align::Start)
+            .ellipsize(gtk4::pango::EllipsizeMode::Middle)
+            .build();
+        let mode_suffix = if entry.mode == "normal" { String::new() } else { format!(" · {}", entry.mode) };
+        let meta_label = Label::builder()
+            .label(&format!("{} · {}{}", browser_name(browsers, &entry.browser_id), format_age(entry.timestamp, now), mode_suffix))
+            .halign(Align::Start)
+            .build();
+        meta_label.add_css_class("caption");
+        meta_label.add_css_class("dim-label");
+        info.append(&url_label);
+        info.append(&meta_label);
+        hbox.append(&info);
+
+        // Reopen in the same browser
+        let reopen_btn = Button::from_icon_name("view-refresh-symbolic");
+        reopen_btn.add_css_class("flat");
+        reopen_btn.set_tooltip_text(Some("Reopen in the same browser"));
+        let url = entry.url.clone();
+        let browser_id = entry.browser_id.clone();
+        let parent_weak = parent.downgrade();
+        reopen_btn.connect_clicked(move |_| {
+            if browser_repository::launch_browser(&browser_id, &url).is_ok() {
+                if let Ok(store) = Store::new() {
+                    let _ = store.record_launch(&browser_id, &url, "normal");
+                }
+                if let Some(p) = parent_weak.upgrade() {
+                    p.close();
+                }
+            }
+        });
+        hbox.append(&reopen_btn);
+
+        // Delete from history
+        let del_btn = Button::from_icon_name("user-trash-symbolic");
+        del_btn.add_css_class("flat");
+        del_btn.set_tooltip_text(Some("Remove from History"));
+        let entry_id = entry.id;
+        let entries_del = entries.clone();
+        let lb_weak = list_box.downgrade();
+        del_btn.connect_clicked(move |btn| {
+            if let Ok(store) = Store::new() {
+                if store.delete_history_entry(entry_id).is_ok() {
+                    entries_del.borrow_mut().retain(|e| e.id != entry_id);
+                    if let (Some(row_widget), Some(lb)) = (btn.ancestor(ListBoxRow::static_type()), lb_weak.upgrade()) {
+                        lb.remove(&row_widget);
+                    }
+                }
+            }
+        });
+        hbox.append(&del_btn);
+
+        row.set_child(Some(&hbox));
+        list_box.append(&row);
+    }
+}
+
+fn first_visible_row(list_box: &ListBox) -> Option<ListBoxRow> {
+    let mut idx = 0;
+    while let Some(row) = list_box.row_at_index(idx) {
+        if row.is_child_visible() {
+            return Some(row);
+        }
+        idx += 1;
+    }
+    None
+}
@@ -0,0 +1,308 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, CheckButton, DropDown, Entry, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Window};
+use crate::data::browser_import::{self, ImportCandidate};
+use crate::data::icons;
+use crate::data::search;
+use crate::data::store::{EngineGroup, SearchEngine, Store};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// DropDown positions for a keyword that already exists
+const RESOLVE_SKIP: u32 = 0;
+const RESOLVE_REPLACE: u32 = 1;
+
+struct ImportRow {
+    candidate: ImportCandidate,
+    include: CheckButton,
+    kw_entry: Entry,
+    conflict_box: GtkBox,
+    conflict_label: Label,
+    resolution: DropDown,
+}
+
+impl ImportRow {
+    fn keyword(&self) -> String {
+        self.kw_entry.text().trim().to_string()
+    }
+
+    // The engine as it would be imported, with the keyword from the entry
+    fn engine(&self) -> SearchEngine {
+        SearchEngine { keyword: self.keyword(), ..self.candidate.engine.clone() }
+    }
+
+    // Why the engine can't be imported as it is (bad URL, keyword used by a group…).
+    // Clashes with existing engines are left to the Skip/Replace choice.
+    fn issue(&self, groups: &[EngineGroup]) -> Option<String> {
+        search::validate_engine(&self.engine(), &[], groups, None).into_iter().next().map(|(_, message)| message)
+    }
+}
+
+/// Scans installed browsers and lets the user pick which keyword searches to import.
+/// `on_done` runs after engines were added.
+pub fn show_import_dialog<F: Fn() + 'static>(parent: &Window, on_done: F) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Import from Browsers")
+        .default_width(650)
+        .default_height(500)
+        .build();
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    dialog.set_child(Some(&vbox));
+
+    let status = Label::new(Some("Looking for keyword searches in Firefox and Chromium-based browsers…"));
+    status.set_halign(Align::Start);
+    status.set_wrap(true);
+    vbox.append(&status);
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vexpand(true)
+        .build();
+    scrolled.add_css_class("frame");
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    list_box.add_css_class("content");
+    scrolled.set_child(Some(&list_box));
+    vbox.append(&scrolled);
+
+    let import_btn = Button::with_label("Import");
+    import_btn.add_css_class("suggested-action");
+    import_btn.set_halign(Align::End);
+    import_btn.set_sensitive(false);
+    vbox.append(&import_btn);
+
+    dialog.present();
+
+    // Profile databases are copied before reading, keep that off the main thread
+    let handle = gtk4::gio::spawn_blocking(browser_import::find_candidates);
+    let dialog_weak = dialog.downgrade();
+    gtk4::glib::spawn_future_local(async move {
+        let candidates = handle.await.unwrap_or_default();
+        if candidates.is_empty() {
+            status.set_text("No keyword searches found in your browsers.");
+            return;
+        }
+        status.set_text(&format!(
+            "Found {} keyword searches. Uncheck what you don't want and resolve keyword conflicts.",
+            candidates.len()
+        ));
+
+        // Existing keyword -> engine name; group keywords can't be replaced by an engine
+        let store = Store::new().ok();
+        let existing: Rc<HashMap<String, String>> = Rc::new(
+            store
+                .as_ref()
+                .and_then(|s| s.list_engines().ok())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| (e.keyword, e.name))
+                .collect(),
+        );
+        let groups: Rc<Vec<EngineGroup>> = Rc::new(store.and_then(|s| s.list_engine_groups().ok()).unwrap_or_default());
+
+        let rows: Rc<Vec<ImportRow>> = Rc::new(candidates.into_iter().map(|c| build_row(&list_box, c)).collect());
+        // Engines that can't be imported as they are start unchecked
+        for row in rows.iter() {
+            if row.issue(&groups).is_some() {
+                row.include.set_active(false);
+            }
+        }
+
+        let refresh = {
+            let rows = rows.clone();
+            let existing = existing.clone();
+            let groups = groups.clone();
+            let import_btn = import_btn.clone();
+            move || refresh_conflicts(&rows, &existing, &groups, &import_btn)
+        };
+        for row in rows.iter() {
+            let r = refresh.clone();
+            row.include.connect_toggled(move |_| r());
+            let r = refresh.clone();
+            row.kw_entry.connect_changed(move |_| r());
+            let r = refresh.clone();
+            row.resolution.connect_selected_notify(move |_| r());
+        }
+        refresh();
+
+        let on_done = Rc::new(on_done);
+        import_btn.connect_clicked(move |btn| {
+            let (engines, replace) = collect_engines(&rows, &existing, &groups);
+            if engines.is_empty() {
+                return;
+            }
+            btn.set_sensitive(false);
+            status.set_text("Importing…");
+
+            let handle = gtk4::gio::spawn_blocking(move || {
+                let mut engines = engines;
+                for engine in engines.iter_mut() {
+                    engine.icon_path = icons::fetch_favicon(&engine.url).ok();
+                }
+                let store = Store::new()?;
+                anyhow::Ok(store.import_engines(&engines, &replace)?)
+            });
+
+            let btn = btn.clone();
+            let status = status.clone();
+            let on_done = on_done.clone();
+            let dialog_weak = dialog_weak.clone();
+            gtk4::glib::spawn_future_local(async move {
+                match handle.await {
+                    Ok(Ok(_)) => {
+                        on_done();
+                        if let Some(d) = dialog_weak.upgrade() {
+                            d.close();
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        status.set_text(&format!("Import failed: {}", e));
+                        btn.set_sensitive(true);
+                    }
+                    Err(_) => {
+                        status.set_text("Import failed");
+                        btn.set_sensitive(true);
+                    }
+                }
+            });
+        });
+    });
+}
+
+fn build_row(list_box: &ListBox, candidate: ImportCandidate) -> ImportRow {
+    let row = ListBoxRow::new();
+    row.set_activatable(false);
+    let vbox = GtkBox::new(Orientation::Vertical, 4);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    let include = CheckButton::new();
+    include.set_active(true);
+    hbox.append(&include);
+
+    let info = GtkBox::new(Orientation::Vertical, 2);
+    info.set_hexpand(true);
+    let name_label = Label::new(Some(&candidate.engine.name));
+    name_label.set_halign(Align::Start);
+    name_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    let detail = Label::new(Some(&format!("{} · {}", candidate.source, candidate.engine.url)));
+    detail.set_halign(Align::Start);
+    detail.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    detail.add_css_class("dim-label");
+    info.append(&name_label);
+    info.append(&detail);
+    hbox.append(&info);
+
+    let kw_entry = Entry::builder().text(candidate.engine.keyword.as_str()).width_chars(10).build();
+    kw_entry.set_tooltip_text(Some("Keyword"));
+    kw_entry.set_valign(Align::Center);
+    hbox.append(&kw_entry);
+    vbox.append(&hbox);
+
+    let conflict_box = GtkBox::new(Orientation::Horizontal, 8);
+    conflict_box.set_margin_start(32);
+    let conflict_label = Label::new(None);
+    conflict_label.set_halign(Align::Start);
+    conflict_label.set_hexpand(true);
+    conflict_label.set_wrap(true);
+    conflict_label.add_css_class("warning");
+    let resolution = DropDown::from_strings(&["Skip", "Replace existing"]);
+    resolution.set_selected(RESOLVE_SKIP);
+    conflict_box.append(&conflict_label);
+    conflict_box.append(&resolution);
+    conflict_box.set_visible(false);
+    vbox.append(&conflict_box);
+
+    row.set_child(Some(&vbox));
+    list_box.append(&row);
+
+    ImportRow { candidate, include, kw_entry, conflict_box, conflict_label, resolution }
+}
+
+// Invalid engines are shown with the reason and never imported. Conflicts are
+// against existing engines (skip or replace) and against rows earlier in the
+// list (the later one is skipped unless its keyword is changed).
+fn refresh_conflicts(rows: &[ImportRow], existing: &HashMap<String, String>, groups: &[EngineGroup], import_btn: &Button) {
+    let mut seen = HashSet::new();
+    let mut count = 0;
+    for row in rows {
+        let keyword = row.keyword();
+        let included = row.include.is_active();
+        row.kw_entry.set_sensitive(included);
+
+        if let Some(issue) = row.issue(groups) {
+            row.conflict_label.set_text(&issue);
+            row.resolution.set_visible(false);
+            row.conflict_box.set_visible(true);
+            continue;
+        }
+        if !included {
+            row.conflict_box.set_visible(false);
+            continue;
+        }
+
+        if !seen.insert(keyword.clone()) {
+            row.conflict_label.set_text("Same keyword as an entry above. Change it to import this one.");
+            row.resolution.set_visible(false);
+            row.conflict_box.set_visible(true);
+        } else if let Some(name) = existing.get(&keyword) {
+            row.conflict_label.set_text(&format!("\"{}\" is already used by {}", keyword, name));
+            row.resolution.set_visible(true);
+            row.conflict_box.set_visible(true);
+            if row.resolution.selected() == RESOLVE_REPLACE {
+                count += 1;
+            }
+        } else {
+            row.conflict_box.set_visible(false);
+            count += 1;
+        }
+    }
+
+    import_btn.set_sensitive(count > 0);
+    import_btn.set_label(&match count {
+        0 => "Import".to_string(),
+        1 => "Import 1 Engine".to_string(),
+        n => format!("Import {} Engines", n),
+    });
+}
+
+// Engines to insert, plus the keywords allowed to overwrite an existing engine
+fn collect_engines(rows: &[ImportRow], existing: &HashMap<String, String>, groups: &[EngineGroup]) -> (Vec<SearchEngine>, Vec<String>) {
+    let mut seen = HashSet::new();
+    let mut engines = Vec::new();
+    let mut replace = Vec::new();
+    for row in rows {
+        let keyword = row.keyword();
+        if !row.include.is_active() || row.issue(groups).is_some() || !seen.insert(keyword.clone()) {
+            continue;
+        }
+        if existing.contains_key(&keyword) {
+            if row.resolution.selected() != RESOLVE_REPLACE {
+                continue;
+            }
+            replace.push(keyword.clone());
+        }
+        engines.push(row.engine());
+    }
+    (engines, replace)
+}
@@ -0,0 +1,197 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Entry, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Window};
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::data::browser_repository::Browser;
+use crate::data::read_later::{self, display_title};
+use crate::data::routing;
+use crate::data::store::{self, format_age, ReadingItem, Store};
+
+// Every whitespace-separated term must appear in the title, URL or source app
+fn item_matches(item: &ReadingItem, filter: &str) -> bool {
+    let haystack = format!("{} {} {}", display_title(item), item.url, item.source_app.as_deref().unwrap_or_default()).to_lowercase();
+    filter
+        .split_whitespace()
+        .all(|term| haystack.contains(&term.to_lowercase()))
+}
+
+fn remove_item(items: &RefCell<Vec<ReadingItem>>, id: i64) -> bool {
+    let removed = Store::new().and_then(|s| s.delete_reading_item(id));
+    if let Err(e) = &removed {
+        log::warn!("Cannot remove saved link {}: {}", id, e);
+    }
+    items.borrow_mut().retain(|i| i.id != id);
+    removed.is_ok()
+}
+
+/// The read-later queue. Activating a row hands the item's id and URL to `on_pick`
+/// (the picker puts it in the URL bar to choose a browser, and removes it from the
+/// queue once launched); the route button opens it with its routing rule.
+pub fn show_later_dialog<F: Fn(i64, &str) + 'static>(parent: &Window, browsers: Rc<Vec<Browser>>, on_pick: F) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Read Later")
+        .default_width(550)
+        .default_height(450)
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+
+    let search_entry = Entry::builder()
+        .placeholder_text("Filter saved links...")
+        .primary_icon_name("system-search-symbolic")
+        .build();
+    vbox.append(&search_entry);
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vexpand(true)
+        .build();
+    scrolled.add_css_class("frame");
+
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::Browse);
+    scrolled.set_child(Some(&list_box));
+    vbox.append(&scrolled);
+
+    // Items in row order, so filter/activate can map a row index back to its item
+    let items = Rc::new(RefCell::new(Store::new().and_then(|s| s.list_reading_items()).unwrap_or_default()));
+    let rules = Store::new().and_then(|s| s.list_active_routing_rules()).unwrap_or_default();
+    let now = store::now();
+
+    for item in items.borrow().iter() {
+        let row = ListBoxRow::new();
+        let hbox = GtkBox::new(Orientation::Horizontal, 12);
+        hbox.set_margin_top(8);
+        hbox.set_margin_bottom(8);
+        hbox.set_margin_start(12);
+        hbox.set_margin_end(12);
+
+        let info = GtkBox::new(Orientation::Vertical, 2);
+        info.set_hexpand(true);
+        let title_label = Label::builder()
+            .label(display_title(item))
+            .halign(Align::Start)
+            .ellipsize(gtk4::pango::EllipsizeMode::End)
+            .build();
+        title_label.set_tooltip_text(Some(&item.url));
+        let from = read_later::source_app(item).map(|s| format!(" · from {}", s.name)).unwrap_or_default();
+        let meta_label = Label::builder()
+            .label(&format!("{} · {}{}", crate::data::share::fallback_title(&item.url), format_age(item.added_at, now), from))
+            .halign(Align::Start)
+            .ellipsize(gtk4::pango::EllipsizeMode::Middle)
+            .build();
+        meta_label.add_css_class("caption");
+        meta_label.add_css_class("dim-label");
+        info.append(&title_label);
+        info.append(&meta_label);
+        hbox.append(&info);
+
+        // Open with the routing rule that would have handled it
+        let source = read_later::source_app(item);
+        if let Some(rule) = routing::matching_rule(&rules, &item.url, source.as_ref()) {
+            let browser_name = browsers.iter().find(|b| b.id == rule.browser_id).map(|b| b.name.clone()).unwrap_or_else(|| rule.browser_id.clone());
+            let route_btn = Button::from_icon_name("mail-forward-symbolic");
+            route_btn.add_css_class("flat");
+            route_btn.set_tooltip_text(Some(&format!("Open in {} (routing rule)", browser_name)));
+            let (url, item_id, items_route) = (item.url.clone(), item.id, items.clone());
+            let parent_weak = parent.downgrade();
+            route_btn.connect_clicked(move |_| {
+                if routing::try_route(&url, source.as_ref()) {
+                    remove_item(&items_route, item_id);
+                    if let Some(p) = parent_weak.upgrade() {
+                        p.close();
+                    }
+                }
+            });
+            hbox.append(&route_btn);
+        }
+
+        let del_btn = Button::from_icon_name("user-trash-symbolic");
+        del_btn.add_css_class("flat");
+        del_btn.set_tooltip_text(Some("Remove from Read Later"));
+        let (item_id, items_del) = (item.id, items.clone());
+        let lb_weak = list_box.downgrade();
+        del_btn.connect_clicked(move |btn| {
+            if remove_item(&items_del, item_id) {
+                if let (Some(row_widget), Some(lb)) = (btn.ancestor(ListBoxRow::static_type()), lb_weak.upgrade()) {
+                    lb.remove(&row_widget);
+                }
+            }
+        });
+        hbox.append(&del_btn);
+
+        row.set_child(Some(&hbox));
+        list_box.append(&row);
+    }
+
+    let empty = Label::new(Some("Nothing saved for later. Ctrl+D in the picker saves a link here."));
+    empty.add_css_class("dim-label");
+    empty.set_wrap(true);
+    empty.set_margin_top(20);
+    empty.set_margin_bottom(20);
+    list_box.set_placeholder(Some(&empty));
+
+    // Filtering
+    let items_filter = items.clone();
+    let search_for_filter = search_entry.clone();
+    list_box.set_filter_func(move |row| {
+        let filter = search_for_filter.text();
+        match items_filter.borrow().get(row.index() as usize) {
+            Some(item) => item_matches(item, &filter),
+            None => true,
+        }
+    });
+
+    let lb_for_search = list_box.clone();
+    search_entry.connect_changed(move |_| {
+        lb_for_search.invalidate_filter();
+    });
+
+    // Activating a row: the link goes back to the picker
+    let items_activate = items.clone();
+    let dialog_weak = dialog.downgrade();
+    list_box.connect_row_activated(move |_, row| {
+        let item = items_activate.borrow().get(row.index() as usize).map(|i| (i.id, i.url.clone()));
+        if let Some((id, url)) = item {
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+            on_pick(id, &url);
+        }
+    });
+
+    // Enter in the filter picks the first visible row
+    let lb_for_enter = list_box.clone();
+    search_entry.connect_activate(move |_| {
+        let mut idx = 0;
+        while let Some(row) = lb_for_enter.row_at_index(idx) {
+            if row.is_child_visible() {
+                row.emit_activate();
+                return;
+            }
+            idx += 1;
+        }
+    });
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    dialog.set_child(Some(&vbox));
+    dialog.present();
+    search_entry.grab_focus();
+}
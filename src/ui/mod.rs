@@ -1 +1,14 @@
 pub mod window;
+pub mod completion;
+pub mod config_dialog;
+pub mod fuzzy;
+pub mod history_dialog;
+pub mod import_dialog;
+pub mod later_dialog;
+pub mod open_at_dialog;
+pub mod palette;
+pub mod rules_dialog;
+pub mod share_menu;
+pub mod shortcuts;
+pub mod undo_bar;
+pub mod vim;
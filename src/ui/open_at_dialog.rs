@@ -0,0 +1,152 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Entry, Label, Orientation, Window};
+use crate::data::browser_repository::Browser;
+use crate::data::delayed_open::{self, Trigger};
+
+/// Schedules `url` in `browser` and closes the picker. Errors are shown in the
+/// "Open at…" dialog, with `when` filled in.
+pub fn schedule_or_ask(parent: &Window, browser: &Browser, url: &str, when: &str) {
+    match schedule(url, &browser.id, when) {
+        Ok(()) => parent.close(),
+        Err(e) => show_open_at_dialog(parent, browser, url, when, Some(format!("{:#}", e))),
+    }
+}
+
+fn schedule(url: &str, browser_id: &str, when: &str) -> anyhow::Result<()> {
+    let due = delayed_open::parse_due(when).map_err(anyhow::Error::msg)?;
+    match delayed_open::schedule(url, browser_id, due)? {
+        Trigger::Daemon => log::info!("Scheduled {}; the running daemon will open it", url),
+        Trigger::Timer => log::info!("Scheduled {} with a systemd user timer", url),
+    }
+    Ok(())
+}
+
+/// "Open at…": asks for a time ("09:55") or delay ("+1h") to open `url` in `browser`.
+pub fn show_open_at_dialog(parent: &Window, browser: &Browser, url: &str, initial: &str, error: Option<String>) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title(format!("Open in {} at…", browser.name))
+        .default_width(420)
+        .resizable(false)
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+
+    let url_label = Label::new(Some(url));
+    url_label.set_halign(Align::Start);
+    url_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    url_label.set_tooltip_text(Some(url));
+    vbox.append(&url_label);
+
+    let time_entry = Entry::builder()
+        .placeholder_text("09:55, or a delay like +1h, +30m")
+        .text(initial)
+        .build();
+    vbox.append(&time_entry);
+
+    // When it will open, or why it can't
+    let status = Label::new(None);
+    status.set_halign(Align::Start);
+    status.set_wrap(true);
+    status.add_css_class("caption");
+    vbox.append(&status);
+
+    let hint = Label::new(Some("Opened by \"opennav --daemon\" if it is running, else by a systemd user timer."));
+    hint.add_css_class("dim-label");
+    hint.add_css_class("caption");
+    hint.set_halign(Align::Start);
+    hint.set_wrap(true);
+    vbox.append(&hint);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 10);
+    button_box.set_halign(Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let schedule_btn = Button::with_label("Schedule");
+    schedule_btn.add_css_class("suggested-action");
+    button_box.append(&cancel_btn);
+    button_box.append(&schedule_btn);
+    vbox.append(&button_box);
+
+    let browser_name = browser.name.clone();
+    let update_status = {
+        let (status, schedule_btn) = (status.clone(), schedule_btn.clone());
+        move |text: &str| match delayed_open::parse_due(text) {
+            Ok(due) => {
+                status.set_text(&format!("Opens {} in {}", delayed_open::format_due(due), browser_name));
+                status.remove_css_class("error");
+                schedule_btn.set_sensitive(true);
+            }
+            Err(e) => {
+                status.set_text(&e);
+                status.add_css_class("error");
+                schedule_btn.set_sensitive(false);
+            }
+        }
+    };
+    update_status(initial);
+    if let Some(error) = error {
+        status.set_text(&error);
+        status.add_css_class("error");
+    }
+    time_entry.connect_changed(move |entry| update_status(&entry.text()));
+
+    let dialog_weak = dialog.downgrade();
+    cancel_btn.connect_clicked(move |_| {
+        if let Some(d) = dialog_weak.upgrade() {
+            d.close();
+        }
+    });
+
+    let (browser_id, url) = (browser.id.clone(), url.to_string());
+    let (dialog_weak, parent_weak, entry_weak, status_weak) = (dialog.downgrade(), parent.downgrade(), time_entry.downgrade(), status.downgrade());
+    schedule_btn.connect_clicked(move |_| {
+        let Some(entry) = entry_weak.upgrade() else {
+            return;
+        };
+        match schedule(&url, &browser_id, &entry.text()) {
+            Ok(()) => {
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+                if let Some(p) = parent_weak.upgrade() {
+                    p.close();
+                }
+            }
+            Err(e) => {
+                if let Some(status) = status_weak.upgrade() {
+                    status.set_text(&format!("{:#}", e));
+                    status.add_css_class("error");
+                }
+            }
+        }
+    });
+
+    let btn_for_enter = schedule_btn.clone();
+    time_entry.connect_activate(move |_| {
+        if btn_for_enter.is_sensitive() {
+            btn_for_enter.emit_clicked();
+        }
+    });
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() {
+                d.close();
+            }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    dialog.set_child(Some(&vbox));
+    dialog.present();
+    time_entry.grab_focus();
+}
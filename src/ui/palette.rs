@@ -0,0 +1,326 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Entry, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Window};
+use gtk4::glib::WeakRef;
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::data::browser_repository::{self, Browser};
+use crate::data::store::{EngineGroup, SearchEngine, Store};
+use crate::ui::fuzzy::fuzzy_match;
+use crate::ui::shortcuts::hint;
+use crate::ui::undo_bar::UndoBar;
+
+/// One palette command. `run` acts on whatever the picker has selected when it
+/// is picked; the palette closes first.
+pub struct PaletteEntry {
+    pub title: String,
+    pub detail: Option<String>, // Shortcut or keyword, shown dimmed and searched too
+    pub run: Box<dyn Fn()>,
+}
+
+impl PaletteEntry {
+    pub fn new<F: Fn() + 'static>(title: impl Into<String>, detail: Option<String>, run: F) -> Self {
+        PaletteEntry { title: title.into(), detail, run: Box::new(run) }
+    }
+
+    fn score(&self, query: &str) -> Option<i64> {
+        let title = fuzzy_match(query, &self.title).map(|m| m.score);
+        let detail = self.detail.as_deref().and_then(|d| fuzzy_match(query, d)).map(|m| m.score);
+        title.max(detail)
+    }
+}
+
+/// What the picker hands the palette when Ctrl+K is pressed: the selected
+/// browser, the URL bar's targets, and the picker state the commands change.
+pub struct PaletteContext {
+    pub window: Window,
+    pub selected: Option<Browser>,
+    pub pinned: bool, // Whether `selected` is pinned
+    pub targets: Vec<String>,
+    pub engines: Vec<SearchEngine>,
+    pub groups: Vec<EngineGroup>,
+    pub default_keyword: Rc<RefCell<String>>,
+    pub source_id: Option<String>,
+    pub history_btn: WeakRef<Button>,
+    pub later_btn: WeakRef<Button>,
+    pub settings_btn: WeakRef<Button>,
+    pub help_btn: WeakRef<Button>,
+    pub toggle_pin: Rc<dyn Fn(&Browser)>,
+    pub undo_bar: UndoBar,
+    pub refresh_stats: Rc<dyn Fn()>,
+}
+
+/// Every palette command that applies right now, in display order.
+pub fn build_entries(ctx: PaletteContext) -> Vec<PaletteEntry> {
+    let mut entries = Vec::new();
+    let window = &ctx.window;
+
+    if let Some(browser) = &ctx.selected {
+        if browser_repository::private_flag(browser).is_some() {
+            let browser = browser.clone();
+            let targets = ctx.targets.clone();
+            let window_weak = window.downgrade();
+            // Not recorded in history or stats
+            entries.push(PaletteEntry::new(format!("Open in {} (Private Window)", browser.name), None, move || {
+                let urls: Vec<&str> = targets.iter().map(|u| u.as_str()).collect();
+                match browser_repository::launch_private(&browser, &urls) {
+                    Ok(()) => {
+                        if let Some(w) = window_weak.upgrade() {
+                            w.close();
+                        }
+                    }
+                    Err(e) => log::warn!("Private launch failed: {}", e),
+                }
+            }));
+        }
+        // Scheduled for later in this browser ("open the standup board at 9:55")
+        if let Some(url) = ctx.targets.first().filter(|u| !u.is_empty()) {
+            for (title, when) in [(format!("Open in {} in 1 Hour", browser.name), Some("+1h")), (format!("Open in {} at…", browser.name), None)] {
+                let (browser, url, window_weak) = (browser.clone(), url.clone(), window.downgrade());
+                entries.push(PaletteEntry::new(title, None, move || {
+                    let Some(w) = window_weak.upgrade() else {
+                        return;
+                    };
+                    match when {
+                        Some(when) => crate::ui::open_at_dialog::schedule_or_ask(&w, &browser, &url, when),
+                        None => crate::ui::open_at_dialog::show_open_at_dialog(&w, &browser, &url, "", None),
+                    }
+                }));
+            }
+        }
+        let title = format!("{} {}", if ctx.pinned { "Unpin" } else { "Pin" }, browser.name);
+        let (browser, toggle_pin) = (browser.clone(), ctx.toggle_pin.clone());
+        entries.push(PaletteEntry::new(title, hint("toggle-pin"), move || toggle_pin(&browser)));
+    }
+
+    if let Some(url) = ctx.targets.first().filter(|u| !u.is_empty()).cloned() {
+        let window_weak = window.downgrade();
+        entries.push(PaletteEntry::new("Copy URL", Some(url.clone()), move || {
+            if let Some(w) = window_weak.upgrade() {
+                w.clipboard().set_text(&url);
+            }
+        }));
+        for (action, title) in crate::ui::share_menu::ACTIONS {
+            let (window_weak, url, source_app) = (window.downgrade(), url.clone(), ctx.source_id.clone());
+            entries.push(PaletteEntry::new(*title, None, move || {
+                if let Some(w) = window_weak.upgrade() {
+                    crate::ui::share_menu::run(*action, &w, &url, source_app.as_deref(), || {});
+                }
+            }));
+        }
+    }
+
+    // Groups are valid defaults too: resolve_search falls back to them
+    let search_with = ctx
+        .engines
+        .iter()
+        .map(|e| (&e.name, &e.keyword))
+        .chain(ctx.groups.iter().map(|g| (&g.name, &g.keyword)));
+    for (name, keyword) in search_with {
+        let (default_keyword, keyword) = (ctx.default_keyword.clone(), keyword.clone());
+        entries.push(PaletteEntry::new(format!("Search with {}", name), Some(keyword.clone()), move || {
+            *default_keyword.borrow_mut() = keyword.clone();
+        }));
+    }
+
+    for (title, name, button_weak) in [
+        ("Open History", "history", &ctx.history_btn),
+        ("Open Read Later", "read-later", &ctx.later_btn),
+        ("Open Settings", "settings", &ctx.settings_btn),
+        ("Show Shortcuts", "help", &ctx.help_btn),
+    ] {
+        let button_weak = button_weak.clone();
+        entries.push(PaletteEntry::new(title, hint(name), move || {
+            if let Some(btn) = button_weak.upgrade() {
+                btn.emit_clicked();
+            }
+        }));
+    }
+
+    let window_weak = window.downgrade();
+    entries.push(PaletteEntry::new("Edit Routing Rules", None, move || {
+        if let Some(w) = window_weak.upgrade() {
+            crate::ui::rules_dialog::show_rules_dialog(&w);
+        }
+    }));
+
+    for (title, recent) in [("Clear Recent Usage", true), ("Clear Usage Counts", false)] {
+        let (undo_bar, refresh) = (ctx.undo_bar.clone(), ctx.refresh_stats.clone());
+        entries.push(PaletteEntry::new(title, None, move || {
+            let Ok(store) = Store::new() else {
+                return;
+            };
+            let reset = if recent { store.reset_recent_stats() } else { store.reset_frequent_stats() };
+            if let Ok(trash_id) = reset {
+                refresh();
+                let refresh = refresh.clone();
+                undo_bar.show(if recent { "Recent usage reset" } else { "Usage counts reset" }, trash_id, move || refresh());
+            }
+        }));
+    }
+
+    entries
+}
+
+/// Ctrl+K: fuzzy-searchable list of everything the picker can do.
+pub fn show_palette(parent: &Window, entries: Vec<PaletteEntry>) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .decorated(false)
+        .title("Commands")
+        .default_width(460)
+        .default_height(380)
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.set_margin_top(12);
+    vbox.set_margin_bottom(12);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+
+    let search_entry = Entry::builder()
+        .placeholder_text("Type a command...")
+        .primary_icon_name("system-search-symbolic")
+        .build();
+    vbox.append(&search_entry);
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .vexpand(true)
+        .build();
+    scrolled.add_css_class("frame");
+
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::Browse);
+    scrolled.set_child(Some(&list_box));
+    vbox.append(&scrolled);
+
+    let empty = Label::new(Some("No matching commands."));
+    empty.add_css_class("dim-label");
+    empty.set_margin_top(20);
+    empty.set_margin_bottom(20);
+    list_box.set_placeholder(Some(&empty));
+
+    // Row i shows entries[order[i]]; rebuilt on every keystroke, best match first
+    let entries = Rc::new(entries);
+    let order = Rc::new(RefCell::new(Vec::new()));
+    populate(&list_box, &entries, "", &order);
+
+    let entries_search = entries.clone();
+    let order_search = order.clone();
+    let lb_for_search = list_box.clone();
+    search_entry.connect_changed(move |entry| {
+        populate(&lb_for_search, &entries_search, entry.text().trim(), &order_search);
+    });
+
+    let entries_activate = entries.clone();
+    let dialog_weak = dialog.downgrade();
+    list_box.connect_row_activated(move |_, row| {
+        let index = order.borrow().get(row.index() as usize).copied();
+        if let Some(d) = dialog_weak.upgrade() {
+            d.close();
+        }
+        if let Some(entry) = index.and_then(|i| entries_activate.get(i)) {
+            (entry.run)();
+        }
+    });
+
+    // Enter runs the highlighted command
+    let lb_for_enter = list_box.clone();
+    search_entry.connect_activate(move |_| {
+        if let Some(row) = lb_for_enter.selected_row().or_else(|| lb_for_enter.row_at_index(0)) {
+            row.emit_activate();
+        }
+    });
+
+    // Up/Down move the highlight while typing; Esc closes
+    let lb_for_keys = list_box.clone();
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        match key {
+            gtk4::gdk::Key::Escape => {
+                if let Some(d) = d_weak.upgrade() {
+                    d.close();
+                }
+            }
+            // Focus stays in the search entry
+            gtk4::gdk::Key::Down | gtk4::gdk::Key::Up => {
+                let current = lb_for_keys.selected_row().map(|r| r.index()).unwrap_or(-1);
+                let next = if key == gtk4::gdk::Key::Down { current + 1 } else { (current - 1).max(0) };
+                if let Some(row) = lb_for_keys.row_at_index(next) {
+                    lb_for_keys.select_row(Some(&row));
+                    scroll_to_row(&row);
+                }
+            }
+            _ => return gtk4::glib::Propagation::Proceed,
+        }
+        gtk4::glib::Propagation::Stop
+    });
+    dialog.add_controller(key_controller);
+
+    dialog.set_child(Some(&vbox));
+    dialog.present();
+    search_entry.grab_focus();
+}
+
+fn populate(list_box: &ListBox, entries: &[PaletteEntry], query: &str, order: &RefCell<Vec<usize>>) {
+    // Rows only; the placeholder is a child too
+    while let Some(row) = list_box.row_at_index(0) {
+        list_box.remove(&row);
+    }
+
+    let mut matches: Vec<(usize, i64)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| e.score(query).map(|score| (i, score)))
+        .collect();
+    // Ties keep the palette's own order
+    matches.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    for (i, _) in &matches {
+        let entry = &entries[*i];
+        let row = ListBoxRow::new();
+        let hbox = GtkBox::new(Orientation::Horizontal, 12);
+        hbox.set_margin_top(6);
+        hbox.set_margin_bottom(6);
+        hbox.set_margin_start(12);
+        hbox.set_margin_end(12);
+        let title = Label::builder()
+            .label(&entry.title)
+            .halign(Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk4::pango::EllipsizeMode::End)
+            .build();
+        hbox.append(&title);
+        if let Some(detail) = &entry.detail {
+            let detail_label = Label::new(Some(detail));
+            detail_label.add_css_class("dim-label");
+            detail_label.add_css_class("caption");
+            hbox.append(&detail_label);
+        }
+        row.set_child(Some(&hbox));
+        list_box.append(&row);
+    }
+
+    *order.borrow_mut() = matches.into_iter().map(|(i, _)| i).collect();
+    list_box.select_row(list_box.row_at_index(0).as_ref());
+}
+
+// Keeps the highlighted row in view while navigating from the search entry
+fn scroll_to_row(row: &ListBoxRow) {
+    let Some(scrolled) = row.ancestor(ScrolledWindow::static_type()).and_downcast::<ScrolledWindow>() else {
+        return;
+    };
+    let Some(bounds) = row.compute_bounds(&scrolled) else {
+        return;
+    };
+    let adjustment = scrolled.vadjustment();
+    let (top, bottom) = (bounds.y() as f64, (bounds.y() + bounds.height()) as f64);
+    if top < 0.0 {
+        adjustment.set_value(adjustment.value() + top);
+    } else if bottom > adjustment.page_size() {
+        adjustment.set_value(adjustment.value() + bottom - adjustment.page_size());
+    }
+}
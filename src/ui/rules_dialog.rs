@@ -0,0 +1,457 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, DropDown, Entry, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, StringList, Window};
+use crate::data::browser_repository::{self, Browser};
+use crate::data::store::{RoutingRule, Store};
+
+pub fn build_rules_management_ui() -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 10);
+
+    // Header / Toolbar
+    let toolbar = GtkBox::new(Orientation::Horizontal, 10);
+    let label = Label::new(Some("<b>Routing Rules</b>"));
+    label.set_use_markup(true);
+    label.set_hexpand(true);
+    label.set_halign(Align::Start);
+    toolbar.append(&label);
+
+    let add_btn = Button::with_label("Add");
+    add_btn.add_css_class("suggested-action");
+    add_btn.set_width_request(100);
+    toolbar.append(&add_btn);
+
+    container.append(&toolbar);
+
+    // List
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_min_content_height(120);
+    scrolled.add_css_class("frame");
+
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    list_box.add_css_class("content");
+    scrolled.set_child(Some(&list_box));
+    container.append(&scrolled);
+
+    let file_rules = crate::data::config_file::current().rules.len();
+    if file_rules > 0 {
+        let note = Label::new(Some(&format!(
+            "{} more rules come from config.toml and replace rules here for the same domain and source app.",
+            file_rules
+        )));
+        note.add_css_class("dim-label");
+        note.set_wrap(true);
+        note.set_halign(Align::Start);
+        container.append(&note);
+    }
+
+    let browsers = browser_repository::get_installed_browsers();
+    populate_list(&list_box, &browsers);
+
+    container.append(&build_schedule_section(&browsers));
+
+    let list_box_clone = list_box.clone();
+    add_btn.connect_clicked(move |btn| {
+        let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        if let Some(parent) = root {
+            show_add_dialog(&parent, list_box_clone.clone(), browsers.clone());
+        }
+    });
+
+    container
+}
+
+/// The routing rules on their own, for the command palette's "Edit Routing Rules".
+pub fn show_rules_dialog(parent: &Window) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Routing Rules")
+        .default_width(550)
+        .default_height(450)
+        .build();
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    let rules_ui = build_rules_management_ui();
+    rules_ui.set_margin_top(20);
+    rules_ui.set_margin_bottom(20);
+    rules_ui.set_margin_start(20);
+    rules_ui.set_margin_end(20);
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .child(&rules_ui)
+        .build();
+    dialog.set_child(Some(&scrolled));
+    dialog.present();
+}
+
+fn browser_name(browsers: &[Browser], id: &str) -> String {
+    browsers
+        .iter()
+        .find(|b| b.id == id)
+        .map(|b| b.name.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+// "Preferred Hours": browsers listed first while their schedule is active
+fn build_schedule_section(browsers: &[Browser]) -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 10);
+
+    let toolbar = GtkBox::new(Orientation::Horizontal, 10);
+    let label = Label::new(Some("<b>Preferred Hours</b>"));
+    label.set_use_markup(true);
+    label.set_hexpand(true);
+    label.set_halign(Align::Start);
+    toolbar.append(&label);
+
+    let add_btn = Button::with_label("Add Schedule");
+    add_btn.set_width_request(100);
+    toolbar.append(&add_btn);
+    container.append(&toolbar);
+
+    let list_box = ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    list_box.add_css_class("content");
+    list_box.add_css_class("frame");
+    container.append(&list_box);
+
+    populate_schedules(&list_box, browsers);
+
+    let browsers = browsers.to_vec();
+    add_btn.connect_clicked(move |btn| {
+        let root = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        if let Some(parent) = root {
+            show_schedule_dialog(&parent, list_box.clone(), browsers.clone());
+        }
+    });
+
+    container
+}
+
+fn populate_schedules(list_box: &ListBox, browsers: &[Browser]) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    let schedules = Store::new().and_then(|s| s.list_browser_schedules()).unwrap_or_default();
+    if schedules.is_empty() {
+        let empty = Label::new(Some("No schedules. A browser with preferred hours (e.g. \"Mon-Fri 09:00-18:00\") is listed first during them."));
+        empty.add_css_class("dim-label");
+        empty.set_wrap(true);
+        empty.set_margin_top(12);
+        empty.set_margin_bottom(12);
+        list_box.append(&empty);
+        return;
+    }
+    for (browser_id, schedule) in schedules {
+        let row = ListBoxRow::new();
+        let hbox = GtkBox::new(Orientation::Horizontal, 12);
+        hbox.set_margin_top(8);
+        hbox.set_margin_bottom(8);
+        hbox.set_margin_start(12);
+        hbox.set_margin_end(12);
+
+        let active = if crate::data::schedule::is_active(&schedule) { "  <i>(now)</i>" } else { "" };
+        let text = Label::builder()
+            .label(&format!(
+                "{}  ·  <tt>{}</tt>{}",
+                gtk4::glib::markup_escape_text(&browser_name(browsers, &browser_id)),
+                gtk4::glib::markup_escape_text(&schedule),
+                active
+            ))
+            .use_markup(true)
+            .halign(Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk4::pango::EllipsizeMode::End)
+            .build();
+        hbox.append(&text);
+
+        let del_btn = Button::from_icon_name("user-trash-symbolic");
+        del_btn.add_css_class("destructive-action");
+        del_btn.set_tooltip_text(Some("Delete Schedule"));
+        let lb_weak = list_box.downgrade();
+        let browsers = browsers.to_vec();
+        del_btn.connect_clicked(move |_| {
+            if let Ok(store) = Store::new() {
+                if store.delete_browser_schedule(&browser_id).is_ok() {
+                    if let Some(lb) = lb_weak.upgrade() {
+                        populate_schedules(&lb, &browsers);
+                    }
+                }
+            }
+        });
+        hbox.append(&del_btn);
+
+        row.set_child(Some(&hbox));
+        list_box.append(&row);
+    }
+}
+
+fn show_schedule_dialog(parent: &Window, list_box: ListBox, browsers: Vec<Browser>) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Add Preferred Hours")
+        .default_width(400)
+        .build();
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 15);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    dialog.set_child(Some(&vbox));
+
+    let names: Vec<&str> = browsers.iter().map(|b| b.name.as_str()).collect();
+    let browser_dropdown = DropDown::new(Some(StringList::new(&names)), None::<&gtk4::Expression>);
+    vbox.append(&Label::new(Some("Browser")));
+    vbox.append(&browser_dropdown);
+
+    let schedule_entry = Entry::builder().placeholder_text("e.g. Mon-Fri 09:00-18:00").build();
+    vbox.append(&Label::new(Some("Preferred during")));
+    vbox.append(&schedule_entry);
+    let error_label = validation_label();
+    vbox.append(&error_label);
+
+    let save_btn = Button::with_label("Save Schedule");
+    save_btn.add_css_class("suggested-action");
+
+    let dialog_weak = dialog.downgrade();
+    save_btn.connect_clicked(move |_| {
+        let schedule = schedule_entry.text().trim().to_string();
+        let Some(browser) = browsers.get(browser_dropdown.selected() as usize) else {
+            return;
+        };
+        if let Err(e) = crate::data::schedule::Schedule::parse(&schedule) {
+            error_label.set_text(&e);
+            error_label.set_visible(true);
+            return;
+        }
+        if let Ok(store) = Store::new() {
+            if store.set_browser_schedule(&browser.id, &schedule).is_ok() {
+                populate_schedules(&list_box, &browsers);
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+            }
+        }
+    });
+
+    vbox.append(&save_btn);
+    dialog.present();
+}
+
+fn validation_label() -> Label {
+    let label = Label::new(None);
+    label.add_css_class("error");
+    label.set_halign(Align::Start);
+    label.set_wrap(true);
+    label.set_visible(false);
+    label
+}
+
+fn populate_list(list_box: &ListBox, browsers: &[Browser]) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    if let Ok(store) = Store::new() {
+        if let Ok(rules) = store.list_routing_rules() {
+            if rules.is_empty() {
+                let empty = Label::new(Some("No rules yet. OpenNav offers one when you keep opening a site in the same browser."));
+                empty.add_css_class("dim-label");
+                empty.set_wrap(true);
+                empty.set_margin_top(12);
+                empty.set_margin_bottom(12);
+                list_box.append(&empty);
+            }
+            for rule in rules {
+                add_row(list_box, rule, browsers);
+            }
+        }
+    }
+}
+
+fn add_row(list_box: &ListBox, rule: RoutingRule, browsers: &[Browser]) {
+    let row = ListBoxRow::new();
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+
+    // Fall back to the desktop id if the browser was uninstalled
+    let browser_name = browser_name(browsers, &rule.browser_id);
+
+    let condition = match (rule.domain.is_empty(), rule.source_app.as_deref()) {
+        (false, Some(source)) => format!(
+            "<tt>{}</tt> from {}",
+            gtk4::glib::markup_escape_text(&rule.domain),
+            gtk4::glib::markup_escape_text(source)
+        ),
+        (true, Some(source)) => format!("Links from {}", gtk4::glib::markup_escape_text(source)),
+        _ => format!("<tt>{}</tt>", gtk4::glib::markup_escape_text(&rule.domain)),
+    };
+    let condition = match rule.schedule.as_deref() {
+        Some(schedule) => format!("{}, {}", condition, gtk4::glib::markup_escape_text(schedule)),
+        None => condition,
+    };
+    let condition = match rule.requires.as_deref() {
+        Some(requires) => format!("{}, when <tt>{}</tt>", condition, gtk4::glib::markup_escape_text(requires)),
+        None => condition,
+    };
+    let text = Label::builder()
+        .label(&format!("{}  →  {}", condition, gtk4::glib::markup_escape_text(&browser_name)))
+        .use_markup(true)
+        .halign(Align::Start)
+        .hexpand(true)
+        .ellipsize(gtk4::pango::EllipsizeMode::End)
+        .build();
+    hbox.append(&text);
+
+    let del_btn = Button::from_icon_name("user-trash-symbolic");
+    del_btn.add_css_class("destructive-action");
+    del_btn.set_tooltip_text(Some("Delete Rule"));
+
+    let lb_weak = list_box.downgrade();
+    let rule_id = rule.id;
+    del_btn.connect_clicked(move |btn| {
+        if let Ok(store) = Store::new() {
+            if store.delete_routing_rule(rule_id).is_ok() {
+                if let Some(row_widget) = btn.ancestor(ListBoxRow::static_type()) {
+                    if let Some(lb) = lb_weak.upgrade() {
+                        lb.remove(&row_widget);
+                    }
+                }
+            }
+        }
+    });
+    hbox.append(&del_btn);
+
+    row.set_child(Some(&hbox));
+    list_box.append(&row);
+}
+
+fn show_add_dialog(parent: &Window, list_box: ListBox, browsers: Vec<Browser>) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Add Routing Rule")
+        .default_width(400)
+        .build();
+
+    // Add Esc handler for dialog
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() { d.close(); }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 15);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+    dialog.set_child(Some(&vbox));
+
+    let domain_entry = Entry::builder().placeholder_text("Domain (e.g. github.com or *.corp)").build();
+    vbox.append(&Label::new(Some("Domain")));
+    vbox.append(&domain_entry);
+
+    // Matched against the app shown as "Opened from" in the picker
+    let source_entry = Entry::builder().placeholder_text("Any app (or e.g. slack, thunderbird)").build();
+    vbox.append(&Label::new(Some("Opened from")));
+    vbox.append(&source_entry);
+
+    let schedule_entry = Entry::builder().placeholder_text("Any time (or e.g. Mon-Fri 09:00-18:00)").build();
+    vbox.append(&Label::new(Some("Only during")));
+    vbox.append(&schedule_entry);
+    let schedule_error = validation_label();
+    vbox.append(&schedule_error);
+
+    // Machine state, see data::environment
+    let requires_entry = Entry::builder().placeholder_text("Always (or e.g. iface:tun0, host:work-laptop, env:CORP=1)").build();
+    vbox.append(&Label::new(Some("Only when")));
+    vbox.append(&requires_entry);
+    let requires_error = validation_label();
+    vbox.append(&requires_error);
+
+    let names: Vec<&str> = browsers.iter().map(|b| b.name.as_str()).collect();
+    let browser_dropdown = DropDown::new(Some(StringList::new(&names)), None::<&gtk4::Expression>);
+    vbox.append(&Label::new(Some("Open in")));
+    vbox.append(&browser_dropdown);
+
+    let save_btn = Button::with_label("Save Rule");
+    save_btn.add_css_class("suggested-action");
+
+    let dialog_weak = dialog.downgrade();
+    save_btn.connect_clicked(move |_| {
+        let domain = domain_entry.text().trim().to_lowercase();
+        let source = source_entry.text().trim().to_lowercase();
+        let Some(browser) = browsers.get(browser_dropdown.selected() as usize) else {
+            return;
+        };
+        if domain.is_empty() && source.is_empty() {
+            return;
+        }
+        let schedule = schedule_entry.text().trim().to_string();
+        if !schedule.is_empty() {
+            if let Err(e) = crate::data::schedule::Schedule::parse(&schedule) {
+                schedule_error.set_text(&e);
+                schedule_error.set_visible(true);
+                return;
+            }
+        }
+        let requires = requires_entry.text().trim().to_string();
+        if !requires.is_empty() {
+            if let Err(e) = crate::data::environment::validate(&requires) {
+                requires_error.set_text(&e);
+                requires_error.set_visible(true);
+                return;
+            }
+        }
+
+        let rule = RoutingRule {
+            id: 0,
+            domain,
+            browser_id: browser.id.clone(),
+            source_app: Some(source).filter(|s| !s.is_empty()),
+            schedule: Some(schedule).filter(|s| !s.is_empty()),
+            requires: Some(requires).filter(|s| !s.is_empty()),
+        };
+        if let Ok(store) = Store::new() {
+            if store.add_routing_rule(&rule).is_ok() {
+                populate_list(&list_box, &browsers);
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+            }
+        }
+    });
+
+    vbox.append(&save_btn);
+    dialog.present();
+}
@@ -0,0 +1,230 @@
+// Things to do with the incoming link other than opening it: copy it without
+// tracking parameters or as a Markdown/HTML link, show it as a QR code for a
+// phone, or save it to the read-later queue. All act on the cleaned URL.
+
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, DrawingArea, Label, MenuButton, Orientation, Popover, Window};
+use crate::data::share;
+use crate::data::store::Store;
+use std::rc::Rc;
+
+const ICON: &str = "send-to-symbolic";
+const DONE_ICON: &str = "object-select-symbolic";
+// Light modules around the code, as scanners expect
+const QR_QUIET_ZONE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareAction {
+    CopyClean,
+    CopyMarkdown,
+    CopyHtml,
+    QrCode,
+    ReadLater,
+}
+
+pub const ACTIONS: &[(ShareAction, &str)] = &[
+    (ShareAction::CopyClean, "Copy Clean URL"),
+    (ShareAction::CopyMarkdown, "Copy as Markdown Link"),
+    (ShareAction::CopyHtml, "Copy as HTML Link"),
+    (ShareAction::QrCode, "Show QR Code"),
+    (ShareAction::ReadLater, "Save for Later"),
+];
+
+/// Runs `action` on `url`. Copying keeps the picker open (the clipboard goes
+/// away with it); saving it for later closes it, keeping `source_app` (the id
+/// of the app the link came from) with it. `done` runs once the clipboard is
+/// set or the link saved.
+pub fn run<F: FnOnce() + 'static>(action: ShareAction, window: &Window, url: &str, source_app: Option<&str>, done: F) {
+    let url = share::clean_url(url);
+    match action {
+        ShareAction::CopyClean => {
+            window.clipboard().set_text(&url);
+            done();
+        }
+        ShareAction::CopyMarkdown | ShareAction::CopyHtml => {
+            let window_weak = window.downgrade();
+            with_title(url, move |url, title| {
+                let title = title.unwrap_or_else(|| share::fallback_title(&url));
+                let text = if action == ShareAction::CopyMarkdown {
+                    share::markdown_link(&url, &title)
+                } else {
+                    share::html_link(&url, &title)
+                };
+                if let Some(w) = window_weak.upgrade() {
+                    w.clipboard().set_text(&text);
+                    done();
+                }
+            });
+        }
+        ShareAction::QrCode => show_qr_dialog(window, &url),
+        ShareAction::ReadLater => {
+            // Nothing left to do in the picker; hide it while the title loads
+            window.set_visible(false);
+            let window_weak = window.downgrade();
+            let source_app = source_app.map(str::to_string);
+            with_title(url, move |url, title| {
+                let saved = Store::new().and_then(|s| s.add_reading_item(&url, title.as_deref(), source_app.as_deref()));
+                if let Err(e) = saved {
+                    log::warn!("Cannot save {} for later: {}", url, e);
+                }
+                done();
+                if let Some(w) = window_weak.upgrade() {
+                    w.close();
+                }
+            });
+        }
+    }
+}
+
+// Fetches the page title off the UI thread, then calls back with (url, title).
+// Without the "fetch_page_titles" setting the link is never requested: title is None.
+fn with_title<F: FnOnce(String, Option<String>) + 'static>(url: String, then: F) {
+    if !share::fetch_titles_enabled() {
+        then(url, None);
+        return;
+    }
+    let url_for_fetch = url.clone();
+    let handle = gtk4::gio::spawn_blocking(move || share::fetch_title(&url_for_fetch));
+    gtk4::glib::spawn_future_local(async move {
+        let title = handle.await.ok().flatten();
+        then(url, title);
+    });
+}
+
+/// The share button next to the URL bar. `current_url` is what the picker would
+/// open right now, None when the URL bar is empty.
+pub fn build_share_button<F: Fn() -> Option<String> + 'static>(window: &Window, current_url: F, source_app: Option<String>) -> MenuButton {
+    let button = MenuButton::builder()
+        .icon_name(ICON)
+        .tooltip_text("Copy, share or save this link")
+        .valign(Align::Center)
+        .build();
+    button.add_css_class("flat");
+
+    let popover = Popover::new();
+    let list = GtkBox::new(Orientation::Vertical, 2);
+    let current_url = Rc::new(current_url);
+    let window_weak = window.downgrade();
+
+    for (action, title) in ACTIONS {
+        let item = Button::with_label(title);
+        item.add_css_class("flat");
+        if let Some(child) = item.child().and_downcast::<Label>() {
+            child.set_halign(Align::Start);
+        }
+        let (action, current_url, window_weak, source_app) = (*action, current_url.clone(), window_weak.clone(), source_app.clone());
+        let (popover, button_weak) = (popover.clone(), button.downgrade());
+        item.connect_clicked(move |_| {
+            popover.popdown();
+            let (Some(window), Some(url)) = (window_weak.upgrade(), current_url()) else {
+                return;
+            };
+            let button_weak = button_weak.clone();
+            run(action, &window, &url, source_app.as_deref(), move || {
+                if let Some(b) = button_weak.upgrade() {
+                    flash_done(&b);
+                }
+            });
+        });
+        list.append(&item);
+    }
+
+    popover.set_child(Some(&list));
+    button.set_popover(Some(&popover));
+    button
+}
+
+// Brief checkmark on the button after copying
+fn flash_done(button: &MenuButton) {
+    button.set_icon_name(DONE_ICON);
+    let button_weak = button.downgrade();
+    gtk4::glib::timeout_add_local_once(std::time::Duration::from_millis(1500), move || {
+        if let Some(b) = button_weak.upgrade() {
+            b.set_icon_name(ICON);
+        }
+    });
+}
+
+fn show_qr_dialog(parent: &Window, url: &str) {
+    let dialog = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("QR Code")
+        .default_width(340)
+        .resizable(false)
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 12);
+    vbox.set_margin_top(20);
+    vbox.set_margin_bottom(20);
+    vbox.set_margin_start(20);
+    vbox.set_margin_end(20);
+
+    match qrcode::QrCode::new(url.as_bytes()) {
+        Ok(code) => {
+            let modules = code.width();
+            let colors = code.to_colors();
+            let area = DrawingArea::builder().content_width(300).content_height(300).halign(Align::Center).build();
+            area.set_draw_func(move |_, cr, width, height| {
+                let size = (modules + 2 * QR_QUIET_ZONE) as f64;
+                // Whole pixels per module keep the edges sharp
+                let scale = (width.min(height) as f64 / size).floor().max(1.0);
+                let left = (width as f64 - scale * size) / 2.0;
+                let top = (height as f64 - scale * size) / 2.0;
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+                cr.rectangle(left, top, scale * size, scale * size);
+                let _ = cr.fill();
+                cr.set_source_rgb(0.0, 0.0, 0.0);
+                for (i, color) in colors.iter().enumerate() {
+                    if *color == qrcode::Color::Dark {
+                        let (x, y) = (i % modules + QR_QUIET_ZONE, i / modules + QR_QUIET_ZONE);
+                        cr.rectangle(left + x as f64 * scale, top + y as f64 * scale, scale, scale);
+                    }
+                }
+                let _ = cr.fill();
+            });
+            vbox.append(&area);
+        }
+        Err(e) => {
+            let error = Label::new(Some(&format!("Cannot show this link as a QR code: {}", e)));
+            error.add_css_class("error");
+            error.set_wrap(true);
+            vbox.append(&error);
+        }
+    }
+
+    let url_label = Label::new(Some(url));
+    url_label.add_css_class("dim-label");
+    url_label.add_css_class("caption");
+    url_label.set_selectable(true);
+    url_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    url_label.set_max_width_chars(40);
+    url_label.set_tooltip_text(Some(url));
+    vbox.append(&url_label);
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(Align::End);
+    let dialog_weak = dialog.downgrade();
+    close_btn.connect_clicked(move |_| {
+        if let Some(d) = dialog_weak.upgrade() {
+            d.close();
+        }
+    });
+    vbox.append(&close_btn);
+
+    let d_weak = dialog.downgrade();
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            if let Some(d) = d_weak.upgrade() {
+                d.close();
+            }
+            return gtk4::glib::Propagation::Stop;
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+    dialog.add_controller(key_controller);
+
+    dialog.set_child(Some(&vbox));
+    dialog.present();
+}
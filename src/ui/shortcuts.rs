@@ -0,0 +1,323 @@
+// Picker shortcuts: one registry for what the keys do, what Settings lets you
+// rebind and what the Shortcuts dialog lists.
+//
+// An action's accelerator comes from config.toml's [keybindings], else the
+// "keybinding.<action>" setting, else its default. An empty value unbinds it,
+// "|" separates alternatives ("<Control>question|<Control>slash").
+
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Label, Orientation, ShortcutController};
+use crate::data::config_file;
+use crate::data::store::Store;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+pub struct Action {
+    pub name: &'static str, // Setting and config.toml key
+    pub title: &'static str,
+    pub default_accel: &'static str,
+}
+
+pub const ACTIONS: &[Action] = &[
+    Action { name: "focus-url", title: "Focus URL Bar", default_accel: "<Control>l" },
+    Action { name: "toggle-pin", title: "Toggle Pin", default_accel: "<Control>p" },
+    Action { name: "undo", title: "Undo", default_accel: "<Control>z" },
+    Action { name: "history", title: "History", default_accel: "<Control>h" },
+    Action { name: "save-later", title: "Save for Later", default_accel: "<Control>d" },
+    Action { name: "read-later", title: "Read Later Queue", default_accel: "<Control><Shift>d" },
+    Action { name: "settings", title: "Settings", default_accel: "<Control>s" },
+    Action { name: "help", title: "Shortcuts (Help)", default_accel: "<Control>question|<Control>slash" },
+    Action { name: "palette", title: "Command Palette", default_accel: "<Control>k" },
+];
+
+// Keys the picker handles itself; listed in the help, not rebindable
+const FIXED_KEYS: &[(&str, &str)] = &[
+    ("Type", "Search Browsers"),
+    ("Tab", "Accept URL Suggestion"),
+    ("Up/Down Arrows", "Navigation"),
+    ("Enter / Click", "Launch Selected"),
+    ("Ctrl + Enter", "Launch & Keep Open"),
+    ("Ctrl + Click", "Launch & Keep Open"),
+    ("Esc", "Close / Clear Search"),
+];
+
+const SETTING_PREFIX: &str = "keybinding.";
+
+fn setting_key(name: &str) -> String {
+    format!("{}{}", SETTING_PREFIX, name)
+}
+
+fn find(name: &str) -> Option<&'static Action> {
+    ACTIONS.iter().find(|a| a.name == name)
+}
+
+// config.toml's bindings and the ones saved from Settings, read once for all actions
+struct Bindings {
+    file: BTreeMap<String, String>,
+    saved: HashMap<String, String>,
+}
+
+impl Bindings {
+    fn load() -> Self {
+        let saved = Store::new()
+            .and_then(|s| s.list_settings_with_prefix(SETTING_PREFIX))
+            .map(|rows| {
+                rows.into_iter()
+                    .filter_map(|(key, accel)| Some((key.strip_prefix(SETTING_PREFIX)?.to_string(), accel)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Bindings { file: config_file::keybindings(), saved }
+    }
+
+    fn accelerator(&self, name: &str) -> Option<String> {
+        let accel = self
+            .file
+            .get(name)
+            .or_else(|| self.saved.get(name))
+            .cloned()
+            .or_else(|| find(name).map(|a| a.default_accel.to_string()))?;
+        Some(accel).filter(|a| !a.trim().is_empty())
+    }
+}
+
+/// The accelerator in effect for an action, None when unbound.
+pub fn accelerator(name: &str) -> Option<String> {
+    Bindings::load().accelerator(name)
+}
+
+// The accelerators of a binding with alternatives
+fn alternatives(accel: &str) -> impl Iterator<Item = &str> {
+    accel.split('|').map(str::trim).filter(|a| !a.is_empty())
+}
+
+/// "Ctrl+L" for "<Control>l", "Ctrl+? / Ctrl+/" for alternatives; invalid
+/// accelerators are shown as written.
+pub fn label(accel: &str) -> String {
+    alternatives(accel)
+        .map(|a| match gtk4::accelerator_parse(a) {
+            Some((key, mods)) => gtk4::accelerator_get_label(key, mods).to_string(),
+            None => a.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// "Ctrl+H" for an action's current binding, for tooltips and the palette.
+pub fn hint(name: &str) -> Option<String> {
+    accelerator(name).map(|accel| label(&accel))
+}
+
+/// (keys, description) rows for the Shortcuts dialog.
+pub fn help_entries() -> Vec<(String, String)> {
+    let bindings = Bindings::load();
+    let mut rows: Vec<(String, String)> = FIXED_KEYS.iter().map(|(k, d)| (k.to_string(), d.to_string())).collect();
+    rows.extend(ACTIONS.iter().filter_map(|a| bindings.accelerator(a.name).map(|accel| (label(&accel), a.title.to_string()))));
+    if crate::ui::vim::is_enabled_setting() {
+        rows.extend(crate::ui::vim::HELP.iter().map(|(k, d)| (k.to_string(), d.to_string())));
+    }
+    rows
+}
+
+/// Another action already using (one of) `accel`, for the Settings warning.
+fn conflict(name: &str, accel: &str) -> Option<&'static Action> {
+    let parsed: Vec<_> = alternatives(accel).filter_map(gtk4::accelerator_parse).collect();
+    let bindings = Bindings::load();
+    ACTIONS.iter().filter(|a| a.name != name).find(|a| {
+        bindings
+            .accelerator(a.name)
+            .is_some_and(|other| alternatives(&other).filter_map(gtk4::accelerator_parse).any(|p| parsed.contains(&p)))
+    })
+}
+
+fn is_function_key(key: gtk4::gdk::Key) -> bool {
+    key.name()
+        .and_then(|name| name.strip_prefix('F').map(|n| n.parse::<u32>().is_ok()))
+        .unwrap_or(false)
+}
+
+type Handler = Rc<dyn Fn() -> bool>;
+
+/// A ShortcutController for the registry's actions. Handlers return false to
+/// let the key through (e.g. Undo with nothing to undo).
+#[derive(Clone)]
+pub struct Shortcuts {
+    controller: ShortcutController,
+    handlers: Rc<RefCell<Vec<(&'static str, Handler)>>>,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        let controller = ShortcutController::new();
+        Shortcuts { controller, handlers: Rc::new(RefCell::new(Vec::new())) }
+    }
+
+    pub fn controller(&self) -> &ShortcutController {
+        &self.controller
+    }
+
+    /// Registers a handler; call `reload` once all are added to bind their keys.
+    pub fn add<F: Fn() -> bool + 'static>(&self, name: &'static str, handler: F) {
+        debug_assert!(find(name).is_some(), "unregistered action {}", name);
+        self.handlers.borrow_mut().push((name, Rc::new(handler)));
+    }
+
+    /// Runs an action as if its shortcut was pressed (vim mode's "p", ":history").
+    pub fn activate(&self, name: &str) -> bool {
+        let handler = self.handlers.borrow().iter().find(|(n, _)| *n == name).map(|(_, h)| h.clone());
+        handler.is_some_and(|h| h())
+    }
+
+    /// Rebuilds the shortcuts from the current bindings (after adding handlers
+    /// or when a binding changed).
+    pub fn reload(&self) {
+        while let Some(shortcut) = self.controller.item(0).and_downcast::<gtk4::Shortcut>() {
+            self.controller.remove_shortcut(&shortcut);
+        }
+        let bindings = Bindings::load();
+        for (name, handler) in self.handlers.borrow().iter() {
+            let Some(accel) = bindings.accelerator(name) else {
+                continue;
+            };
+            let Some(trigger) = gtk4::ShortcutTrigger::parse_string(&accel) else {
+                log::warn!("Ignoring invalid keybinding {} = \"{}\"", name, accel);
+                continue;
+            };
+            let handler = handler.clone();
+            let action = gtk4::CallbackAction::new(move |_, _| {
+                if handler() {
+                    gtk4::glib::Propagation::Stop
+                } else {
+                    gtk4::glib::Propagation::Proceed
+                }
+            });
+            self.controller.add_shortcut(gtk4::Shortcut::new(Some(trigger), Some(action)));
+        }
+    }
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// "Keyboard Shortcuts" section for Settings: click a binding, press the new keys.
+/// Backspace unbinds, Esc cancels. Changes are applied to `shortcuts` right away.
+pub fn build_shortcuts_ui(shortcuts: &[Shortcuts]) -> GtkBox {
+    let container = GtkBox::new(Orientation::Vertical, 10);
+
+    let heading = Label::new(Some("<b>Keyboard Shortcuts</b>"));
+    heading.set_use_markup(true);
+    heading.set_halign(Align::Start);
+    container.append(&heading);
+
+    let grid = gtk4::Grid::builder().column_spacing(12).row_spacing(6).build();
+    let message = Label::new(None);
+    message.add_css_class("dim-label");
+    message.set_halign(Align::Start);
+    message.set_wrap(true);
+    message.set_visible(false);
+
+    for (i, action) in ACTIONS.iter().enumerate() {
+        let title = Label::new(Some(action.title));
+        title.set_halign(Align::Start);
+        title.set_hexpand(true);
+
+        let key_btn = Button::new();
+        key_btn.set_width_request(140);
+        let reset_btn = Button::from_icon_name("edit-undo-symbolic");
+        reset_btn.add_css_class("flat");
+        reset_btn.set_tooltip_text(Some(&format!("Reset to {}", label(action.default_accel))));
+
+        let refresh = {
+            let key_btn = key_btn.clone();
+            let reset_btn = reset_btn.clone();
+            let name = action.name;
+            move || {
+                let bindings = Bindings::load();
+                key_btn.set_label(&bindings.accelerator(name).map(|a| label(&a)).unwrap_or_else(|| "Disabled".to_string()));
+                let from_file = bindings.file.contains_key(name);
+                key_btn.set_sensitive(!from_file);
+                key_btn.set_tooltip_text(Some(if from_file { "Set in config.toml" } else { "Click, then press the new shortcut" }));
+                let customized = bindings.saved.contains_key(name);
+                reset_btn.set_visible(customized && !from_file);
+            }
+        };
+        refresh();
+
+        // Save (Some("") unbinds) or reset (None) a binding, then apply it
+        let save: Rc<dyn Fn(Option<&str>)> = {
+            let shortcuts = shortcuts.to_vec();
+            let message = message.clone();
+            let refresh = refresh.clone();
+            let name = action.name;
+            Rc::new(move |accel: Option<&str>| {
+                let Ok(store) = Store::new() else {
+                    return;
+                };
+                let result = match accel {
+                    Some(accel) => store.set_setting(&setting_key(name), accel),
+                    None => store.delete_setting(&setting_key(name)),
+                };
+                if let Err(e) = result {
+                    log::warn!("Cannot save keybinding {}: {}", name, e);
+                }
+                match accelerator(name).and_then(|a| conflict(name, &a).map(|other| (a, other))) {
+                    Some((accel, other)) => {
+                        message.set_text(&format!("{} is also used for \"{}\"", label(&accel), other.title));
+                        message.set_visible(true);
+                    }
+                    None => message.set_visible(false),
+                }
+                refresh();
+                shortcuts.iter().for_each(Shortcuts::reload);
+            })
+        };
+
+        let capture = gtk4::EventControllerKey::new();
+        let recording = Rc::new(std::cell::Cell::new(false));
+        let recording_for_key = recording.clone();
+        let save_for_key = save.clone();
+        let refresh_for_key = refresh.clone();
+        capture.connect_key_pressed(move |_, key, _, modifiers| {
+            if !recording_for_key.get() {
+                return gtk4::glib::Propagation::Proceed;
+            }
+            let modifiers = modifiers & gtk4::accelerator_get_default_mod_mask();
+            match key {
+                gtk4::gdk::Key::Escape => refresh_for_key(),
+                gtk4::gdk::Key::BackSpace if modifiers.is_empty() => save_for_key(Some("")),
+                // Wait for the actual key
+                gtk4::gdk::Key::Control_L | gtk4::gdk::Key::Control_R | gtk4::gdk::Key::Shift_L
+                | gtk4::gdk::Key::Shift_R | gtk4::gdk::Key::Alt_L | gtk4::gdk::Key::Alt_R
+                | gtk4::gdk::Key::Super_L | gtk4::gdk::Key::Super_R => return gtk4::glib::Propagation::Stop,
+                // Unmodified keys belong to typing and navigation (Tab, Enter), except F1-F12…
+                _ if modifiers.is_empty() && !is_function_key(key) => {
+                    recording_for_key.set(false);
+                    refresh_for_key();
+                    return gtk4::glib::Propagation::Proceed;
+                }
+                _ => save_for_key(Some(gtk4::accelerator_name(key, modifiers).as_str())),
+            }
+            recording_for_key.set(false);
+            gtk4::glib::Propagation::Stop
+        });
+        key_btn.add_controller(capture);
+
+        key_btn.connect_clicked(move |btn| {
+            recording.set(true);
+            btn.set_label("Press keys…");
+        });
+        reset_btn.connect_clicked(move |_| save(None));
+
+        grid.attach(&title, 0, i as i32, 1, 1);
+        grid.attach(&key_btn, 1, i as i32, 1, 1);
+        grid.attach(&reset_btn, 2, i as i32, 1, 1);
+    }
+
+    container.append(&grid);
+    container.append(&message);
+    container
+}
@@ -0,0 +1,110 @@
+use gtk4::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Label, Orientation, Revealer};
+use crate::data::store::Store;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// How long the bar stays up; the trash entry itself is kept longer (see Store)
+const UNDO_SECONDS: u32 = 10;
+
+struct Pending {
+    trash_id: i64,
+    on_restored: Box<dyn Fn()>,
+    timeout: gtk4::glib::SourceId,
+}
+
+/// "Engine deleted · Undo" bar. The action has already happened and its previous
+/// state sits in the Store trash; Undo restores it via `Store::restore_trash`.
+#[derive(Clone)]
+pub struct UndoBar {
+    revealer: Revealer,
+    label: Label,
+    pending: Rc<RefCell<Option<Pending>>>,
+}
+
+impl UndoBar {
+    pub fn new() -> Self {
+        let revealer = Revealer::builder()
+            .transition_type(gtk4::RevealerTransitionType::SlideDown)
+            .reveal_child(false)
+            .visible(false)
+            .build();
+        // Hidden when closed so it takes no spacing in the parent box
+        revealer.connect_child_revealed_notify(|r| {
+            if !r.is_child_revealed() {
+                r.set_visible(false);
+            }
+        });
+
+        let hbox = GtkBox::new(Orientation::Horizontal, 10);
+        hbox.add_css_class("card");
+        hbox.set_margin_top(4);
+        hbox.set_margin_bottom(4);
+        let label = Label::new(None);
+        label.set_hexpand(true);
+        label.set_halign(Align::Start);
+        label.set_margin_start(12);
+        label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        let undo_btn = Button::with_label("Undo");
+        undo_btn.set_tooltip_text(crate::ui::shortcuts::hint("undo").as_deref());
+        undo_btn.set_margin_top(4);
+        undo_btn.set_margin_bottom(4);
+        undo_btn.set_margin_end(4);
+        hbox.append(&label);
+        hbox.append(&undo_btn);
+        revealer.set_child(Some(&hbox));
+
+        let bar = UndoBar { revealer, label, pending: Rc::new(RefCell::new(None)) };
+        let bar_for_click = bar.clone();
+        undo_btn.connect_clicked(move |_| {
+            bar_for_click.undo();
+        });
+        bar
+    }
+
+    pub fn widget(&self) -> &Revealer {
+        &self.revealer
+    }
+
+    /// Offers to undo trash entry `trash_id`. `on_restored` refreshes whatever shows it.
+    /// A newer action replaces the previous offer, whose entry simply stays trashed.
+    pub fn show<F: Fn() + 'static>(&self, message: &str, trash_id: i64, on_restored: F) {
+        if let Some(previous) = self.pending.borrow_mut().take() {
+            previous.timeout.remove();
+        }
+
+        let pending = self.pending.clone();
+        let revealer = self.revealer.clone();
+        let timeout = gtk4::glib::timeout_add_seconds_local_once(UNDO_SECONDS, move || {
+            // The source is gone once this runs, so drop it without removing
+            pending.borrow_mut().take();
+            revealer.set_reveal_child(false);
+        });
+
+        *self.pending.borrow_mut() = Some(Pending { trash_id, on_restored: Box::new(on_restored), timeout });
+        self.label.set_text(message);
+        self.revealer.set_visible(true);
+        self.revealer.set_reveal_child(true);
+    }
+
+    /// Restores the offered item. Returns false when nothing was on offer.
+    pub fn undo(&self) -> bool {
+        let Some(pending) = self.pending.borrow_mut().take() else {
+            return false;
+        };
+        pending.timeout.remove();
+        self.revealer.set_reveal_child(false);
+
+        match Store::new().and_then(|s| s.restore_trash(pending.trash_id)) {
+            Ok(_) => (pending.on_restored)(),
+            Err(e) => log::warn!("Undo failed: {}", e),
+        }
+        true
+    }
+}
+
+impl Default for UndoBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
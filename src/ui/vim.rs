@@ -0,0 +1,213 @@
+// Optional vim-style navigation for the picker (Settings: "vim_mode"). Typing no
+// longer filters right away; in normal mode:
+//
+//   j / k    next / previous browser
+//   gg / G   first / last browser
+//   /        filter by typing; Enter keeps the filter, Esc clears it
+//   o / O    launch, or launch and keep open
+//   p        pin or unpin
+//   :        command prompt, e.g. ":engine d", ":history", ":q"
+
+use gtk4::prelude::*;
+use gtk4::gdk::Key;
+use gtk4::{Align, Box as GtkBox, Entry, Label, Orientation};
+use crate::data::store::Store;
+use std::cell::Cell;
+use std::rc::Rc;
+
+pub const SETTING: &str = "vim_mode";
+
+pub const HELP: &[(&str, &str)] = &[
+    ("j / k", "Next / Previous (vim mode)"),
+    ("g g / G", "First / Last (vim mode)"),
+    ("/", "Filter (vim mode)"),
+    ("o / O", "Launch / Launch & Keep Open (vim mode)"),
+    ("p", "Toggle Pin (vim mode)"),
+    (":", "Command, e.g. :engine d (vim mode)"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Filter,
+    Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Move(i32),
+    First,
+    Last,
+    Filter,
+    Launch { keep_open: bool },
+    TogglePin,
+    Command,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Engine(String), // Search with this engine or group keyword for this launch
+    Pin,
+    History,
+    Settings,
+    Help,
+    Quit,
+}
+
+pub fn parse_command(text: &str) -> Result<Command, String> {
+    let text = text.trim().trim_start_matches(':');
+    let (name, arg) = match text.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (text, ""),
+    };
+    let command = match name {
+        "engine" | "e" if !arg.is_empty() => Command::Engine(arg.to_string()),
+        "engine" | "e" => return Err("Usage: :engine <keyword>".to_string()),
+        "pin" => Command::Pin,
+        "history" => Command::History,
+        "settings" | "set" => Command::Settings,
+        "help" | "h" => Command::Help,
+        "q" | "quit" => Command::Quit,
+        "" => return Err("Empty command".to_string()),
+        other => return Err(format!("Not a command: {}", other)),
+    };
+    Ok(command)
+}
+
+pub fn is_enabled_setting() -> bool {
+    Store::new()
+        .ok()
+        .and_then(|s| s.get_setting(SETTING).ok().flatten())
+        .is_some_and(|v| v == "1")
+}
+
+/// Mode state plus the status line under the browser list ("-- NORMAL --", or
+/// the ":" prompt in command mode).
+#[derive(Clone)]
+pub struct Vim {
+    enabled: Rc<Cell<bool>>,
+    mode: Rc<Cell<Mode>>,
+    pending_g: Rc<Cell<bool>>,
+    bar: GtkBox,
+    mode_label: Label,
+    prompt: GtkBox,
+    command_entry: Entry,
+}
+
+impl Vim {
+    pub fn new() -> Self {
+        let bar = GtkBox::new(Orientation::Horizontal, 6);
+        bar.set_margin_start(15);
+        bar.set_margin_end(15);
+
+        let mode_label = Label::new(None);
+        mode_label.add_css_class("dim-label");
+        mode_label.add_css_class("caption");
+        mode_label.set_halign(Align::Start);
+        mode_label.set_hexpand(true);
+        bar.append(&mode_label);
+
+        let prompt = GtkBox::new(Orientation::Horizontal, 4);
+        prompt.set_hexpand(true);
+        prompt.append(&Label::new(Some(":")));
+        let command_entry = Entry::builder()
+            .placeholder_text("engine d, pin, history, settings, help, q")
+            .hexpand(true)
+            .build();
+        prompt.append(&command_entry);
+        prompt.set_visible(false);
+        bar.append(&prompt);
+
+        let vim = Vim {
+            enabled: Rc::new(Cell::new(false)),
+            mode: Rc::new(Cell::new(Mode::Normal)),
+            pending_g: Rc::new(Cell::new(false)),
+            bar,
+            mode_label,
+            prompt,
+            command_entry,
+        };
+        vim.set_enabled(is_enabled_setting());
+        vim
+    }
+
+    pub fn widget(&self) -> &GtkBox {
+        &self.bar
+    }
+
+    pub fn command_entry(&self) -> &Entry {
+        &self.command_entry
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Turns vim mode on or off for this window; Settings stores the choice.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+        self.bar.set_visible(enabled);
+        self.set_mode(Mode::Normal);
+    }
+
+    pub fn mode(&self) -> Mode {
+        if self.enabled.get() {
+            self.mode.get()
+        } else {
+            Mode::Filter // Without vim mode, typing always filters
+        }
+    }
+
+    pub fn set_mode(&self, mode: Mode) {
+        self.mode.set(mode);
+        self.pending_g.set(false);
+        self.mode_label.set_text(match mode {
+            Mode::Normal => "-- NORMAL --",
+            Mode::Filter => "-- FILTER --",
+            Mode::Command => "",
+        });
+        self.mode_label.remove_css_class("error");
+        self.mode_label.set_visible(mode != Mode::Command);
+        self.prompt.set_visible(mode == Mode::Command);
+        if mode == Mode::Command {
+            self.command_entry.set_text("");
+            self.command_entry.grab_focus();
+        }
+    }
+
+    /// Shows a command's result or error on the status line.
+    pub fn show_message(&self, text: &str, is_error: bool) {
+        self.set_mode(Mode::Normal);
+        self.mode_label.set_text(text);
+        if is_error {
+            self.mode_label.add_css_class("error");
+        }
+    }
+
+    /// Maps a normal-mode key. `g` waits for a second `g`.
+    pub fn normal_key(&self, key: Key) -> Option<Action> {
+        let after_g = self.pending_g.replace(false);
+        match key.to_unicode()? {
+            'j' => Some(Action::Move(1)),
+            'k' => Some(Action::Move(-1)),
+            'g' if after_g => Some(Action::First),
+            'g' => {
+                self.pending_g.set(true);
+                None
+            }
+            'G' => Some(Action::Last),
+            '/' => Some(Action::Filter),
+            'o' => Some(Action::Launch { keep_open: false }),
+            'O' => Some(Action::Launch { keep_open: true }),
+            'p' => Some(Action::TogglePin),
+            ':' => Some(Action::Command),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Vim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
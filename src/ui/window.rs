@@ -1,10 +1,15 @@
 use gtk4::prelude::*;
-use gtk4::{Application, ApplicationWindow, ListView, SignalListItemFactory, SingleSelection, StringList, Label, Box as GtkBox, Orientation, Align, ScrolledWindow, FilterListModel, StringFilter};
+use gtk4::{Application, ApplicationWindow, ListView, SignalListItemFactory, SingleSelection, StringList, Label, Box as GtkBox, Orientation, Align, ScrolledWindow, FilterListModel, CustomFilter, CustomSorter, SortListModel};
 use crate::data::browser_repository;
+use crate::data::config_file;
+use crate::data::source_app::SourceApp;
+use crate::data::routing;
 use gtk4::gdk;
 
 use gtk4::glib::WeakRef; 
 use crate::data::store::Store; 
+use crate::ui::fuzzy;
+use crate::ui::undo_bar::UndoBar;
 
 // Helper to update label markup
 // Helper to update label markup
@@ -12,24 +17,52 @@ fn update_label_markup(label: &Label, text: &str, query: &str, _is_pinned: bool)
     // Prefix removed, handled by icon now
     if query.is_empty() {
         label.set_markup(&gtk4::glib::markup_escape_text(text));
+    } else if let Some(m) = fuzzy::fuzzy_match(query, text) {
+        // Highlight every matched character, not just one contiguous run
+        label.set_markup(&fuzzy::highlight_markup(text, &m.positions));
     } else {
-        let query_lower = query.to_lowercase();
-        let text_lower = text.to_lowercase();
-        
-        if let Some(idx) = text_lower.find(&query_lower) {
-            let start = idx;
-            let end = start + query_lower.len();
-            
-            let before = gtk4::glib::markup_escape_text(&text[0..start]);
-            let matched = gtk4::glib::markup_escape_text(&text[start..end]);
-            let after = gtk4::glib::markup_escape_text(&text[end..]);
-            
-            let markup = format!("{}<span foreground='#f0e68c' underline='single'>{}</span>{}", before, matched, after);
-            label.set_markup(&markup);
-        } else {
-             label.set_markup(&gtk4::glib::markup_escape_text(text));
+        // Matched via id/keywords/profile rather than the visible name
+        label.set_markup(&gtk4::glib::markup_escape_text(text));
+    }
+}
+
+// Best fuzzy score for a browser across name, desktop id, keywords and profile.
+// Visible-name matches are preferred over matches on the hidden fields.
+fn browser_match_score(browser: &browser_repository::Browser, query: &str) -> Option<i64> {
+    const HIDDEN_FIELD_PENALTY: i64 = 12;
+
+    let mut best = fuzzy::fuzzy_match(query, &browser.name).map(|m| m.score);
+    let id = browser.id.trim_end_matches(".desktop");
+    let hidden = std::iter::once(id)
+        .chain(browser.keywords.iter().map(|k| k.as_str()))
+        .chain(browser.profile.as_deref());
+
+    for field in hidden {
+        if let Some(m) = fuzzy::fuzzy_match(query, field) {
+            let score = m.score - HIDDEN_FIELD_PENALTY;
+            if best.is_none_or(|b| score > b) {
+                best = Some(score);
+            }
         }
     }
+    best
+}
+
+// Ranking for a filtered list: match quality plus a small boost from usage stats.
+fn browser_rank(browser: &browser_repository::Browser, query: &str) -> Option<i64> {
+    let score = browser_match_score(browser, query)?;
+    let usage_bonus = ((browser.usage_count.max(0) as f64 + 1.0).log2() * 4.0) as i64;
+    let pin_bonus = if browser.is_pinned { 8 } else { 0 };
+    Some(score + usage_bonus + pin_bonus)
+}
+
+// Re-run filter and sorter after the query changed, and select the best match.
+fn apply_search(filter: &CustomFilter, sorter: &CustomSorter, selection: &SingleSelection) {
+    filter.changed(gtk4::FilterChange::Different);
+    sorter.changed(gtk4::SorterChange::Different);
+    if selection.n_items() > 0 {
+        selection.set_selected(0);
+    }
 }
 
 fn refresh_rows(
@@ -41,16 +74,17 @@ fn refresh_rows(
     let borrowed = rows.borrow();
     for weak in borrowed.iter() {
         if let Some(hbox) = weak.upgrade() {
-            // Structure: Icon (0), Label (1), Pin (2)
+            // Structure: Icon (0), Label (1), Badge (2), Pin (3), hidden desktop id (last)
+            let id = row_browser_id(&hbox).unwrap_or_default();
             if let Some(child1) = hbox.first_child().and_then(|w| w.next_sibling()) {
                 if let Some(label) = child1.downcast_ref::<Label>() {
                     let current_text = label.text();
                     // No prefix removal needed anymore
-                    let is_pinned = *pinned_map.get(current_text.as_str()).unwrap_or(&false);
+                    let is_pinned = *pinned_map.get(id.as_str()).unwrap_or(&false);
                     update_label_markup(label, current_text.as_str(), query, false); // false = no prefix
                     
-                    // Pin Button
-                    if let Some(pin_btn) = label.next_sibling().and_downcast::<gtk4::Button>() {
+                    // Pin Button (after the badge)
+                    if let Some(pin_btn) = label.next_sibling().and_then(|w| w.next_sibling()).and_downcast::<gtk4::Button>() {
                          if is_pinned {
                              pin_btn.add_css_class("pinned");
                          } else {
@@ -67,8 +101,166 @@ fn refresh_rows(
     *rows.borrow_mut() = live;
 }
 
+// Ends an offer of a copied link. Keeping it makes it an ordinary URL bar entry;
+// ignoring it empties the URL bar again.
+fn settle_clipboard_offer(entry: &gtk4::Entry, hint: &Label, offered: &std::cell::Cell<bool>, keep: bool) {
+    if !offered.replace(false) {
+        return;
+    }
+    hint.set_visible(false);
+    entry.remove_css_class("from-clipboard");
+    if !keep {
+        entry.set_text("");
+    }
+}
+
+// Selects and scrolls to a row, clamped to the list (vim mode's j/k/gg/G)
+fn select_row(selection: &SingleSelection, list_view: &ListView, position: i64) {
+    let n_items = selection.n_items();
+    if n_items == 0 {
+        return;
+    }
+    let position = position.clamp(0, n_items as i64 - 1) as u32;
+    selection.set_selected(position);
+    list_view.scroll_to(position, gtk4::ListScrollFlags::FOCUS, None);
+}
+
+// Desktop id of the browser a row shows, kept in its hidden last label
+fn row_browser_id(hbox: &GtkBox) -> Option<String> {
+    hbox.last_child().and_downcast::<Label>().map(|l| l.text().to_string()).filter(|id| !id.is_empty())
+}
+
+// Shows the "Suggested" badge (child 2) on the row for `suggested` (a desktop id) only
+fn refresh_badges(rows: &std::rc::Rc<std::cell::RefCell<Vec<WeakRef<GtkBox>>>>, suggested: Option<&str>) {
+    for hbox in rows.borrow().iter().filter_map(|w| w.upgrade()) {
+        let Some(label) = hbox.first_child().and_then(|w| w.next_sibling()).and_downcast::<Label>() else {
+            continue;
+        };
+        if let Some(badge) = label.next_sibling() {
+            badge.set_visible(suggested.is_some() && suggested == row_browser_id(&hbox).as_deref());
+        }
+    }
+}
+
+// Banner: "You always open <domain> in <browser>" with Always / Dismiss actions
+fn build_rule_offer(domain: &str, browser: &browser_repository::Browser, dismissed_key: String) -> GtkBox {
+    let banner = GtkBox::new(Orientation::Horizontal, 10);
+    banner.add_css_class("rule-offer");
+    banner.set_margin_start(15);
+    banner.set_margin_end(15);
+    banner.set_margin_bottom(10);
+
+    let text = format!(
+        "You usually open <b>{}</b> in <b>{}</b>.",
+        gtk4::glib::markup_escape_text(domain),
+        gtk4::glib::markup_escape_text(&browser.name)
+    );
+    let label = Label::new(None);
+    label.set_markup(&text);
+    label.set_hexpand(true);
+    label.set_halign(Align::Start);
+    label.set_wrap(true);
+    banner.append(&label);
+
+    let always_btn = gtk4::Button::with_label("Always");
+    always_btn.add_css_class("suggested-action");
+    always_btn.set_tooltip_text(Some("Create a routing rule and skip the picker for this site"));
+    let dismiss_btn = gtk4::Button::from_icon_name("window-close-symbolic");
+    dismiss_btn.add_css_class("flat");
+    dismiss_btn.set_tooltip_text(Some("Don't ask again for this site"));
+
+    let banner_weak = banner.downgrade();
+    let rule = crate::data::store::RoutingRule {
+        id: 0,
+        domain: domain.to_string(),
+        browser_id: browser.id.clone(),
+        source_app: None,
+        schedule: None,
+        requires: None,
+    };
+    always_btn.connect_clicked(move |_| {
+        if let Ok(store) = Store::new() {
+            let _ = store.add_routing_rule(&rule);
+        }
+        if let Some(b) = banner_weak.upgrade() {
+            b.set_visible(false);
+        }
+    });
 
-pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
+    let banner_weak = banner.downgrade();
+    dismiss_btn.connect_clicked(move |_| {
+        if let Ok(store) = Store::new() {
+            let _ = store.set_setting(&dismissed_key, "1");
+        }
+        if let Some(b) = banner_weak.upgrade() {
+            b.set_visible(false);
+        }
+    });
+
+    banner.append(&always_btn);
+    banner.append(&dismiss_btn);
+    banner
+}
+
+// Usage and domain stats count once per launch; every opened URL goes to history
+fn record_launches(store: &Store, browser_id: &str, urls: &[String]) {
+    let first = urls.first().map(|u| u.as_str()).unwrap_or("");
+    let _ = store.record_launch(browser_id, first, "normal");
+    for url in urls.iter().skip(1) {
+        let _ = store.add_history(url, browser_id, "normal");
+    }
+}
+
+// Launches and, once the browser really started, records the launch and takes
+// the read-later item out of the queue if its URL is among the launched ones.
+// The URL bar may have been edited since the item was picked; then it stays saved.
+fn launch_urls(browser_id: &str, urls: &[String], pending_later: &std::cell::RefCell<Option<(i64, String)>>) {
+    let url_refs: Vec<&str> = urls.iter().map(|u| u.as_str()).collect();
+    match browser_repository::launch_browser_urls(browser_id, &url_refs) {
+        Ok(()) => {
+            if let Ok(store) = Store::new() {
+                record_launches(&store, browser_id, urls);
+            }
+            let opened = pending_later.borrow().as_ref().filter(|(_, url)| urls.contains(url)).map(|(id, _)| *id);
+            if let Some(id) = opened {
+                pending_later.replace(None);
+                crate::data::read_later::mark_opened(id);
+            }
+        }
+        Err(e) => log::warn!("Cannot launch {}: {}", browser_id, e),
+    }
+}
+
+// Pins or unpins a browser and updates the rows. Unpinning goes through the
+// trash, so the undo bar can put the pin back.
+fn toggle_pin(
+    browser: &browser_repository::Browser,
+    pinned_map: &std::rc::Rc<std::cell::RefCell<std::collections::HashMap<String, bool>>>,
+    rows: &std::rc::Rc<std::cell::RefCell<Vec<WeakRef<GtkBox>>>>,
+    query: &std::rc::Rc<std::cell::RefCell<String>>,
+    undo_bar: &UndoBar,
+) {
+    let Ok(store) = Store::new() else {
+        return;
+    };
+    let was_pinned = pinned_map.borrow().get(&browser.id).copied().unwrap_or(false);
+    if was_pinned {
+        let Ok(trash_id) = store.trash_pin(&browser.id) else {
+            return;
+        };
+        let (id, pinned_map_undo, rows_undo, query_undo) = (browser.id.clone(), pinned_map.clone(), rows.clone(), query.clone());
+        undo_bar.show(&format!("Unpinned {}", browser.name), trash_id, move || {
+            pinned_map_undo.borrow_mut().insert(id.clone(), true);
+            refresh_rows(&rows_undo, &query_undo.borrow(), &pinned_map_undo.borrow());
+        });
+    } else if store.toggle_pin(&browser.id).is_err() {
+        return;
+    }
+    pinned_map.borrow_mut().insert(browser.id.clone(), !was_pinned);
+    refresh_rows(rows, &query.borrow(), &pinned_map.borrow());
+}
+
+pub fn build_ui(app: &Application, url_to_open: Option<&str>, source: Option<&SourceApp>, from_later: Option<i64>) {
     // Load CSS
     let provider = gtk4::CssProvider::new();
     // Embed CSS at compile time to ensure it is always available
@@ -163,8 +355,13 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     // Shared state
     let search_query = std::rc::Rc::new(std::cell::RefCell::new(String::new()));
     let active_rows = std::rc::Rc::new(std::cell::RefCell::new(Vec::<WeakRef<GtkBox>>::new()));
+    let undo_bar = UndoBar::new(); // Removed pins (Ctrl+Z)
     let pinned_map = std::rc::Rc::new(std::cell::RefCell::new(std::collections::HashMap::<String, bool>::new()));
+    // Rows, pins, icons and the suggestion are keyed by desktop id: names can repeat
+    let suggested_id = std::rc::Rc::new(std::cell::RefCell::new(None::<String>));
     let icon_map = std::rc::Rc::new(std::cell::RefCell::new(std::collections::HashMap::<String, String>::new()));
+    // Read-later item in the URL bar, removed from the queue once it is launched
+    let pending_later = std::rc::Rc::new(std::cell::RefCell::new(from_later.zip(url_to_open.map(str::to_string))));
 
     // Main layout container (Vertical Box)
     let vbox = GtkBox::builder()
@@ -179,6 +376,7 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         .margin_bottom(15)
         .margin_start(15)
         .margin_end(15)
+        .hexpand(true)
         .build();
     
     if let Some(u) = url_to_open {
@@ -186,7 +384,39 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         url_entry.set_position(-1);
     }
     
-    vbox.append(&url_entry);
+    // URL bar plus the share menu, added once the engines are loaded
+    let url_row = GtkBox::new(Orientation::Horizontal, 0);
+    url_row.append(&url_entry);
+    vbox.append(&url_row);
+
+    if let Some(source) = source {
+        let source_label = Label::new(Some(&format!("Opened from: {}", source.name)));
+        source_label.add_css_class("dim-label");
+        source_label.add_css_class("caption");
+        source_label.set_halign(Align::Start);
+        source_label.set_margin_start(18);
+        source_label.set_margin_bottom(10);
+        url_entry.set_margin_bottom(4);
+        source_label.set_tooltip_text(Some(&format!("Routing rules can match this app as \"{}\"", source.id)));
+        vbox.append(&source_label);
+    }
+
+    // Opened without a link (e.g. the quick-launch shortcut): a copied link is
+    // offered in the URL bar, see the end of this function
+    let clipboard_hint = Label::new(Some("From clipboard · Enter opens it, Tab keeps it, Esc ignores it"));
+    clipboard_hint.add_css_class("dim-label");
+    clipboard_hint.add_css_class("caption");
+    clipboard_hint.set_halign(Align::Start);
+    clipboard_hint.set_margin_start(18);
+    clipboard_hint.set_margin_bottom(10);
+    clipboard_hint.set_visible(false);
+    vbox.append(&clipboard_hint);
+    let clipboard_offered = std::rc::Rc::new(std::cell::Cell::new(false));
+    {
+        let (hint, offered) = (clipboard_hint.clone(), clipboard_offered.clone());
+        // Editing the offered link accepts it
+        url_entry.connect_changed(move |entry| settle_clipboard_offer(entry, &hint, &offered, true));
+    }
 
     // Sort by usage and pin status
     let store = Store::new().ok();
@@ -194,28 +424,49 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     // Cache engines for icon lookup
     let engines_cache = std::rc::Rc::new(std::cell::RefCell::new(Vec::<crate::data::store::SearchEngine>::new()));
     let default_engine_keyword = std::rc::Rc::new(std::cell::RefCell::new("g".to_string()));
+    let groups_cache = std::rc::Rc::new(std::cell::RefCell::new(Vec::<crate::data::store::EngineGroup>::new()));
     
     if let Some(ref s) = store {
-        if let Ok(list) = s.list_engines() {
+        if let Ok(list) = s.list_active_engines() {
             *engines_cache.borrow_mut() = list;
         }
-        if let Ok(Some(k)) = s.get_setting("search_engine") {
-            // Check if it's a legacy name or keyword
-             let keyword = match k.as_str() {
-                 "Google" => "g",
-                 "DuckDuckGo" => "d",
-                 "Bing" => "b",
-                 "Brave" => "br",
-                 "Ecosia" => "e",
-                 k => k,
-             };
-            *default_engine_keyword.borrow_mut() = keyword.to_string();
+        if let Ok(list) = s.list_engine_groups() {
+            *groups_cache.borrow_mut() = list;
         }
+        *default_engine_keyword.borrow_mut() = s.default_engine_keyword();
     }
     
+    // Copy/share/save menu for whatever the URL bar would open
+    {
+        let (engines, groups, def_kw) = (engines_cache.clone(), groups_cache.clone(), default_engine_keyword.clone());
+        let entry_weak = url_entry.downgrade();
+        let current_url = move || {
+            let text = entry_weak.upgrade()?.text();
+            crate::data::search::launch_targets(&text, &engines.borrow(), &groups.borrow(), &def_kw.borrow())
+                .into_iter()
+                .next()
+                .filter(|u| !u.is_empty())
+        };
+        let share_button = crate::ui::share_menu::build_share_button(window.upcast_ref(), current_url, source.map(|s| s.id.clone()));
+        share_button.set_margin_end(15);
+        url_entry.set_margin_end(4);
+        share_button.set_sensitive(!url_entry.text().trim().is_empty());
+        let share_weak = share_button.downgrade();
+        url_entry.connect_changed(move |entry| {
+            if let Some(b) = share_weak.upgrade() {
+                b.set_sensitive(!entry.text().trim().is_empty());
+            }
+        });
+        url_row.append(&share_button);
+    }
+
+    // URL Autocompletion (history URLs/domains and engine keywords)
+    let url_completion = crate::ui::completion::UrlCompletion::attach(&url_entry, engines_cache.clone(), default_engine_keyword.clone());
+
     // URL Icon Logic
     {
         let engines = engines_cache.clone();
+        let groups = groups_cache.clone();
         let def_kw = default_engine_keyword.clone();
         let entry_for_icon = url_entry.clone();
         let res_path = resource_path.clone();
@@ -258,26 +509,34 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
             }
             
             // 2. Identify Type
-            let is_url = text.contains("://") || (!text.contains(' ') && text.contains('.'));
+            let is_url = crate::data::search::looks_like_url(text);
             
             if !is_url {
                 // It is a Search
-                let parts: Vec<&str> = text.splitn(2, ' ').collect();
-                
                 // Determine effective keyword
-                let effective_keyword = if parts.len() > 1 {
-                    let potential = parts[0];
-                    if engines.borrow().iter().any(|e| e.keyword == potential) {
-                         potential.to_string()
-                    } else {
-                         def_kw.borrow().clone()
-                    }
-                } else {
-                     def_kw.borrow().clone()
+                // Bangs count anywhere in the query ("rust !gh"), keywords only as the first word
+                let effective_keyword = match crate::data::search::resolve_search(text, &engines.borrow(), &groups.borrow(), &def_kw.borrow()) {
+                    Some((crate::data::search::SearchTarget::Engine(engine), _)) => engine.keyword.clone(),
+                    // Groups show their first engine
+                    Some((crate::data::search::SearchTarget::Group(group), _)) => group.members.first().cloned().unwrap_or_default(),
+                    Some((crate::data::search::SearchTarget::Bang(bang), _)) => bang.trigger,
+                    None => def_kw.borrow().clone(),
+                };
+                
+                // Fallback mappings (also used for bangs from the bang database)
+                let fallback_icon = match effective_keyword.as_str() {
+                    "g" => "google-chrome",
+                    "d" | "ddg" => "duckduckgo",
+                    "yt" => "youtube",
+                    "gh" => "github",
+                    "b" => "bing",
+                    "br" | "brave" => "brave",
+                    "e" => "ecosia",
+                    _ => "system-search-symbolic",
                 };
                 
                 // Lookup Engine for this keyword
-                let mut icon_name = "system-search-symbolic".to_string();
+                let mut icon_name = fallback_icon.to_string();
                 
                 if let Some(engine) = engines.borrow().iter().find(|e| e.keyword == effective_keyword) {
                     if let Some(path) = &engine.icon_path {
@@ -295,18 +554,6 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                             // It's an icon name (theme)
                             icon_name = path.clone();
                         }
-                    } else {
-                         // Fallback mappings
-                         icon_name = match engine.keyword.as_str() {
-                             "g" => "google-chrome".to_string(), 
-                             "d" => "duckduckgo".to_string(), 
-                             "yt" => "youtube".to_string(),
-                             "gh" => "github".to_string(),
-                             "b" => "bing".to_string(),
-                             "br" => "brave".to_string(),
-                             "e" => "ecosia".to_string(), 
-                             _ => "system-search-symbolic".to_string(),
-                         };
                     }
                 }
                 
@@ -359,16 +606,27 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
              
              let sort_mode = s.get_setting("sort_order").ok().flatten().unwrap_or("freq".to_string());
              
+             // Browsers whose preferred hours are now (e.g. a work profile during office hours)
+             let scheduled: std::collections::HashSet<String> = s
+                 .list_browser_schedules()
+                 .unwrap_or_default()
+                 .into_iter()
+                 .filter(|(_, schedule)| crate::data::schedule::is_active(schedule))
+                 .map(|(id, _)| id)
+                 .collect();
+             
              // First pass: update is_pinned in struct
              for browser in &mut browsers {
-                 if let Some((_, pinned, _)) = stat_map.get(&browser.id) {
+                 if let Some((count, pinned, _)) = stat_map.get(&browser.id) {
                      browser.is_pinned = *pinned;
+                     browser.usage_count = *count;
                  }
              }
              
              browsers.sort_by(|a, b| {
                  // Pin status first (true > false)
                  b.is_pinned.cmp(&a.is_pinned)
+                     .then_with(|| scheduled.contains(&b.id).cmp(&scheduled.contains(&a.id)))
                      .then_with(|| {
                          match sort_mode.as_str() {
                              "recent" => {
@@ -391,40 +649,152 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         }
     }
     
+    // Per-domain suggestion: the browser most often used for this URL's domain
+    let domain_suggestion = match (&store, url_to_open) {
+        (Some(s), Some(u)) => routing::suggest_for_url(s, u),
+        _ => None,
+    };
+    let suggested_browser = domain_suggestion
+        .as_ref()
+        .and_then(|sugg| browsers.iter().find(|b| b.id == sugg.browser_id))
+        .cloned();
+    *suggested_id.borrow_mut() = suggested_browser.as_ref().map(|b| b.id.clone());
+
     // Populate maps
     {
         let mut p_map = pinned_map.borrow_mut();
         let mut i_map = icon_map.borrow_mut();
         for b in &browsers {
-            p_map.insert(b.name.clone(), b.is_pinned);
-            i_map.insert(b.name.clone(), b.icon.clone());
+            p_map.insert(b.id.clone(), b.is_pinned);
+            i_map.insert(b.id.clone(), b.icon.clone());
         }
     }
 
     let browsers_rc = std::rc::Rc::new(browsers);
-    let string_list = StringList::new(&browsers_rc.iter().map(|b| b.name.as_str()).collect::<Vec<&str>>());
-    
-    // Search Filter
-    let filter = StringFilter::builder()
-        .match_mode(gtk4::StringFilterMatchMode::Substring)
-        .ignore_case(true)
-        .build();
-    
-    // Bind search entry to filter (Implicitly via variable now)
-    filter.set_expression(Some(gtk4::PropertyExpression::new(
-        gtk4::StringObject::static_type(),
-        None::<&gtk4::Expression>,
-        "string",
-    )));
+    let string_list = StringList::new(&browsers_rc.iter().map(|b| b.id.as_str()).collect::<Vec<&str>>());
     
+    // Search Filter (fuzzy, over name, desktop id, keywords and profile)
+    let browsers_for_filter = browsers_rc.clone();
+    let query_for_filter = search_query.clone();
+    let filter = CustomFilter::new(move |obj| {
+        let Some(id) = obj.downcast_ref::<gtk4::StringObject>().map(|s| s.string()) else {
+            return false;
+        };
+        let Some(browser) = browsers_for_filter.iter().find(|b| b.id == id.as_str()) else {
+            return false;
+        };
+        // Checked here rather than when loading so config.toml reloads apply
+        if config_file::is_hidden(&browser.id) {
+            return false;
+        }
+        let query = query_for_filter.borrow();
+        query.is_empty() || browser_match_score(browser, &query).is_some()
+    });
+
     let filter_model = FilterListModel::builder()
         .model(&string_list)
         .filter(&filter)
-        .incremental(true)
         .build();
 
-    let selection_model = SingleSelection::new(Some(filter_model));
+    // Rank filtered results; with no query keep the pin/usage order computed above
+    let browsers_for_sorter = browsers_rc.clone();
+    let query_for_sorter = search_query.clone();
+    let sorter = CustomSorter::new(move |a, b| {
+        let position = |obj: &gtk4::glib::Object| {
+            let id = obj.downcast_ref::<gtk4::StringObject>().map(|s| s.string()).unwrap_or_default();
+            browsers_for_sorter.iter().position(|br| br.id == id.as_str()).unwrap_or(usize::MAX)
+        };
+        let (pos_a, pos_b) = (position(a), position(b));
+
+        let query = query_for_sorter.borrow();
+        let rank = |pos: usize| {
+            if query.is_empty() {
+                return 0;
+            }
+            browsers_for_sorter.get(pos).and_then(|br| browser_rank(br, &query)).unwrap_or(i64::MIN)
+        };
+
+        rank(pos_b).cmp(&rank(pos_a))
+            .then_with(|| pos_a.cmp(&pos_b))
+            .into()
+    });
+
+    let sort_model = SortListModel::new(Some(filter_model), Some(sorter.clone()));
+
+    let selection_model = SingleSelection::new(Some(sort_model));
     selection_model.set_autoselect(true); 
+    
+    // Preselect the suggested browser for this domain
+    if let Some(ref suggested) = suggested_browser {
+        let position = (0..selection_model.n_items()).find(|&i| {
+            selection_model
+                .item(i)
+                .and_downcast::<gtk4::StringObject>()
+                .is_some_and(|s| s.string() == suggested.id.as_str())
+        });
+        if let Some(pos) = position {
+            selection_model.set_selected(pos);
+        }
+    }
+
+    // Engine preferred browser: "yt cats" preselects and badges the browser set on the yt engine
+    let domain_suggested = std::rc::Rc::new(std::cell::RefCell::new(suggested_browser.as_ref().map(|b| b.id.clone())));
+    let update_suggestion: std::rc::Rc<dyn Fn()> = {
+        let engines = engines_cache.clone();
+        let browsers = browsers_rc.clone();
+        let suggested_id = suggested_id.clone();
+        let domain_suggested = domain_suggested.clone();
+        let rows = active_rows.clone();
+        let selection_weak = selection_model.downgrade();
+        let entry_for_engine = url_entry.downgrade();
+
+        let update_engine_browser = move || {
+            let Some(entry) = entry_for_engine.upgrade() else {
+                return;
+            };
+            let engine_browser = crate::data::search::keyword_engine(&entry.text(), &engines.borrow())
+                .and_then(|e| e.browser_id.clone())
+                .filter(|id| browsers.iter().any(|b| &b.id == id));
+            let target = engine_browser.clone().or_else(|| domain_suggested.borrow().clone());
+            if *suggested_id.borrow() == target {
+                return;
+            }
+            *suggested_id.borrow_mut() = target;
+            refresh_badges(&rows, suggested_id.borrow().as_deref());
+
+            if let (Some(id), Some(selection)) = (engine_browser, selection_weak.upgrade()) {
+                let position = (0..selection.n_items()).find(|&i| {
+                    selection
+                        .item(i)
+                        .and_downcast::<gtk4::StringObject>()
+                        .is_some_and(|s| s.string() == id.as_str())
+                });
+                if let Some(pos) = position {
+                    selection.set_selected(pos);
+                }
+            }
+        };
+        std::rc::Rc::new(update_engine_browser)
+    };
+    update_suggestion();
+    let update_for_entry = update_suggestion.clone();
+    url_entry.connect_changed(move |_| update_for_entry());
+
+    // After usage stats are reset, or the reset undone: the domain suggestion follows them
+    let refresh_after_stats: std::rc::Rc<dyn Fn()> = {
+        let browsers = browsers_rc.clone();
+        let url = url_to_open.map(str::to_string);
+        std::rc::Rc::new(move || {
+            let suggestion = match (Store::new(), url.as_deref()) {
+                (Ok(s), Some(u)) => routing::suggest_for_url(&s, u),
+                _ => None,
+            };
+            *domain_suggested.borrow_mut() = suggestion
+                .map(|sugg| sugg.browser_id)
+                .filter(|id| browsers.iter().any(|b| &b.id == id));
+            update_suggestion();
+        })
+    };
 
     let factory = SignalListItemFactory::new();
 
@@ -432,17 +802,24 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     let active_rows_for_bind = active_rows.clone();
     let pinned_map_for_bind = pinned_map.clone();
     let icon_map_for_bind = icon_map.clone();
+    let suggested_id_for_bind = suggested_id.clone();
+    let browsers_for_bind = browsers_rc.clone();
 
     // Context for GestureClick
     let browsers_for_click = browsers_rc.clone();
     let url_entry_weak_click = url_entry.downgrade();
+    let engines_for_click = engines_cache.clone();
+    let groups_for_click = groups_cache.clone();
+    let def_kw_for_click = default_engine_keyword.clone();
     let window_weak_click = window.downgrade();
+    let pending_later_click = pending_later.clone();
     
     // Clones for Setup (Pin Button)
     let browsers_setup = browsers_rc.clone();
     let pinned_map_setup = pinned_map.clone();
     let active_rows_setup = active_rows.clone();
     let search_query_setup = search_query.clone();
+    let undo_bar_setup = undo_bar.clone();
     
     // factory.connect_setup
     factory.connect_setup(move |_, list_item| {
@@ -460,6 +837,12 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         label.set_hexpand(true);
         label.set_use_markup(true);
         
+        // "Suggested" badge for the browser usually used with this domain
+        let badge = Label::new(Some("Suggested"));
+        badge.add_css_class("suggested-badge");
+        badge.set_valign(Align::Center);
+        badge.set_visible(false);
+        
         // Pin Button (Button instead of Image)
         let pin_btn = gtk4::Button::builder()
             .icon_name("view-pin-symbolic")
@@ -473,6 +856,7 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         let pinned_map_pin = pinned_map_setup.clone();
         let active_rows_pin = active_rows_setup.clone(); 
         let search_query_pin = search_query_setup.clone();
+        let undo_bar_pin = undo_bar_setup.clone();
         
         pin_btn.connect_clicked(move |btn| {
              // Avoid row activation by stopping propagation? Button does this naturally.
@@ -483,18 +867,10 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                   if let Some(hbox) = btn.parent().and_then(|p| p.downcast::<GtkBox>().ok()) {
                        if let Some(last) = hbox.last_child() {
                            if let Some(lbl) = last.downcast_ref::<Label>() {
-                               let name = lbl.text();
-                               if !name.is_empty() {
-                                   if let Some(browser) = browsers_pin.iter().find(|b| b.name == name.as_str()) {
-                                        if let Ok(store) = Store::new() {
-                                            if let Ok(new_state) = store.toggle_pin(&browser.id) {
-                                                pinned_map_pin.borrow_mut().insert(browser.name.clone(), new_state);
-                                                
-                                                // Refresh Rows
-                                                let query = search_query_pin.borrow();
-                                                refresh_rows(&active_rows_pin, &query, &pinned_map_pin.borrow());
-                                            }
-                                        }
+                               let id = lbl.text();
+                               if !id.is_empty() {
+                                   if let Some(browser) = browsers_pin.iter().find(|b| b.id == id.as_str()) {
+                                        toggle_pin(browser, &pinned_map_pin, &active_rows_pin, &search_query_pin, &undo_bar_pin);
                                    }
                                }
                            }
@@ -505,9 +881,10 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         
         hbox.append(&icon);
         hbox.append(&label);
+        hbox.append(&badge);
         hbox.append(&pin_btn);
         
-        // Hidden Label for Data Transfer
+        // Hidden Label for Data Transfer: the browser's desktop id
         let hidden_label = Label::new(None);
         hidden_label.set_visible(false);
         hbox.append(&hidden_label);
@@ -516,7 +893,11 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         let gesture = gtk4::GestureClick::new();
         let browsers_inner = browsers_for_click.clone();
         let url_inner = url_entry_weak_click.clone();
+        let engines_inner = engines_for_click.clone();
+        let groups_inner = groups_for_click.clone();
+        let def_kw_inner = def_kw_for_click.clone();
         let win_inner = window_weak_click.clone();
+        let pending_later_inner = pending_later_click.clone();
         
         gesture.connect_released(move |gesture, _, _, _| {
              let modifiers = gesture.current_event().map(|e| e.modifier_state()).unwrap_or_else(gtk4::gdk::ModifierType::empty);
@@ -529,21 +910,18 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
              if let Some(hbox) = widget.downcast_ref::<GtkBox>() {
                  if let Some(last_child) = hbox.last_child() {
                      if let Some(lbl) = last_child.downcast_ref::<Label>() {
-                         let name = lbl.text();
-                         if !name.is_empty() {
-                             if let Some(browser) = browsers_inner.iter().find(|b| b.name == name.as_str()) {
-                                 let target_url = if let Some(entry) = url_inner.upgrade() {
-                                     entry.text().to_string()
+                         let id = lbl.text();
+                         if !id.is_empty() {
+                             if let Some(browser) = browsers_inner.iter().find(|b| b.id == id.as_str()) {
+                                 // Searches (keywords, bangs, groups) resolve to the engines' URLs
+                                 let target_urls = if let Some(entry) = url_inner.upgrade() {
+                                     crate::data::search::launch_targets(&entry.text(), &engines_inner.borrow(), &groups_inner.borrow(), &def_kw_inner.borrow())
                                  } else {
-                                     String::new()
+                                     Vec::new()
                                  };
 
-                                 // Increment usage
-                                 if let Ok(store) = crate::data::store::Store::new() {
-                                     let _ = store.increment_usage(&browser.id);
-                                 }
-                                 // Launch
-                                 let _ = browser_repository::launch_browser(&browser.id, &target_url);
+                                 // Launch; usage (browser and domain) counts once it started
+                                 launch_urls(&browser.id, &target_urls, &pending_later_inner);
 
                                  if let Some(win) = win_inner.upgrade() {
                                      if !keep_open {
@@ -579,13 +957,14 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         // Track the row
         active_rows_for_bind.borrow_mut().push(hbox.downgrade());
         
-        let name = string_object.string();
-        let name_str = name.as_str();
+        let id = string_object.string();
+        let id_str = id.as_str();
+        let name_str = browsers_for_bind.iter().find(|b| b.id == id_str).map_or(id_str, |b| b.name.as_str());
 
         // Update Hidden Label for Gesture
         if let Some(last_child) = hbox.last_child() {
             if let Some(lbl) = last_child.downcast_ref::<Label>() {
-                lbl.set_text(name_str);
+                lbl.set_text(id_str);
             }
         }
         
@@ -594,10 +973,12 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         let p_map = pinned_map_for_bind.borrow();
         let i_map = icon_map_for_bind.borrow();
         
-        let is_pinned = *p_map.get(name_str).unwrap_or(&false);
-        let icon_name = i_map.get(name_str).cloned().unwrap_or_else(|| "web-browser".to_string());
+        let is_pinned = *p_map.get(id_str).unwrap_or(&false);
+        let icon_name = i_map.get(id_str).cloned().unwrap_or_else(|| "web-browser".to_string());
         
-        // 0: Icon, 1: Label, 2: Pin Button
+        let is_suggested = suggested_id_for_bind.borrow().as_deref() == Some(id_str);
+        
+        // 0: Icon, 1: Label, 2: Badge, 3: Pin Button
         if let Some(child0) = hbox.first_child() {
              // Icon Logic
              if let Some(icon) = child0.downcast_ref::<gtk4::Image>() {
@@ -628,7 +1009,12 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                  if let Some(label) = child1.downcast_ref::<Label>() {
                      update_label_markup(&label, name_str, &query, is_pinned); 
                      
-                     if let Some(pin_btn) = child1.next_sibling().and_downcast::<gtk4::Button>() {
+                     let badge = child1.next_sibling();
+                     if let Some(badge) = badge.as_ref() {
+                         badge.set_visible(is_suggested);
+                     }
+                     
+                     if let Some(pin_btn) = badge.and_then(|w| w.next_sibling()).and_downcast::<gtk4::Button>() {
                          // Pin Button Logic
                          if is_pinned {
                              pin_btn.add_css_class("pinned");
@@ -655,8 +1041,58 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
 
     vbox.append(&scrolled_window);
 
+    // Vim mode status line and ":" prompt, hidden unless enabled
+    let vim = crate::ui::vim::Vim::new();
+    vbox.append(vim.widget());
+
+    undo_bar.widget().set_margin_start(15);
+    undo_bar.widget().set_margin_end(15);
+    vbox.append(undo_bar.widget());
+
+    // Offer a permanent routing rule when a domain is always opened in the same browser
+    if let (Some(sugg), Some(browser), Some(s)) = (&domain_suggestion, &suggested_browser, &store) {
+        let dismissed_key = format!("rule_offer_dismissed.{}", sugg.domain);
+        let dismissed = s.get_setting(&dismissed_key).ok().flatten().is_some();
+        let has_rule = s
+            .list_active_routing_rules()
+            .map(|rules| rules.iter().any(|r| routing::domain_matches(&r.domain, &sugg.domain)))
+            .unwrap_or(false);
+
+        if sugg.is_consistent() && !dismissed && !has_rule {
+            let banner = build_rule_offer(&sugg.domain, browser, dismissed_key);
+            vbox.insert_child_after(&banner, Some(&url_row));
+        }
+    }
+
+    // A rule would have routed this link but its machine conditions don't hold,
+    // e.g. *.corp needs the VPN and it is down: say why the picker appeared
+    if let (Some(u), Some(s)) = (url_to_open, &store) {
+        let rules = s.list_active_routing_rules().unwrap_or_default();
+        if let Some((rule, reason)) = routing::blocked_rule(&rules, u, source) {
+            let browser_name = browsers_rc
+                .iter()
+                .find(|b| b.id == rule.browser_id)
+                .map(|b| b.name.clone())
+                .unwrap_or_else(|| rule.browser_id.clone());
+            let subject = if rule.domain.is_empty() { "These links".to_string() } else { format!("Links to {}", rule.domain) };
+            let warning = Label::new(Some(&format!("{} usually open in {}, but {}", subject, browser_name, reason)));
+            warning.add_css_class("rule-offer");
+            warning.add_css_class("warning");
+            warning.set_wrap(true);
+            warning.set_xalign(0.0);
+            warning.set_margin_start(15);
+            warning.set_margin_end(15);
+            warning.set_margin_bottom(10);
+            warning.set_tooltip_text(rule.requires.as_deref());
+            vbox.insert_child_after(&warning, Some(&url_row));
+        }
+    }
+
 
     
+    // Registered below once the buttons they trigger exist
+    let shortcuts = crate::ui::shortcuts::Shortcuts::new();
+
     // Status Bar
     let status_box = GtkBox::new(Orientation::Horizontal, 10);
     status_box.set_margin_bottom(10);
@@ -699,18 +1135,8 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
             vbox.set_margin_start(20);
             vbox.set_margin_end(20);
             
-            let shortcuts = [
-                ("Type", "Search Browsers"),
-                ("Ctrl + L", "Focus URL Bar"),
-                ("Up/Down Arrows", "Navigation"),
-                ("Enter / Click", "Launch Selected"),
-                ("Ctrl + Enter", "Launch & Keep Open"),
-                ("Ctrl + Click", "Launch & Keep Open"),
-                ("Ctrl + P", "Toggle Pin"),
-                ("Ctrl + S", "Settings"),
-                ("Ctrl + ?", "Shortcuts (Help)"),
-                ("Esc", "Close / Clear Search"),
-            ];
+            // Same registry the key handling uses, so this list can't drift
+            let shortcuts = crate::ui::shortcuts::help_entries();
             
             let grid = gtk4::Grid::builder()
                 .column_spacing(20)
@@ -721,7 +1147,7 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
 
             for (i, (key, desc)) in shortcuts.iter().enumerate() {
                 let key_label = Label::new(None);
-                key_label.set_markup(&format!("<b>{}</b>", key));
+                key_label.set_markup(&format!("<b>{}</b>", gtk4::glib::markup_escape_text(key)));
                 key_label.set_halign(Align::Start);
                 
                 let desc_label = Label::new(Some(desc));
@@ -747,6 +1173,72 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     
     status_box.append(&help_btn);
     
+    // History Button
+    let history_btn = gtk4::Button::builder()
+        .icon_name("document-open-recent-symbolic")
+        .has_frame(false)
+        .tooltip_text("History")
+        .build();
+    
+    let window_weak_for_history = window.downgrade();
+    let browsers_for_history = browsers_rc.clone();
+    let url_entry_weak_history = url_entry.downgrade();
+    let list_view_weak_history = list_view.downgrade();
+    let pending_later_for_history = pending_later.clone();
+    history_btn.connect_clicked(move |_| {
+        if let Some(parent) = window_weak_for_history.upgrade() {
+            let url_entry_weak = url_entry_weak_history.clone();
+            let list_view_weak = list_view_weak_history.clone();
+            let pending_later = pending_later_for_history.clone();
+            // Picked URL goes into the URL bar, then any browser can be chosen from the list
+            crate::ui::history_dialog::show_history_dialog(parent.upcast_ref(), browsers_for_history.clone(), move |url| {
+                if let Some(entry) = url_entry_weak.upgrade() {
+                    entry.set_text(url);
+                    entry.set_position(-1);
+                }
+                pending_later.replace(None); // Replaced a read-later link, if any
+                if let Some(lv) = list_view_weak.upgrade() {
+                    lv.grab_focus();
+                }
+            });
+        }
+    });
+    
+    status_box.append(&history_btn);
+
+    // Read Later Button
+    let later_btn = gtk4::Button::builder()
+        .icon_name("bookmark-new-symbolic")
+        .has_frame(false)
+        .tooltip_text("Read Later")
+        .build();
+
+    let window_weak_for_later = window.downgrade();
+    let browsers_for_later = browsers_rc.clone();
+    let url_entry_weak_later = url_entry.downgrade();
+    let list_view_weak_later = list_view.downgrade();
+    let pending_later_for_dialog = pending_later.clone();
+    later_btn.connect_clicked(move |_| {
+        if let Some(parent) = window_weak_for_later.upgrade() {
+            let url_entry_weak = url_entry_weak_later.clone();
+            let list_view_weak = list_view_weak_later.clone();
+            let pending_later = pending_later_for_dialog.clone();
+            // Same as history: the saved link goes into the URL bar to pick a browser
+            crate::ui::later_dialog::show_later_dialog(parent.upcast_ref(), browsers_for_later.clone(), move |id, url| {
+                if let Some(entry) = url_entry_weak.upgrade() {
+                    entry.set_text(url);
+                    entry.set_position(-1);
+                }
+                pending_later.replace(Some((id, url.to_string())));
+                if let Some(lv) = list_view_weak.upgrade() {
+                    lv.grab_focus();
+                }
+            });
+        }
+    });
+
+    status_box.append(&later_btn);
+    
     // Settings Button
     let settings_btn = gtk4::Button::builder()
         .icon_name("emblem-system-symbolic")
@@ -756,6 +1248,12 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     
     let window_weak_for_settings = window.downgrade();
     let resource_path_for_settings = resource_path.clone();
+    let engines_for_settings = engines_cache.clone();
+    let groups_for_settings = groups_cache.clone();
+    let default_kw_for_settings = default_engine_keyword.clone();
+    let shortcuts_for_settings = shortcuts.clone();
+    let vim_for_settings = vim.clone();
+    let refresh_for_settings = refresh_after_stats.clone();
     settings_btn.connect_clicked(move |_| {
         if let Some(parent) = window_weak_for_settings.upgrade() {
             let dialog = gtk4::Window::builder()
@@ -783,6 +1281,15 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
             vbox.set_margin_bottom(20);
             vbox.set_margin_start(20);
             vbox.set_margin_end(20);
+
+            // Undo for engine deletions and stats resets made in this dialog
+            let undo_bar = crate::ui::undo_bar::UndoBar::new();
+            vbox.append(undo_bar.widget());
+            let undo_for_key = undo_bar.clone();
+            let dialog_shortcuts = crate::ui::shortcuts::Shortcuts::new();
+            dialog_shortcuts.add("undo", move || undo_for_key.undo());
+            dialog_shortcuts.reload();
+            dialog.add_controller(dialog_shortcuts.controller().clone());
             
             // App Logo
             let logo_path = resource_path_for_settings.join("app-icon.png");
@@ -862,13 +1369,20 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
             
             // Logic for Reset Click
             let dropdown_clone = dropdown.clone();
+            let undo_for_reset = undo_bar.clone();
+            let refresh_for_reset = refresh_for_settings.clone();
             reset_btn.connect_clicked(move |_| {
                 let idx = dropdown_clone.selected();
                 if let Ok(store) = Store::new() {
-                     match idx {
-                         1 => { let _ = store.reset_recent_stats(); },
-                         2 => { let _ = store.reset_frequent_stats(); },
-                         _ => {}
+                     let reset = match idx {
+                         1 => store.reset_recent_stats().map(|id| ("Recent usage reset", id)),
+                         2 => store.reset_frequent_stats().map(|id| ("Usage counts reset", id)),
+                         _ => return,
+                     };
+                     if let Ok((message, trash_id)) = reset {
+                         refresh_for_reset();
+                         let refresh = refresh_for_reset.clone();
+                         undo_for_reset.show(message, trash_id, move || refresh());
                      }
                 }
             });
@@ -878,14 +1392,135 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
             
             vbox.append(&sort_box);
             
+            // Live Search Suggestions (opt-in: typed queries are sent to the engine)
+            let suggest_check = gtk4::CheckButton::with_label("Show live search suggestions from the search engine");
+            suggest_check.set_tooltip_text(Some("Queries typed in the URL bar are sent to the engine's suggestion service"));
+            let suggest_enabled = Store::new()
+                .ok()
+                .and_then(|s| s.get_setting("search_suggestions").ok().flatten())
+                .is_some_and(|v| v == "1");
+            suggest_check.set_active(suggest_enabled);
+            suggest_check.connect_toggled(|check| {
+                if let Ok(store) = Store::new() {
+                    let _ = store.set_setting("search_suggestions", if check.is_active() { "1" } else { "0" });
+                }
+            });
+            vbox.append(&suggest_check);
+
+            // Vim-style navigation, applied to the open picker right away
+            let vim_check = gtk4::CheckButton::with_label("Vim-style navigation (j/k, /, o, :engine …)");
+            vim_check.set_tooltip_text(Some("Typing no longer filters directly; press / to filter"));
+            vim_check.set_active(vim_for_settings.is_enabled());
+            let vim_for_toggle = vim_for_settings.clone();
+            vim_check.connect_toggled(move |check| {
+                if let Ok(store) = Store::new() {
+                    let _ = store.set_setting(crate::ui::vim::SETTING, if check.is_active() { "1" } else { "0" });
+                }
+                vim_for_toggle.set_enabled(check.is_active());
+            });
+            vbox.append(&vim_check);
+
+            // Copied links offered when opened without one
+            let clipboard_check = gtk4::CheckButton::with_label("Offer a copied link when opened without one");
+            clipboard_check.set_active(
+                Store::new()
+                    .ok()
+                    .and_then(|s| s.get_setting("clipboard_offer").ok().flatten())
+                    .is_none_or(|v| v != "0"),
+            );
+            clipboard_check.connect_toggled(|check| {
+                if let Ok(store) = Store::new() {
+                    let _ = store.set_setting("clipboard_offer", if check.is_active() { "1" } else { "0" });
+                }
+            });
+            vbox.append(&clipboard_check);
+
+            // Link titles for "Copy as Markdown/HTML" and "Save for Later"; requests the page
+            let titles_check = gtk4::CheckButton::with_label("Fetch page titles for copied and saved links");
+            titles_check.set_tooltip_text(Some("Requests the page even though it isn't opened. Off: the host and path are used."));
+            titles_check.set_active(crate::data::share::fetch_titles_enabled());
+            titles_check.connect_toggled(|check| {
+                if let Ok(store) = Store::new() {
+                    let _ = store.set_setting(crate::data::share::FETCH_TITLES_SETTING, if check.is_active() { "1" } else { "0" });
+                }
+            });
+            vbox.append(&titles_check);
+            
+            // Bangs ("!w query"): bundled list, optionally replaced by DuckDuckGo's full list
+            let bangs_row = GtkBox::new(Orientation::Horizontal, 10);
+            let bangs_label = Label::new(Some(&format!("Bangs: {} available (e.g. \"!w rust\")", crate::data::bangs::count())));
+            bangs_label.set_hexpand(true);
+            bangs_label.set_halign(gtk4::Align::Start);
+            let bangs_btn = gtk4::Button::with_label("Update Bangs");
+            bangs_btn.set_tooltip_text(Some(&format!("Download the full bang list from {}", crate::data::bangs::BANG_SOURCE_URL)));
+            bangs_row.append(&bangs_label);
+            bangs_row.append(&bangs_btn);
+            vbox.append(&bangs_row);
+            
+            bangs_btn.connect_clicked(move |btn| {
+                btn.set_sensitive(false);
+                bangs_label.set_text("Updating bangs…");
+                let handle = gtk4::gio::spawn_blocking(crate::data::bangs::update);
+                let btn = btn.clone();
+                let bangs_label = bangs_label.clone();
+                gtk4::glib::spawn_future_local(async move {
+                    match handle.await {
+                        Ok(Ok(count)) => bangs_label.set_text(&format!("Bangs: {} available (updated)", count)),
+                        Ok(Err(e)) => bangs_label.set_text(&format!("Bang update failed: {}", e)),
+                        Err(_) => bangs_label.set_text("Bang update failed"),
+                    }
+                    btn.set_sensitive(true);
+                });
+            });
+            
+            // Portable configuration file. An import changes what this dialog shows,
+            // so reload the caches and close it.
+            let engines = engines_for_settings.clone();
+            let groups = groups_for_settings.clone();
+            let default_kw = default_kw_for_settings.clone();
+            let dialog_weak_import = dialog.downgrade();
+            let config_ui = crate::ui::config_dialog::build_config_transfer_ui(move || {
+                if let Ok(store) = Store::new() {
+                    *engines.borrow_mut() = store.list_active_engines().unwrap_or_default();
+                    *groups.borrow_mut() = store.list_engine_groups().unwrap_or_default();
+                    *default_kw.borrow_mut() = store.default_engine_keyword();
+                }
+                if let Some(d) = dialog_weak_import.upgrade() {
+                    d.close();
+                }
+            });
+            vbox.append(&config_ui);
+
+            // Declarative config.toml, layered over everything configured here
+            let config_file_label = Label::new(Some(&config_file::summary()));
+            config_file_label.add_css_class("dim-label");
+            config_file_label.set_halign(gtk4::Align::Start);
+            config_file_label.set_wrap(true);
+            config_file_label.set_selectable(true);
+            vbox.append(&config_file_label);
+            
             // Separator
             vbox.append(&gtk4::Separator::new(Orientation::Horizontal));
        
             // Embed Search Engine Management UI
-            let engines_ui = crate::ui::engines_dialog::build_engine_management_ui();
+            let engines_ui = crate::ui::engines_dialog::build_engine_management_ui(&undo_bar);
             engines_ui.set_vexpand(true);
             vbox.append(&engines_ui);
 
+            // Separator
+            vbox.append(&gtk4::Separator::new(Orientation::Horizontal));
+
+            // Routing Rules (domain -> browser, skips the picker)
+            let rules_ui = crate::ui::rules_dialog::build_rules_management_ui();
+            vbox.append(&rules_ui);
+
+            // Separator
+            vbox.append(&gtk4::Separator::new(Orientation::Horizontal));
+
+            // Rebinding applies to the picker and this dialog right away
+            let shortcuts_ui = crate::ui::shortcuts::build_shortcuts_ui(&[shortcuts_for_settings.clone(), dialog_shortcuts.clone()]);
+            vbox.append(&shortcuts_ui);
+
             // Separator
             vbox.append(&gtk4::Separator::new(Orientation::Horizontal));
             
@@ -946,19 +1581,56 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     let selection_model_weak = selection_model.downgrade();
     let window_weak = window.downgrade();
     let url_entry_weak_2 = url_entry.downgrade();
+    let engines_for_key = engines_cache.clone();
+    let groups_for_key = groups_cache.clone();
+    let def_kw_for_key = default_engine_keyword.clone();
+    let pending_later_for_key = pending_later.clone();
     let search_query_clone = search_query.clone();
     let active_rows_clone = active_rows.clone();
     let pinned_map_clone = pinned_map.clone();
     let filter_weak = filter.downgrade();
+    let sorter_weak = sorter.downgrade();
     let list_view_weak = list_view.downgrade();
     
-    // Buttons for shortcuts
-    let help_btn_weak = help_btn.downgrade();
-    let settings_btn_weak = settings_btn.downgrade();
+    let completion_for_key = url_completion.clone();
+    let vim_for_key = vim.clone();
+    let shortcuts_for_key = shortcuts.clone();
+    let clipboard_hint_for_key = clipboard_hint.clone();
+    let clipboard_offered_for_key = clipboard_offered.clone();
+    let shortcut_mods = gtk4::gdk::ModifierType::CONTROL_MASK | gtk4::gdk::ModifierType::ALT_MASK | gtk4::gdk::ModifierType::SUPER_MASK;
 
     key_controller.connect_key_pressed(move |_controller, key, _keycode, modifiers| {
+        // The vim ":" prompt takes all keys; Esc leaves it
+        if vim_for_key.mode() == crate::ui::vim::Mode::Command {
+            if key == gtk4::gdk::Key::Escape {
+                vim_for_key.set_mode(crate::ui::vim::Mode::Normal);
+                if let Some(lv) = list_view_weak.upgrade() {
+                    lv.grab_focus();
+                }
+                return gtk4::glib::Propagation::Stop;
+            }
+            return gtk4::glib::Propagation::Proceed;
+        }
+
         // Handle Esc globally (Highest priority)
         if key == gtk4::gdk::Key::Escape {
+            // First Esc only closes the URL suggestions
+            if completion_for_key.is_open() {
+                completion_for_key.hide();
+                return gtk4::glib::Propagation::Stop;
+            }
+            // Esc ignores a link offered from the clipboard
+            if clipboard_offered_for_key.get() {
+                if let Some(entry) = url_entry_weak_2.upgrade() {
+                    settle_clipboard_offer(&entry, &clipboard_hint_for_key, &clipboard_offered_for_key, false);
+                }
+                return gtk4::glib::Propagation::Stop;
+            }
+            // In vim mode, Esc leaves filtering (clearing the filter) before it closes
+            let leaving_filter = vim_for_key.is_enabled() && vim_for_key.mode() == crate::ui::vim::Mode::Filter;
+            if leaving_filter {
+                vim_for_key.set_mode(crate::ui::vim::Mode::Normal);
+            }
             if let Some(window) = window_weak.upgrade() {
                 let should_stop = {
                     let mut query = search_query_clone.borrow_mut();
@@ -971,23 +1643,35 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                 };
 
                 if should_stop {
-                    if let Some(f) = filter_weak.upgrade() {
-                        f.set_search(None::<&str>);
+                    if let (Some(f), Some(s), Some(sel)) = (filter_weak.upgrade(), sorter_weak.upgrade(), selection_model_weak.upgrade()) {
+                        apply_search(&f, &s, &sel);
                         // Refresh labels to clear markup
                         refresh_rows(&active_rows_clone, "", &pinned_map_clone.borrow());
                     }
                     return gtk4::glib::Propagation::Stop;
                 }
+                if leaving_filter {
+                    return gtk4::glib::Propagation::Stop;
+                }
                 
                 window.close();
                 return gtk4::glib::Propagation::Stop;
             }
         }
 
+        // Tab keeps a link offered from the clipboard
+        if key == gtk4::gdk::Key::Tab && clipboard_offered_for_key.get() {
+            if let Some(entry) = url_entry_weak_2.upgrade() {
+                settle_clipboard_offer(&entry, &clipboard_hint_for_key, &clipboard_offered_for_key, true);
+            }
+            return gtk4::glib::Propagation::Stop;
+        }
+
         // Check focus to avoid eating URL entry inputs
 
         // "has_focus()" on Entry might return false if internal Text widget has focus
         // We must check if the focused widget is the entry or a child of it.
+        let mut from_url_entry = false;
         if let Some(window) = window_weak.upgrade() {
             if let Some(focus_widget) = gtk4::prelude::GtkWindowExt::focus(&window) {
                 if let Some(entry) = url_entry_weak_2.upgrade() {
@@ -1000,6 +1684,7 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                         
                         if key == gtk4::gdk::Key::Return || key == gtk4::gdk::Key::KP_Enter {
                             // Fallthrough to launch logic
+                            from_url_entry = true;
                         } else {
                              return gtk4::glib::Propagation::Proceed;
                         }
@@ -1018,61 +1703,56 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
         // Handle Shortcuts
         if let Some(window) = window_weak.upgrade() {
             // Esc handled at top
+            // Ctrl/Alt shortcuts are left to the ShortcutController (see shortcuts.rs)
+            if modifiers.intersects(shortcut_mods) && key != gtk4::gdk::Key::Return && key != gtk4::gdk::Key::KP_Enter {
+                return gtk4::glib::Propagation::Proceed;
+            }
 
+            // Enter launches; Ctrl+Enter (or vim's O) keeps the picker open
+            let is_enter = key == gtk4::gdk::Key::Return || key == gtk4::gdk::Key::KP_Enter;
+            let mut launch_request = is_enter.then(|| modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK));
 
-            // Ctrl + L (Focus URL Bar)
-            if key == gtk4::gdk::Key::l && modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
-                if let Some(entry) = url_entry_weak_2.upgrade() {
-                    entry.grab_focus();
-                    entry.select_region(0, -1);
-                }
-                return gtk4::glib::Propagation::Stop;
-            }
-            
-            // Ctrl+P for Pinning
-            if key == gtk4::gdk::Key::p && modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
-                 if let Some(selection_model) = selection_model_weak.upgrade() {
-                    if let Some(item) = selection_model.selected_item() {
-                        let string_object = item.downcast::<gtk4::StringObject>().unwrap();
-                        let name = string_object.string();
-                        
-                        // Find browser ID
-                        if let Some(browser) = browsers_for_key.iter().find(|b| b.name == name) {
-                             if let Ok(store) = Store::new() {
-                                 if let Ok(new_state) = store.toggle_pin(&browser.id) {
-                                     // Update map
-                                     pinned_map_clone.borrow_mut().insert(browser.name.clone(), new_state);
-                                     
-                                     // Refresh labels immediately to show/hide pin
-                                     refresh_rows(&active_rows_clone, &search_query_clone.borrow(), &pinned_map_clone.borrow());
-                                 }
-                             }
+            if vim_for_key.is_enabled() && !from_url_entry {
+                use crate::ui::vim::{Action, Mode};
+                match vim_for_key.mode() {
+                    // Enter ends filtering and keeps the filter, like a vim search
+                    Mode::Filter if is_enter => {
+                        vim_for_key.set_mode(Mode::Normal);
+                        return gtk4::glib::Propagation::Stop;
+                    }
+                    Mode::Filter if key == gtk4::gdk::Key::BackSpace && search_query_clone.borrow().is_empty() => {
+                        vim_for_key.set_mode(Mode::Normal);
+                        return gtk4::glib::Propagation::Stop;
+                    }
+                    Mode::Normal if !is_enter && key != gtk4::gdk::Key::BackSpace => {
+                        let (Some(sel), Some(lv)) = (selection_model_weak.upgrade(), list_view_weak.upgrade()) else {
+                            return gtk4::glib::Propagation::Stop;
+                        };
+                        let current = match sel.selected() {
+                            gtk4::INVALID_LIST_POSITION => -1,
+                            position => position as i64,
+                        };
+                        match vim_for_key.normal_key(key) {
+                            Some(Action::Move(delta)) => select_row(&sel, &lv, current + delta as i64),
+                            Some(Action::First) => select_row(&sel, &lv, 0),
+                            Some(Action::Last) => select_row(&sel, &lv, i64::MAX),
+                            Some(Action::Filter) => vim_for_key.set_mode(Mode::Filter),
+                            Some(Action::Launch { keep_open }) => launch_request = Some(keep_open),
+                            Some(Action::TogglePin) => {
+                                shortcuts_for_key.activate("toggle-pin");
+                            }
+                            Some(Action::Command) => vim_for_key.set_mode(Mode::Command),
+                            // Other keys do nothing in normal mode, arrows still navigate
+                            None if key.to_unicode().is_some_and(|c| !c.is_control()) => {}
+                            None => return gtk4::glib::Propagation::Proceed,
+                        }
+                        if launch_request.is_none() {
+                            return gtk4::glib::Propagation::Stop;
                         }
                     }
-                 }
-                 return gtk4::glib::Propagation::Stop;
-            }
-            
-            // Ctrl + S (Settings)
-            if key == gtk4::gdk::Key::s && modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
-                if let Some(btn) = settings_btn_weak.upgrade() {
-                    btn.emit_clicked();
-                }
-                return gtk4::glib::Propagation::Stop;
-            }
-
-            // Ctrl + ? (Help)
-            // Note: '?' is usually Shift+/, so modifiers might contain Shift. 
-            // We check for Key::question OR (Key::slash + Ctrl).
-            // Actually, simplified check:
-            if (key == gtk4::gdk::Key::question || key == gtk4::gdk::Key::slash) 
-                && modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
-                if let Some(btn) = help_btn_weak.upgrade() {
-                    btn.emit_clicked();
+                    _ => {}
                 }
-                return gtk4::glib::Propagation::Stop;
             }
-            
 
             if key == gtk4::gdk::Key::BackSpace {
                 let new_state = {
@@ -1086,9 +1766,8 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                 };
                 
                 if let Some(q) = new_state {
-                    if let Some(f) = filter_weak.upgrade() {
-                        let s = if q.is_empty() { None } else { Some(q.as_str()) };
-                        f.set_search(s);
+                    if let (Some(f), Some(s), Some(sel)) = (filter_weak.upgrade(), sorter_weak.upgrade(), selection_model_weak.upgrade()) {
+                        apply_search(&f, &s, &sel);
                         refresh_rows(&active_rows_clone, &q, &pinned_map_clone.borrow());
                     }
                     if let Some(lv) = list_view_weak.upgrade() {
@@ -1099,27 +1778,23 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                 return gtk4::glib::Propagation::Stop;
             }
             
-            if key == gtk4::gdk::Key::Return || key == gtk4::gdk::Key::KP_Enter {
+            if let Some(keep_open) = launch_request {
                 // Launch logic
                 if let Some(selection_model) = selection_model_weak.upgrade() {
                     if let Some(item) = selection_model.selected_item() {
                         let string_object = item.downcast::<gtk4::StringObject>().unwrap();
-                        let name = string_object.string();
+                        let id = string_object.string();
                         
-                        if let Some(browser) = browsers_for_key.iter().find(|b| b.name == name) {
-                             let target_url = if let Some(entry) = url_entry_weak_2.upgrade() {
-                                 entry.text().to_string()
+                        if let Some(browser) = browsers_for_key.iter().find(|b| b.id == id) {
+                             let target_urls = if let Some(entry) = url_entry_weak_2.upgrade() {
+                                 crate::data::search::launch_targets(&entry.text(), &engines_for_key.borrow(), &groups_for_key.borrow(), &def_kw_for_key.borrow())
                              } else {
-                                 String::new()
+                                 Vec::new()
                              };
 
-                             if let Ok(store) = crate::data::store::Store::new() {
-                                 let _ = store.increment_usage(&browser.id);
-                             }
-
-                             let _ = browser_repository::launch_browser(&browser.id, &target_url);
+                             launch_urls(&browser.id, &target_urls, &pending_later_for_key);
 
-                             if !modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
+                             if !keep_open {
                                  window.close();
                              } else {
                                  let window_weak_for_timeout = window.downgrade();
@@ -1149,8 +1824,8 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
                 query.clone()
             };
             
-            if let Some(f) = filter_weak.upgrade() {
-                 f.set_search(Some(query_str.as_str()));
+            if let (Some(f), Some(s), Some(sel)) = (filter_weak.upgrade(), sorter_weak.upgrade(), selection_model_weak.upgrade()) {
+                 apply_search(&f, &s, &sel);
                  refresh_rows(&active_rows_clone, &query_str, &pinned_map_clone.borrow());
             }
             
@@ -1167,6 +1842,168 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     });
     window.add_controller(key_controller);
 
+    // Configurable shortcuts (Ctrl+L, Ctrl+P, ...), handled after the focused widget
+    let url_entry_weak_sc = url_entry.downgrade();
+    shortcuts.add("focus-url", move || {
+        if let Some(entry) = url_entry_weak_sc.upgrade() {
+            entry.grab_focus();
+            entry.select_region(0, -1);
+        }
+        true
+    });
+    let selection_model_weak_sc = selection_model.downgrade();
+    let browsers_sc = browsers_rc.clone();
+    let pinned_map_sc = pinned_map.clone();
+    let active_rows_sc = active_rows.clone();
+    let search_query_sc = search_query.clone();
+    let undo_bar_sc = undo_bar.clone();
+    shortcuts.add("toggle-pin", move || {
+        let selected = selection_model_weak_sc
+            .upgrade()
+            .and_then(|sel| sel.selected_item())
+            .and_downcast::<gtk4::StringObject>()
+            .map(|s| s.string());
+        if let Some(browser) = selected.and_then(|id| browsers_sc.iter().find(|b| b.id == id)) {
+            toggle_pin(browser, &pinned_map_sc, &active_rows_sc, &search_query_sc, &undo_bar_sc);
+        }
+        true
+    });
+    let undo_bar_sc = undo_bar.clone();
+    shortcuts.add("undo", move || undo_bar_sc.undo());
+    for (name, button) in [("history", &history_btn), ("read-later", &later_btn), ("settings", &settings_btn), ("help", &help_btn)] {
+        let button_weak = button.downgrade();
+        shortcuts.add(name, move || {
+            if let Some(btn) = button_weak.upgrade() {
+                btn.emit_clicked();
+            }
+            true
+        });
+    }
+    // Ctrl+D: keep the link in the read-later queue instead of choosing a browser now
+    let window_weak_later = window.downgrade();
+    let url_entry_weak_save = url_entry.downgrade();
+    let engines_save = engines_cache.clone();
+    let groups_save = groups_cache.clone();
+    let def_kw_save = default_engine_keyword.clone();
+    let source_id_save = source.map(|s| s.id.clone());
+    shortcuts.add("save-later", move || {
+        let (Some(window), Some(entry)) = (window_weak_later.upgrade(), url_entry_weak_save.upgrade()) else {
+            return false;
+        };
+        let targets = crate::data::search::launch_targets(&entry.text(), &engines_save.borrow(), &groups_save.borrow(), &def_kw_save.borrow());
+        let Some(url) = targets.first().filter(|u| !u.is_empty()) else {
+            return false;
+        };
+        crate::ui::share_menu::run(crate::ui::share_menu::ShareAction::ReadLater, window.upcast_ref(), url, source_id_save.as_deref(), || {});
+        true
+    });
+
+    // Command palette: every action, run against the selected browser and the URL bar
+    let window_weak_palette = window.downgrade();
+    let selection_model_weak_palette = selection_model.downgrade();
+    let url_entry_weak_palette = url_entry.downgrade();
+    let browsers_palette = browsers_rc.clone();
+    let engines_palette = engines_cache.clone();
+    let groups_palette = groups_cache.clone();
+    let def_kw_palette = default_engine_keyword.clone();
+    let pinned_map_palette = pinned_map.clone();
+    let undo_bar_palette = undo_bar.clone();
+    let refresh_palette = refresh_after_stats.clone();
+    let source_id_palette = source.map(|s| s.id.clone());
+    let toggle_pin_palette: std::rc::Rc<dyn Fn(&browser_repository::Browser)> = {
+        let (pinned_map, rows, query, undo_bar) = (pinned_map.clone(), active_rows.clone(), search_query.clone(), undo_bar.clone());
+        std::rc::Rc::new(move |browser: &browser_repository::Browser| toggle_pin(browser, &pinned_map, &rows, &query, &undo_bar))
+    };
+    let buttons_palette = (history_btn.downgrade(), later_btn.downgrade(), settings_btn.downgrade(), help_btn.downgrade());
+    shortcuts.add("palette", move || {
+        let Some(window) = window_weak_palette.upgrade() else {
+            return false;
+        };
+        let selected = selection_model_weak_palette
+            .upgrade()
+            .and_then(|sel| sel.selected_item())
+            .and_downcast::<gtk4::StringObject>()
+            .and_then(|s| browsers_palette.iter().find(|b| b.id == s.string()).cloned());
+        let pinned = selected.as_ref().is_some_and(|b| pinned_map_palette.borrow().get(&b.id).copied().unwrap_or(false));
+        let text = url_entry_weak_palette.upgrade().map(|e| e.text().to_string()).unwrap_or_default();
+        let targets = crate::data::search::launch_targets(&text, &engines_palette.borrow(), &groups_palette.borrow(), &def_kw_palette.borrow());
+        let (history_btn, later_btn, settings_btn, help_btn) = buttons_palette.clone();
+        let entries = crate::ui::palette::build_entries(crate::ui::palette::PaletteContext {
+            window: window.clone().upcast(),
+            selected,
+            pinned,
+            targets,
+            engines: engines_palette.borrow().clone(),
+            groups: groups_palette.borrow().clone(),
+            default_keyword: def_kw_palette.clone(),
+            source_id: source_id_palette.clone(),
+            history_btn,
+            later_btn,
+            settings_btn,
+            help_btn,
+            toggle_pin: toggle_pin_palette.clone(),
+            undo_bar: undo_bar_palette.clone(),
+            refresh_stats: refresh_palette.clone(),
+        });
+        crate::ui::palette::show_palette(window.upcast_ref(), entries);
+        true
+    });
+    shortcuts.reload();
+    window.add_controller(shortcuts.controller().clone());
+
+    // Vim ":" commands
+    let vim_for_command = vim.clone();
+    let shortcuts_for_command = shortcuts.clone();
+    let engines_for_command = engines_cache.clone();
+    let groups_for_command = groups_cache.clone();
+    let def_kw_for_command = default_engine_keyword.clone();
+    let window_weak_command = window.downgrade();
+    let list_view_weak_command = list_view.downgrade();
+    vim.command_entry().connect_activate(move |entry| {
+        use crate::ui::vim::Command;
+        let command = crate::ui::vim::parse_command(&entry.text());
+        if let Some(lv) = list_view_weak_command.upgrade() {
+            lv.grab_focus();
+        }
+        vim_for_command.set_mode(crate::ui::vim::Mode::Normal);
+        match command {
+            Ok(Command::Engine(keyword)) => {
+                // Groups work too: resolve_search falls back to a default group
+                let name = engines_for_command
+                    .borrow()
+                    .iter()
+                    .find(|e| e.keyword == keyword)
+                    .map(|e| e.name.clone())
+                    .or_else(|| groups_for_command.borrow().iter().find(|g| g.keyword == keyword).map(|g| g.name.clone()));
+                match name {
+                    Some(name) => {
+                        *def_kw_for_command.borrow_mut() = keyword;
+                        vim_for_command.show_message(&format!("Searching with {}", name), false);
+                    }
+                    None => vim_for_command.show_message(&format!("No engine or group with keyword \"{}\"", keyword), true),
+                }
+            }
+            Ok(Command::Pin) => {
+                shortcuts_for_command.activate("toggle-pin");
+            }
+            Ok(Command::History) => {
+                shortcuts_for_command.activate("history");
+            }
+            Ok(Command::Settings) => {
+                shortcuts_for_command.activate("settings");
+            }
+            Ok(Command::Help) => {
+                shortcuts_for_command.activate("help");
+            }
+            Ok(Command::Quit) => {
+                if let Some(window) = window_weak_command.upgrade() {
+                    window.close();
+                }
+            }
+            Err(e) => vim_for_command.show_message(&e, true),
+        }
+    });
+
     // URL Entry Key Controller (Down Arrow to List)
     let list_view_weak = list_view.downgrade();
     let entry_controller = gtk4::EventControllerKey::new();
@@ -1181,8 +2018,65 @@ pub fn build_ui(app: &Application, url_to_open: Option<&str>) {
     });
     url_entry.add_controller(entry_controller);
 
+    // Reload config.toml when it changes: engines, default engine, hidden browsers and keybindings
+    let config_file_handle = gtk4::gio::File::for_path(config_file::config_path());
+    match config_file_handle.monitor_file(gtk4::gio::FileMonitorFlags::WATCH_MOVES, None::<&gtk4::gio::Cancellable>) {
+        Ok(monitor) => {
+            let engines = engines_cache.clone();
+            let default_kw = default_engine_keyword.clone();
+            let filter_weak = filter.downgrade();
+            let shortcuts = shortcuts.clone();
+            monitor.connect_changed(move |_, _, _, event| {
+                if event == gtk4::gio::FileMonitorEvent::Changed || !config_file::reload() {
+                    return; // Wait for ChangesDoneHint; ignore no-op rewrites
+                }
+                log::info!("Reloaded {}", config_file::config_path().display());
+                if let Ok(store) = Store::new() {
+                    *engines.borrow_mut() = store.list_active_engines().unwrap_or_default();
+                    *default_kw.borrow_mut() = store.default_engine_keyword();
+                }
+                if let Some(f) = filter_weak.upgrade() {
+                    f.changed(gtk4::FilterChange::Different);
+                }
+                shortcuts.reload();
+            });
+            // The monitor stops when dropped, so keep it until the window goes away
+            window.connect_destroy(move |_| {
+                monitor.cancel();
+            });
+        }
+        Err(e) => log::warn!("Cannot watch {}: {}", config_file::config_path().display(), e),
+    }
+
     window.present();
     
     // Focus list by default so typing searches
     list_view.grab_focus();
+
+    // Offer a copied link; read after presenting, as some compositors only
+    // allow clipboard access to a focused window
+    let clipboard_enabled = store
+        .as_ref()
+        .and_then(|s| s.get_setting("clipboard_offer").ok().flatten())
+        .is_none_or(|v| v != "0");
+    if url_to_open.is_none() && clipboard_enabled {
+        let clipboard = window.clipboard();
+        let url_entry = url_entry.clone();
+        gtk4::glib::spawn_future_local(async move {
+            let Ok(Some(text)) = clipboard.read_text_future().await else {
+                return;
+            };
+            let Some(url) = crate::data::search::clipboard_url(&text) else {
+                return;
+            };
+            // The user may have started typing meanwhile
+            if !url_entry.text().is_empty() {
+                return;
+            }
+            url_entry.set_text(&url);
+            url_entry.add_css_class("from-clipboard");
+            clipboard_hint.set_visible(true);
+            clipboard_offered.set(true);
+        });
+    }
 }